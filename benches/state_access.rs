@@ -0,0 +1,63 @@
+use std::{
+  hint::black_box,
+  time::{Duration, Instant},
+};
+
+use witer::prelude::*;
+
+/*
+  Measures the cost of reading window state from another thread while the main
+  thread keeps iterating under `Flow::Poll`, which is when the state locks see the
+  most contention. Run with `cargo bench --bench state_access`.
+*/
+
+const CALLS: u32 = 1_000_000;
+
+fn main() {
+  let window = Window::builder()
+    .with_title("state access benchmark")
+    .with_flow(Flow::Poll)
+    .with_visibility(Visibility::Hidden)
+    .build()
+    .unwrap();
+
+  let reader = {
+    let window = window.clone();
+    std::thread::spawn(move || {
+      let results = [
+        measure("is_closing", || black_box(window.is_closing())),
+        measure("inner_size", || black_box(window.inner_size())),
+        measure("key", || black_box(window.key(Key::Space))),
+        measure("request_redraw", || window.request_redraw()),
+        measure("title", || black_box(window.title())),
+      ];
+      window.close();
+      results
+    })
+  };
+
+  let start = Instant::now();
+  let mut messages = 0u64;
+  for message in &window {
+    black_box(message);
+    messages += 1;
+  }
+  let elapsed = start.elapsed();
+
+  for (name, per_call) in reader.join().unwrap() {
+    println!("{name:<16} {:>8.1} ns/call", per_call.as_secs_f64() * 1e9);
+  }
+  println!(
+    "{:<16} {:>8.0} messages/s",
+    "main thread",
+    messages as f64 / elapsed.as_secs_f64()
+  );
+}
+
+fn measure<T>(name: &'static str, mut f: impl FnMut() -> T) -> (&'static str, Duration) {
+  let start = Instant::now();
+  for _ in 0..CALLS {
+    f();
+  }
+  (name, start.elapsed() / CALLS)
+}
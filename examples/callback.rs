@@ -0,0 +1,26 @@
+use witer::prelude::*;
+
+mod common;
+
+/*
+  This example showcases driving the window with a callback
+  instead of iterating over it, closing when Escape is pressed.
+*/
+
+fn main() {
+  common::init_log(env!("CARGO_CRATE_NAME"));
+
+  let window = Window::builder()
+    .with_title("Press Esc to close!")
+    .build()
+    .unwrap();
+
+  window.run(|window, message| {
+    if let Message::Key {
+      key: Key::Escape, ..
+    } = message
+    {
+      window.close();
+    }
+  });
+}
@@ -89,7 +89,7 @@ fn app_loop(
           Some(Message::Resized(new_size)) => {
             app.resize(*new_size);
           }
-          Some(Message::Loop(LoopMessage::Exit)) => break,
+          Some(Message::Loop(LoopMessage::Exit(_))) => break,
           _ => (),
         }
 
@@ -132,7 +132,7 @@ impl App {
         ..Default::default()
       });
 
-      let surface = instance.create_surface(window.clone()).unwrap();
+      let surface = window.create_wgpu_surface(&instance).unwrap();
 
       let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
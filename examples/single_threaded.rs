@@ -39,7 +39,7 @@ fn main() -> Result<(), WindowError> {
   let mut app = App::new(window.clone());
 
   for message in &window {
-    // if let Message::Loop(LoopMessage::Exit) = &message {
+    // if let Message::Loop(LoopMessage::Exit(_)) = &message {
     //   tracing::info!("exit");
     // } else {
     //   tracing::debug!("idc");
@@ -146,7 +146,7 @@ impl App {
         ..Default::default()
       });
 
-      let surface = instance.create_surface(window.clone()).unwrap();
+      let surface = window.create_wgpu_surface(&instance).unwrap();
 
       let adapter = instance
         .request_adapter(&wgpu::RequestAdapterOptions {
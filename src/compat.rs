@@ -3,3 +3,6 @@ pub mod egui;
 
 #[cfg(feature = "opengl")]
 pub mod opengl;
+
+#[cfg(feature = "wgpu")]
+pub mod wgpu;
@@ -24,7 +24,9 @@ use egui::{
 pub use window_settings::WindowSettings;
 
 use self::window::message::{CursorMoveKind, Focus};
-use crate::{prelude::*, raw_window_handle::HasDisplayHandle};
+use crate::prelude::*;
+#[cfg(feature = "rwh_06")]
+use crate::raw_window_handle::HasDisplayHandle;
 
 pub mod clipboard;
 mod window_settings;
@@ -102,7 +104,8 @@ impl State {
   pub fn new(
     egui_ctx: egui::Context,
     viewport_id: ViewportId,
-    #[cfg(feature = "rwh_05")] display_target: &dyn rwh_05::HasRawDisplayHandle,
+    #[cfg(all(feature = "rwh_05", not(feature = "rwh_06")))]
+    display_target: &dyn rwh_05::HasRawDisplayHandle,
     #[cfg(feature = "rwh_06")] display_target: &dyn HasDisplayHandle,
     native_pixels_per_point: Option<f32>,
     max_texture_side: Option<usize>,
@@ -113,7 +116,7 @@ impl State {
     };
 
     let rdh = {
-      #[cfg(feature = "rwh_05")]
+      #[cfg(all(feature = "rwh_05", not(feature = "rwh_06")))]
       {
         use rwh_05::HasRawDisplayHandle;
         Some(display_target.raw_display_handle())
@@ -268,8 +271,8 @@ impl State {
           consumed: self.egui_ctx.wants_pointer_input(),
         }
       }
-      Message::MouseWheel { delta_x, delta_y } => {
-        self.on_mouse_wheel(window, *delta_x, *delta_y);
+      Message::MouseWheel { delta, .. } => {
+        self.on_mouse_wheel(window, *delta);
         EventResponse {
           repaint: true,
           consumed: self.egui_ctx.wants_pointer_input(),
@@ -349,8 +352,17 @@ impl State {
       //     consumed: self.egui_ctx.wants_keyboard_input(),
       //   }
       // }
-      Message::Key { key, state, .. } => {
-        self.on_keyboard_input(key, state);
+      Message::Key {
+        key,
+        physical_key,
+        state,
+        during_composition,
+        ..
+      } => {
+        // keystrokes consumed by the IME arrive as text once composed
+        if !during_composition {
+          self.on_keyboard_input(key, physical_key, state);
+        }
         // When pressing the Tab key, egui focuses the first focusable element, hence
         // Tab always consumes.
         let consumed = self.egui_ctx.wants_keyboard_input() || key == &Key::Tab;
@@ -410,12 +422,12 @@ impl State {
       //     consumed: false,
       //   }
       // }
-      Message::ModifiersChanged { shift, ctrl, alt, win } => {
-        self.egui_input.modifiers.alt = alt.is_pressed();
-        self.egui_input.modifiers.ctrl = ctrl.is_pressed();
-        self.egui_input.modifiers.shift = shift.is_pressed();
+      Message::ModifiersChanged(modifiers) => {
+        self.egui_input.modifiers.alt = modifiers.alt.is_pressed();
+        self.egui_input.modifiers.ctrl = modifiers.ctrl.is_pressed();
+        self.egui_input.modifiers.shift = modifiers.shift.is_pressed();
         self.egui_input.modifiers.mac_cmd = false;
-        self.egui_input.modifiers.command = ctrl.is_pressed();
+        self.egui_input.modifiers.command = modifiers.ctrl.is_pressed();
 
         EventResponse {
           repaint: true,
@@ -427,7 +439,9 @@ impl State {
       Message::Paint
       // | WindowEvent::Occluded(_)
       | Message::Resized(_)
+      | Message::Resizing(_)
       | Message::Moved(_)
+      | Message::TrayIcon { .. }
       // | WindowEvent::ThemeChanged(_)
       // | WindowEvent::TouchpadPressure { .. }
       | Message::CloseRequested => EventResponse {
@@ -441,7 +455,44 @@ impl State {
         Message::Created { .. } |
         Message::BoundsChanged { .. } |
         Message::Command |
-        Message::SystemCommand => EventResponse {
+        Message::SystemCommand(_) |
+        Message::Hotkey { .. } |
+        Message::KeyboardLayoutChanged(_) |
+        Message::CursorIconChanged(_) |
+        Message::CursorModeLost |
+        Message::CursorModeRestored |
+        Message::CommandCompleted(_) |
+        Message::Gesture(_) |
+        Message::Joystick(_) |
+        Message::User(_) |
+        Message::Timer(_) |
+        Message::UserIdle(_) |
+        Message::UserActive |
+        Message::Pointer(_) |
+        Message::ClipboardUpdated |
+        Message::Suspended |
+        Message::Resumed |
+        Message::PowerStatusChanged(_) |
+        Message::SessionLocked |
+        Message::SessionUnlocked |
+        Message::JumpTask(_) |
+        Message::HighContrastChanged(_) |
+        Message::AnimationsChanged(_) |
+        Message::TransparencyChanged(_) |
+        Message::AccentColorChanged(_) |
+        Message::Panic(_) |
+        Message::Error(_) |
+        Message::Custom(_) => EventResponse {
+        repaint: false,
+        consumed: false,
+      },
+      #[cfg(feature = "gamepad")]
+      Message::Gamepad(_) => EventResponse {
+        repaint: false,
+        consumed: false,
+      },
+      #[cfg(feature = "audio")]
+      Message::Audio(_) => EventResponse {
         repaint: false,
         consumed: false,
       },
@@ -613,14 +664,16 @@ impl State {
   //   }
   // }
 
-  fn on_mouse_wheel(&mut self, window: &Window, delta_x: f32, delta_y: f32) {
+  fn on_mouse_wheel(&mut self, window: &Window, delta: ScrollDelta) {
     let pixels_per_point = pixels_per_point(&self.egui_ctx, window);
 
     {
-      let (unit, delta) = (
-        egui::MouseWheelUnit::Point,
-        egui::vec2(delta_x as f32, delta_y as f32) / pixels_per_point,
-      );
+      let (unit, delta) = match delta {
+        ScrollDelta::Lines { x, y } => (egui::MouseWheelUnit::Line, egui::vec2(x, y)),
+        ScrollDelta::Pixels { x, y } => {
+          (egui::MouseWheelUnit::Point, egui::vec2(x, y) / pixels_per_point)
+        }
+      };
       let modifiers = self.egui_input.modifiers;
       self.egui_input.events.push(egui::Event::MouseWheel {
         unit,
@@ -629,7 +682,13 @@ impl State {
       });
     }
 
-    let delta = egui::vec2(delta_x as f32, delta_y as f32) / pixels_per_point;
+    let delta = match delta {
+      ScrollDelta::Lines { x, y } => {
+        let points_per_scroll_line = 50.0;
+        egui::vec2(x, y) * points_per_scroll_line
+      }
+      ScrollDelta::Pixels { x, y } => egui::vec2(x, y) / pixels_per_point,
+    };
 
     if self.egui_input.modifiers.ctrl || self.egui_input.modifiers.command {
       // Treat as zoom instead:
@@ -647,12 +706,16 @@ impl State {
     }
   }
 
-  fn on_keyboard_input(&mut self, key: &Key, state: &KeyState) {
+  fn on_keyboard_input(
+    &mut self,
+    key: &Key,
+    physical_key: &PhysicalKey,
+    state: &KeyState,
+  ) {
     let pressed = state.is_pressed();
 
-    let physical_key = key_from_key_code(key);
-    let logical_key = physical_key;
-    // let logical_key = key_from_winit_key(key);
+    let physical_key = physical_key.key().as_ref().and_then(key_from_key_code);
+    let logical_key = key_from_key_code(key);
 
     if let Some(logical_key) = logical_key {
       if pressed {
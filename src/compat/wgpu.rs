@@ -0,0 +1,42 @@
+//! Surface creation for [`wgpu`], without going through raw window handles.
+
+use std::num::NonZeroIsize;
+
+pub use wgpu;
+use wgpu::rwh;
+
+use crate::{error::WindowError, Window};
+
+/// Hands wgpu handles in the `raw-window-handle` version it was built with,
+/// whichever version the crate itself is built for. Holds a clone of the
+/// window, so the window outlives the surface.
+struct SurfaceTarget(Window);
+
+impl rwh::HasWindowHandle for SurfaceTarget {
+  fn window_handle(&self) -> Result<rwh::WindowHandle<'_>, rwh::HandleError> {
+    let hwnd =
+      NonZeroIsize::new(self.0 .0.hwnd.0).ok_or(rwh::HandleError::Unavailable)?;
+    let mut handle = rwh::Win32WindowHandle::new(hwnd);
+    handle.hinstance = NonZeroIsize::new(self.0 .0.hinstance.0);
+    Ok(unsafe { rwh::WindowHandle::borrow_raw(handle.into()) })
+  }
+}
+
+impl rwh::HasDisplayHandle for SurfaceTarget {
+  fn display_handle(&self) -> Result<rwh::DisplayHandle<'_>, rwh::HandleError> {
+    Ok(rwh::DisplayHandle::windows())
+  }
+}
+
+impl Window {
+  /// Create a surface for rendering to the window with `instance`. The surface
+  /// keeps the window open until it is dropped.
+  pub fn create_wgpu_surface(
+    &self,
+    instance: &wgpu::Instance,
+  ) -> Result<wgpu::Surface<'static>, WindowError> {
+    instance
+      .create_surface(SurfaceTarget(self.clone()))
+      .map_err(|e| WindowError::Error(e.to_string()))
+  }
+}
@@ -1,4 +1,4 @@
-use std::io;
+use std::{io, ops::Deref, sync::Arc};
 
 use thiserror::Error;
 
@@ -12,6 +12,46 @@ pub enum WindowError {
   IOError(#[from] io::Error),
   #[error("{0}")]
   Win32Error(#[from] windows::core::Error),
+  #[error("window thread message queue is full")]
+  QueueFull,
+}
+
+/// A [`WindowError`] which happened while applying a setter or handling a
+/// window message, sent with [`Message::Error`](crate::Message::Error).
+/// Cloning is cheap, and errors are only equal to clones of themselves.
+#[derive(Debug, Clone)]
+pub struct SharedError(Arc<WindowError>);
+
+impl SharedError {
+  pub fn error(&self) -> &WindowError {
+    &self.0
+  }
+}
+
+impl From<WindowError> for SharedError {
+  fn from(error: WindowError) -> Self {
+    Self(Arc::new(error))
+  }
+}
+
+impl Deref for SharedError {
+  type Target = WindowError;
+
+  fn deref(&self) -> &Self::Target {
+    &self.0
+  }
+}
+
+impl std::fmt::Display for SharedError {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    self.0.fmt(f)
+  }
+}
+
+impl PartialEq for SharedError {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
 }
 
 #[macro_export]
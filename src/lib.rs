@@ -29,9 +29,12 @@
 #![cfg(any(target_os = "windows", doc))]
 #![deny(unsafe_op_in_unsafe_fn)]
 
+// `raw_window_handle` is the newest version enabled, both stay reachable when both are
 #[cfg(all(feature = "rwh_05", not(feature = "rwh_06")))]
 pub use rwh_05 as raw_window_handle;
-#[cfg(all(feature = "rwh_06", not(feature = "rwh_05")))]
+#[cfg(feature = "rwh_05")]
+pub use rwh_05 as raw_window_handle_05;
+#[cfg(feature = "rwh_06")]
 pub use rwh_06 as raw_window_handle;
 
 pub mod compat;
@@ -42,7 +45,11 @@ pub mod window;
 
 // re-exports
 pub use window::{
+  app::{quit, shutdown_on_console_exit},
+  capture::RgbaImage,
   data::{
+    Backdrop,
+    Color,
     CursorMode,
     Flow,
     Fullscreen,
@@ -53,19 +60,71 @@ pub use window::{
     Position,
     Size,
     Theme,
+    UserAttentionType,
     Visibility,
   },
+  dialog::{MessageBoxKind, MessageBoxResult},
+  framebuffer::{Framebuffer, PresentMode},
+  gesture::{Gesture, GestureKind, GesturePhase},
+  hit_test::{HitTest, HitTester},
+  icon::{CustomCursor, Icon},
   input::{
-    key::Key,
-    mouse::MouseButton,
-    state::{ButtonState, KeyState, RawKeyState},
+    joystick::{HatDirection, JoystickAxis, JoystickEvent, JoystickId},
+    key::{Key, PhysicalKey},
+    layout::KeyboardLayout,
+    mouse::{double_click_area, double_click_time, MouseButton},
+    pointer::{
+      PointerDevice,
+      PointerDeviceId,
+      PointerDeviceKind,
+      PointerEvent,
+      PointerKind,
+      PointerPhase,
+      PointerPropertyRange,
+    },
+    state::{ButtonState, KeyRepeat, KeyState, Modifiers, RawKeyState},
     Input,
   },
-  message::{LoopMessage, Message, RawInputMessage},
+  journal::{JournalEntry, JournalSink},
+  jump_list::{JumpList, JumpTask},
+  menu::SystemCommand,
+  message::{CommandKind, LoopMessage, Message, RawInputMessage, ScrollDelta},
+  placement::WindowPlacement,
+  power::{power_status, KeepAwake, PowerSource, PowerStatus},
+  proxy::EventProxy,
+  queue::{queue_full_policy, set_queue_full_policy, QueueFullPolicy},
+  recording::{RecordedInput, RecordedMessage, Recording},
+  resize::{ResizeCompletion, ResizeSignal},
   settings::{WindowBuilder, WindowSettings},
+  shell::{add_to_recent_docs, clear_recent_docs},
+  snap::SnapZone,
+  subclass::{Subclass, SubclassId},
+  thread::WindowThread,
+  translator::{
+    CustomMessage,
+    FilterAction,
+    MessageFilter,
+    MessageReplier,
+    MessageTranslator,
+  },
+  tray::TrayBehavior,
+  worker::BackgroundWorker,
   Window,
 };
 
+#[cfg(feature = "accessibility")]
+pub use crate::window::accessibility::AccessibilityHandler;
+#[cfg(feature = "audio")]
+pub use crate::window::audio::{AudioEvent, AudioFlow};
+#[cfg(feature = "composition")]
+pub use crate::window::composition::Composition;
+#[cfg(feature = "gl")]
+pub use crate::window::gl::{GlConfig, GlContext, GlProfile};
+#[cfg(feature = "headless")]
+pub use crate::window::headless::{AnyWindow, HeadlessWindow};
+#[cfg(feature = "gamepad")]
+pub use crate::window::input::gamepad::{GamepadAxis, GamepadButton, GamepadEvent};
+
 #[cfg(doctest)]
 #[doc = include_str!("../README.md")]
 struct ReadMe;
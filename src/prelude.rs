@@ -1,6 +1,22 @@
+#[cfg(feature = "accessibility")]
+pub use crate::window::accessibility::AccessibilityHandler;
+#[cfg(feature = "audio")]
+pub use crate::window::audio::{AudioEvent, AudioFlow};
+#[cfg(feature = "composition")]
+pub use crate::window::composition::Composition;
+#[cfg(feature = "gl")]
+pub use crate::window::gl::{GlConfig, GlContext, GlProfile};
+#[cfg(feature = "headless")]
+pub use crate::window::headless::{AnyWindow, HeadlessWindow};
+#[cfg(feature = "gamepad")]
+pub use crate::window::input::gamepad::{GamepadAxis, GamepadButton, GamepadEvent};
 pub use crate::window::{
   self,
+  app::{quit, shutdown_on_console_exit},
+  capture::RgbaImage,
   data::{
+    Backdrop,
+    Color,
     CursorMode,
     Flow,
     Fullscreen,
@@ -11,15 +27,54 @@ pub use crate::window::{
     Position,
     Size,
     Theme,
+    UserAttentionType,
     Visibility,
   },
+  dialog::{MessageBoxKind, MessageBoxResult},
+  framebuffer::{Framebuffer, PresentMode},
+  gesture::{Gesture, GestureKind, GesturePhase},
+  hit_test::{HitTest, HitTester},
+  icon::{CustomCursor, Icon},
   input::{
-    key::Key,
-    mouse::MouseButton,
-    state::{ButtonState, KeyState, RawKeyState},
+    joystick::{HatDirection, JoystickAxis, JoystickEvent, JoystickId},
+    key::{Key, PhysicalKey},
+    layout::KeyboardLayout,
+    mouse::{double_click_area, double_click_time, MouseButton},
+    pointer::{
+      PointerDevice,
+      PointerDeviceId,
+      PointerDeviceKind,
+      PointerEvent,
+      PointerKind,
+      PointerPhase,
+      PointerPropertyRange,
+    },
+    state::{ButtonState, KeyRepeat, KeyState, Modifiers, RawKeyState},
     Input,
   },
-  message::{LoopMessage, Message, RawInputMessage},
+  journal::{JournalEntry, JournalSink},
+  jump_list::{JumpList, JumpTask},
+  menu::SystemCommand,
+  message::{CommandKind, LoopMessage, Message, RawInputMessage, ScrollDelta},
+  placement::WindowPlacement,
+  power::{power_status, KeepAwake, PowerSource, PowerStatus},
+  proxy::EventProxy,
+  queue::{queue_full_policy, set_queue_full_policy, QueueFullPolicy},
+  recording::{RecordedInput, RecordedMessage, Recording},
+  resize::{ResizeCompletion, ResizeSignal},
   settings::{WindowBuilder, WindowSettings},
+  shell::{add_to_recent_docs, clear_recent_docs},
+  snap::SnapZone,
+  subclass::{Subclass, SubclassId},
+  thread::WindowThread,
+  translator::{
+    CustomMessage,
+    FilterAction,
+    MessageFilter,
+    MessageReplier,
+    MessageTranslator,
+  },
+  tray::TrayBehavior,
+  worker::BackgroundWorker,
   Window,
 };
@@ -4,36 +4,78 @@ use std::{
     atomic::{AtomicBool, Ordering},
     OnceLock,
   },
+  time::{Duration, Instant},
 };
 
 use cursor_icon::CursorIcon;
 use windows::{
-  core::{PCSTR, PCWSTR},
+  core::{PCSTR, PCWSTR, PROPVARIANT},
   Win32::{
     Devices::HumanInterfaceDevice,
-    Foundation::{HWND, NTSTATUS, RECT},
-    Graphics::Gdi::{GetDC, GetMonitorInfoW, HMONITOR, MONITORINFO, MONITORINFOEXW},
+    Foundation::{BOOL, HWND, NTSTATUS, RECT, RPC_E_CHANGED_MODE},
+    Graphics::{
+      Dwm::{
+        self,
+        DwmEnableBlurBehindWindow,
+        DwmExtendFrameIntoClientArea,
+        DwmGetColorizationColor,
+        DwmGetCompositionTimingInfo,
+        DwmSetWindowAttribute,
+        DWM_BLURBEHIND,
+        DWM_TIMING_INFO,
+      },
+      Gdi::{GetDC, GetMonitorInfoW, HMONITOR, MONITORINFO, MONITORINFOEXW},
+    },
+    Storage::EnhancedStorage::PKEY_AppUserModel_ID,
     System::{
+      Com::{
+        CoCreateInstance,
+        CoInitializeEx,
+        CoUninitialize,
+        CLSCTX_ALL,
+        COINIT,
+        COINIT_APARTMENTTHREADED,
+      },
       LibraryLoader::{GetProcAddress, LoadLibraryA},
-      SystemInformation::OSVERSIONINFOW,
+      SystemInformation::{GetTickCount, OSVERSIONINFOW},
+      Threading::{AttachThreadInput, GetCurrentThreadId},
     },
     UI::{
+      Accessibility::{self, HIGHCONTRASTW},
+      Controls::MARGINS,
       HiDpi::{self, GetDpiForMonitor, GetDpiForWindow},
       Input::{
         self,
         GetRawInputData,
+        KeyboardAndMouse::{GetLastInputInfo, LASTINPUTINFO},
         RegisterRawInputDevices,
         HRAWINPUT,
         RAWINPUT,
         RAWINPUTDEVICE,
         RAWINPUTHEADER,
       },
+      Shell::{
+        ITaskbarList,
+        PropertiesSystem::{IPropertyStore, SHGetPropertyStoreForWindow},
+        TaskbarList,
+      },
       WindowsAndMessaging::{
         self,
+        AllowSetForegroundWindow,
+        BringWindowToTop,
         ClipCursor,
+        FlashWindowEx,
         GetClipCursor,
+        GetForegroundWindow,
         GetSystemMetrics,
+        GetWindowThreadProcessId,
+        IsIconic,
+        SetForegroundWindow,
+        SetTimer,
         ShowCursor,
+        ShowWindow,
+        SystemParametersInfoW,
+        FLASHWINFO,
         WINDOW_EX_STYLE,
         WINDOW_STYLE,
       },
@@ -46,7 +88,7 @@ use crate::{
   error::WindowError,
   prelude::{PhysicalPosition, PhysicalSize},
   window::{
-    data::{Fullscreen, Visibility},
+    data::{Backdrop, Color, Fullscreen, UserAttentionType, Visibility},
     frame::Style,
   },
 };
@@ -121,6 +163,30 @@ macro_rules! get_function {
   };
 }
 
+/// Like [`get_function_impl`], for exports only found by ordinal.
+pub(crate) fn get_ordinal_impl(
+  library: &str,
+  ordinal: u16,
+) -> Option<*const std::ffi::c_void> {
+  assert_eq!(library.chars().last(), Some('\0'));
+
+  let module = match unsafe { LoadLibraryA(PCSTR::from_raw(library.as_ptr())) } {
+    Ok(module) => module,
+    Err(_) => return None,
+  };
+
+  // an ordinal is passed in place of the name, in the low word of the pointer
+  unsafe { GetProcAddress(module, PCSTR::from_raw(ordinal as usize as *const u8)) }
+    .map(|function_ptr| function_ptr as _)
+}
+
+macro_rules! get_ordinal {
+  ($lib:expr, $ordinal:expr, $func:ident) => {
+    crate::utilities::get_ordinal_impl(concat!($lib, '\0'), $ordinal)
+      .map(|f| unsafe { std::mem::transmute::<*const _, $func>(f) })
+  };
+}
+
 pub fn windows_10_build_version() -> Option<u32> {
   static WIN10_BUILD_VERSION: OnceLock<Option<u32>> = OnceLock::new();
   *WIN10_BUILD_VERSION.get_or_init(|| {
@@ -164,6 +230,43 @@ pub fn is_dark_mode_supported() -> bool {
   })
 }
 
+/// Let the system menu and other classic popups of `hwnd` follow its theme,
+/// which `DWMWA_USE_IMMERSIVE_DARK_MODE` leaves light. Relies on undocumented
+/// `uxtheme.dll` exports only found by ordinal, so this does nothing where
+/// dark mode isn't supported.
+pub fn set_dark_menus(hwnd: HWND, dark: bool) {
+  // SetPreferredAppMode (AllowDarkModeForApp before build 18362, taking a BOOL)
+  type SetPreferredAppMode = unsafe extern "system" fn(i32) -> i32;
+  type AllowDarkModeForWindow = unsafe extern "system" fn(HWND, BOOL) -> BOOL;
+  type FlushMenuThemes = unsafe extern "system" fn();
+  const APP_MODE_ALLOW_DARK: i32 = 1;
+
+  if !is_dark_mode_supported() {
+    return;
+  }
+
+  static ALLOWED_FOR_APP: OnceLock<()> = OnceLock::new();
+  if dark {
+    // windows opt in one by one, the app only has to allow it once
+    ALLOWED_FOR_APP.get_or_init(|| {
+      if let Some(set_preferred_app_mode) =
+        get_ordinal!("uxtheme.dll", 135, SetPreferredAppMode)
+      {
+        unsafe { set_preferred_app_mode(APP_MODE_ALLOW_DARK) };
+      }
+    });
+  }
+
+  if let Some(allow_dark_mode_for_window) =
+    get_ordinal!("uxtheme.dll", 133, AllowDarkModeForWindow)
+  {
+    unsafe { allow_dark_mode_for_window(hwnd, dark.into()) };
+  }
+  if let Some(flush_menu_themes) = get_ordinal!("uxtheme.dll", 136, FlushMenuThemes) {
+    unsafe { flush_menu_themes() };
+  }
+}
+
 pub fn is_system_dark_mode_enabled() -> bool {
   static IS_SYSTEM_DARK_MODE: OnceLock<bool> = OnceLock::new();
   *IS_SYSTEM_DARK_MODE.get_or_init(|| {
@@ -175,12 +278,423 @@ pub fn is_system_dark_mode_enabled() -> bool {
   })
 }
 
+/// Delay before a held key starts repeating, from the system keyboard settings.
+pub fn key_repeat_delay() -> Duration {
+  let mut delay = 1u32;
+  if let Err(e) = unsafe {
+    SystemParametersInfoW(
+      WindowsAndMessaging::SPI_GETKEYBOARDDELAY,
+      0,
+      Some(std::ptr::addr_of_mut!(delay) as _),
+      Default::default(),
+    )
+  } {
+    tracing::error!("{e}");
+  }
+  // 0 (about 250ms) through 3 (about 1s)
+  Duration::from_millis(250 * (delay.min(3) as u64 + 1))
+}
+
+/// Repeats per second of a held key, from the system keyboard settings.
+pub fn key_repeat_rate() -> f32 {
+  let mut speed = 31u32;
+  if let Err(e) = unsafe {
+    SystemParametersInfoW(
+      WindowsAndMessaging::SPI_GETKEYBOARDSPEED,
+      0,
+      Some(std::ptr::addr_of_mut!(speed) as _),
+      Default::default(),
+    )
+  } {
+    tracing::error!("{e}");
+  }
+  // 0 (about 2.5/s) through 31 (about 30/s)
+  2.5 + speed.min(31) as f32 * (27.5 / 31.0)
+}
+
+/// Lines scrolled per wheel notch, from the system mouse settings.
+pub fn wheel_scroll_lines() -> f32 {
+  let mut lines = 3u32;
+  if let Err(e) = unsafe {
+    SystemParametersInfoW(
+      WindowsAndMessaging::SPI_GETWHEELSCROLLLINES,
+      0,
+      Some(std::ptr::addr_of_mut!(lines) as _),
+      Default::default(),
+    )
+  } {
+    tracing::error!("{e}");
+  }
+  // WHEEL_PAGESCROLL (u32::MAX) asks for a page at a time, which has no fixed size
+  match lines {
+    u32::MAX => 3.0,
+    lines => lines as f32,
+  }
+}
+
+/// Characters scrolled per horizontal wheel notch, from the system mouse
+/// settings.
+pub fn wheel_scroll_chars() -> f32 {
+  let mut chars = 3u32;
+  if let Err(e) = unsafe {
+    SystemParametersInfoW(
+      WindowsAndMessaging::SPI_GETWHEELSCROLLCHARS,
+      0,
+      Some(std::ptr::addr_of_mut!(chars) as _),
+      Default::default(),
+    )
+  } {
+    tracing::error!("{e}");
+  }
+  // same fallback as lines, should a page at a time be reported here too
+  match chars {
+    u32::MAX => 3.0,
+    chars => chars as f32,
+  }
+}
+
+/// Whether high contrast mode is on, in which case custom-drawn UIs should
+/// switch to a palette with enough contrast, such as the system colors.
+pub fn is_high_contrast() -> bool {
+  let mut info = HIGHCONTRASTW {
+    cbSize: std::mem::size_of::<HIGHCONTRASTW>() as u32,
+    ..Default::default()
+  };
+  if let Err(e) = unsafe {
+    SystemParametersInfoW(
+      WindowsAndMessaging::SPI_GETHIGHCONTRAST,
+      info.cbSize,
+      Some(std::ptr::addr_of_mut!(info) as _),
+      Default::default(),
+    )
+  } {
+    tracing::error!("{e}");
+  }
+  info.dwFlags.0 & Accessibility::HCF_HIGHCONTRASTON.0 != 0
+}
+
+/// Whether the system animates controls and other elements inside windows.
+/// Apps should keep motion to a minimum while it's off, as users turn it off
+/// to reduce motion.
+pub fn animations_enabled() -> bool {
+  let mut enabled = BOOL::from(true);
+  if let Err(e) = unsafe {
+    SystemParametersInfoW(
+      WindowsAndMessaging::SPI_GETCLIENTAREAANIMATION,
+      0,
+      Some(std::ptr::addr_of_mut!(enabled) as _),
+      Default::default(),
+    )
+  } {
+    tracing::error!("{e}");
+  }
+  enabled.as_bool()
+}
+
+/// Whether transparency effects are on in the personalization settings.
+/// Apps should draw translucent surfaces opaque while they're off.
+pub fn transparency_enabled() -> bool {
+  UISettings::new()
+    .and_then(|settings| settings.AdvancedEffectsEnabled())
+    .unwrap_or(true)
+}
+
+/// The user's accent color, as used by the taskbar, Start menu, and titlebars
+/// if enabled in the personalization settings.
+pub fn accent_color() -> Color {
+  let accent =
+    UISettings::new().and_then(|settings| settings.GetColorValue(UIColorType::Accent));
+  match accent {
+    Ok(color) => Color::rgb(color.R, color.G, color.B),
+    // the DWM colorization color is the accent color blended for the frame
+    Err(_) => {
+      let mut colorization = 0u32;
+      let mut opaque = BOOL::default();
+      if let Err(e) = unsafe { DwmGetColorizationColor(&mut colorization, &mut opaque) } {
+        tracing::error!("{e}");
+      }
+      // 0xAARRGGBB
+      Color::rgb(
+        (colorization >> 16) as u8,
+        (colorization >> 8) as u8,
+        colorization as u8,
+      )
+    }
+  }
+}
+
+/// Timer used to check for user inactivity on the window thread.
+pub(crate) const IDLE_TIMER_ID: usize = 0x6964;
+
+/// How long it has been since the last keyboard or mouse input to any app in
+/// the session.
+pub fn idle_time() -> Duration {
+  let mut info = LASTINPUTINFO {
+    cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+    dwTime: 0,
+  };
+  if !unsafe { GetLastInputInfo(&mut info) }.as_bool() {
+    tracing::error!("{}", windows::core::Error::from_win32());
+    return Duration::ZERO;
+  }
+  // both tick counts wrap around every 49.7 days
+  let now = unsafe { GetTickCount() };
+  Duration::from_millis(now.wrapping_sub(info.dwTime) as u64)
+}
+
+/// Time of the last keyboard or mouse input to any app in the session. See
+/// [`idle_time`].
+pub fn last_input_time() -> Instant {
+  let now = Instant::now();
+  now.checked_sub(idle_time()).unwrap_or(now)
+}
+
+/// Check for inactivity often enough to report it close to `threshold`.
+pub(crate) fn start_idle_timer(hwnd: HWND, threshold: Duration) {
+  let interval =
+    (threshold / 4).clamp(Duration::from_millis(100), Duration::from_secs(1));
+  if unsafe { SetTimer(hwnd, IDLE_TIMER_ID, interval.as_millis() as u32, None) } == 0 {
+    tracing::error!("{}", windows::core::Error::from_win32());
+  }
+}
+
+/// Bring the window to the foreground from its own thread. Windows only lets
+/// the process that owns the foreground window hand it over, so the input of
+/// the foreground thread is attached to this one for the duration of the call.
+/// If Windows still refuses, the taskbar button flashes until the window is
+/// activated instead. Returns whether the window ended up in the foreground.
+pub fn steal_foreground(hwnd: HWND) -> bool {
+  let foreground = unsafe { GetForegroundWindow() };
+  if foreground == hwnd {
+    return true;
+  }
+
+  unsafe {
+    if IsIconic(hwnd).as_bool() {
+      ShowWindow(hwnd, WindowsAndMessaging::SW_RESTORE);
+    }
+
+    // lets processes started by this one take the foreground later as well
+    let _ = AllowSetForegroundWindow(WindowsAndMessaging::ASFW_ANY);
+
+    let current_thread = GetCurrentThreadId();
+    let foreground_thread = GetWindowThreadProcessId(foreground, None);
+    let attached = foreground_thread != 0
+      && foreground_thread != current_thread
+      && AttachThreadInput(current_thread, foreground_thread, true).as_bool();
+
+    if let Err(e) = BringWindowToTop(hwnd) {
+      tracing::error!("{e}");
+    }
+    SetForegroundWindow(hwnd);
+
+    if attached {
+      AttachThreadInput(current_thread, foreground_thread, false);
+    }
+  }
+
+  let in_foreground = unsafe { GetForegroundWindow() } == hwnd;
+  if !in_foreground {
+    let info = FLASHWINFO {
+      cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+      hwnd,
+      dwFlags: WindowsAndMessaging::FLASHW_ALL | WindowsAndMessaging::FLASHW_TIMERNOFG,
+      uCount: 0,
+      dwTimeout: 0,
+    };
+    unsafe { FlashWindowEx(&info) };
+  }
+  in_foreground
+}
+
+/// COM initialized on the current thread until dropped, which balances the
+/// `CoInitializeEx` it was created with. A thread the app already initialized
+/// with the other model is left as is, the shell and audio APIs used here work
+/// with either. Create it before the COM objects it's for, so they're released
+/// first.
+pub(crate) struct ComGuard {
+  uninitialize: bool,
+  // COM is initialized per thread
+  _not_send: std::marker::PhantomData<*const ()>,
+}
+
+impl ComGuard {
+  pub fn new(model: COINIT) -> windows::core::Result<Self> {
+    let result = unsafe { CoInitializeEx(None, model) };
+    let uninitialize = match result {
+      RPC_E_CHANGED_MODE => false,
+      result => {
+        result.ok()?;
+        true
+      }
+    };
+    Ok(Self {
+      uninitialize,
+      _not_send: std::marker::PhantomData,
+    })
+  }
+}
+
+impl Drop for ComGuard {
+  fn drop(&mut self) {
+    if self.uninitialize {
+      unsafe { CoUninitialize() };
+    }
+  }
+}
+
+/// Add or remove the taskbar button of a shown window right away. Windows
+/// otherwise only looks at the extended style when the window is shown.
+pub(crate) fn set_taskbar_button(hwnd: HWND, shown: bool) -> windows::core::Result<()> {
+  let _com = ComGuard::new(COINIT_APARTMENTTHREADED)?;
+  let taskbar: ITaskbarList =
+    unsafe { CoCreateInstance(&TaskbarList, None, CLSCTX_ALL) }?;
+  unsafe { taskbar.HrInit() }?;
+  match shown {
+    true => unsafe { taskbar.AddTab(hwnd) },
+    false => unsafe { taskbar.DeleteTab(hwnd) },
+  }
+}
+
+/// Group `hwnd` in the taskbar under `app_id` rather than the process's
+/// AppUserModelID, or clear it with `None`. Must be called on the window
+/// thread.
+pub(crate) fn set_window_app_id(
+  hwnd: HWND,
+  app_id: Option<&str>,
+) -> windows::core::Result<()> {
+  let _com = ComGuard::new(COINIT_APARTMENTTHREADED)?;
+  let store: IPropertyStore = unsafe { SHGetPropertyStoreForWindow(hwnd) }?;
+  let value = app_id.map(PROPVARIANT::from).unwrap_or_default();
+  unsafe { store.SetValue(&PKEY_AppUserModel_ID, &value) }?;
+  unsafe { store.Commit() }
+}
+
+pub(crate) fn request_user_attention(hwnd: HWND, attention: Option<UserAttentionType>) {
+  let (flags, count) = match attention {
+    Some(UserAttentionType::Informational) => (WindowsAndMessaging::FLASHW_TRAY, 3),
+    Some(UserAttentionType::Critical) => {
+      (WindowsAndMessaging::FLASHW_ALL | WindowsAndMessaging::FLASHW_TIMERNOFG, 0)
+    }
+    None => (WindowsAndMessaging::FLASHW_STOP, 0),
+  };
+  if attention.is_some() && unsafe { GetForegroundWindow() } == hwnd {
+    return;
+  }
+  let info = FLASHWINFO {
+    cbSize: std::mem::size_of::<FLASHWINFO>() as u32,
+    hwnd,
+    dwFlags: flags,
+    uCount: count,
+    dwTimeout: 0,
+  };
+  unsafe { FlashWindowEx(&info) };
+}
+
+pub fn is_system_backdrop_supported() -> bool {
+  static SYSTEM_BACKDROP_SUPPORTED: OnceLock<bool> = OnceLock::new();
+  *SYSTEM_BACKDROP_SUPPORTED.get_or_init(|| {
+    // DWMWA_SYSTEMBACKDROP_TYPE was added in Windows 11 22H2
+    match windows_10_build_version() {
+      Some(v) => v >= 22621,
+      None => false,
+    }
+  })
+}
+
+pub(crate) fn set_system_backdrop(
+  hwnd: HWND,
+  backdrop: Backdrop,
+) -> Result<(), WindowError> {
+  // extend the frame into the whole client area so the backdrop shows through
+  let inset = if backdrop == Backdrop::None { 0 } else { -1 };
+  let margins = MARGINS {
+    cxLeftWidth: inset,
+    cxRightWidth: inset,
+    cyTopHeight: inset,
+    cyBottomHeight: inset,
+  };
+  unsafe { DwmExtendFrameIntoClientArea(hwnd, &margins) }?;
+
+  if is_system_backdrop_supported() {
+    let backdrop_type = match backdrop {
+      Backdrop::None => Dwm::DWMSBT_NONE,
+      Backdrop::Mica => Dwm::DWMSBT_MAINWINDOW,
+      Backdrop::MicaAlt => Dwm::DWMSBT_TABBEDWINDOW,
+      Backdrop::Acrylic => Dwm::DWMSBT_TRANSIENTWINDOW,
+    };
+    unsafe {
+      DwmSetWindowAttribute(
+        hwnd,
+        Dwm::DWMWA_SYSTEMBACKDROP_TYPE,
+        std::ptr::addr_of!(backdrop_type) as *const std::ffi::c_void,
+        std::mem::size_of::<Dwm::DWM_SYSTEMBACKDROP_TYPE>() as u32,
+      )
+    }?;
+  } else {
+    let blur_behind = DWM_BLURBEHIND {
+      dwFlags: Dwm::DWM_BB_ENABLE,
+      fEnable: BOOL::from(backdrop != Backdrop::None),
+      ..Default::default()
+    };
+    unsafe { DwmEnableBlurBehindWindow(hwnd, &blur_behind) }?;
+  }
+
+  Ok(())
+}
+
+/// Sets one of the DWM color attributes (caption, caption text, or border).
+/// `None` restores the system default. Requires Windows 11.
+pub(crate) fn set_dwm_color(
+  hwnd: HWND,
+  attribute: Dwm::DWMWINDOWATTRIBUTE,
+  color: Option<Color>,
+) -> Result<(), WindowError> {
+  let color = color
+    .map(|color| color.to_colorref().0)
+    .unwrap_or(Dwm::DWMWA_COLOR_DEFAULT);
+  unsafe {
+    DwmSetWindowAttribute(
+      hwnd,
+      attribute,
+      std::ptr::addr_of!(color) as *const std::ffi::c_void,
+      std::mem::size_of::<u32>() as u32,
+    )
+  }?;
+  Ok(())
+}
+
+/// Number of display refreshes the compositor has gone through, used to tell
+/// whether a frame was already presented during the current refresh.
+pub(crate) fn composition_refresh_count() -> Option<u64> {
+  let mut info = DWM_TIMING_INFO {
+    cbSize: std::mem::size_of::<DWM_TIMING_INFO>() as u32,
+    ..Default::default()
+  };
+  // the window handle must be null since Windows 8.1
+  match unsafe { DwmGetCompositionTimingInfo(HWND::default(), &mut info) } {
+    Ok(()) => Some(info.cRefresh),
+    Err(e) => {
+      tracing::error!("{e}");
+      None
+    }
+  }
+}
+
 #[inline]
 fn is_color_light(clr: &windows::UI::Color) -> bool {
   ((5 * clr.G as u32) + (2 * clr.R as u32) + clr.B as u32) > (8 * 128)
 }
 
 pub(crate) fn get_window_style(info: &Style) -> WINDOW_STYLE {
+  if info.child {
+    let mut style = WindowsAndMessaging::WS_CHILD | WindowsAndMessaging::WS_CLIPSIBLINGS;
+    if let Visibility::Shown = info.visibility {
+      style |= WindowsAndMessaging::WS_VISIBLE;
+    }
+    return style;
+  }
+
   let mut style = WindowsAndMessaging::WS_CAPTION
     | WindowsAndMessaging::WS_BORDER
     | WindowsAndMessaging::WS_CLIPSIBLINGS
@@ -220,6 +734,24 @@ pub(crate) fn get_window_ex_style(info: &Style) -> WINDOW_EX_STYLE {
     style &= !WindowsAndMessaging::WS_EX_WINDOWEDGE;
   }
 
+  if info.child {
+    style &=
+      !(WindowsAndMessaging::WS_EX_WINDOWEDGE | WindowsAndMessaging::WS_EX_APPWINDOW);
+  }
+
+  if info.skip_taskbar {
+    style &= !WindowsAndMessaging::WS_EX_APPWINDOW;
+    style |= WindowsAndMessaging::WS_EX_TOOLWINDOW;
+  }
+
+  if info.no_activate {
+    style |= WindowsAndMessaging::WS_EX_NOACTIVATE;
+  }
+
+  if info.no_redirection_bitmap {
+    style |= WindowsAndMessaging::WS_EX_NOREDIRECTIONBITMAP;
+  }
+
   style
 }
 
@@ -387,6 +919,60 @@ impl Monitor {
       .unwrap_or_default()
   }
 
+  /// Position of the monitor's work area, which excludes the taskbar and other
+  /// docked toolbars.
+  pub fn work_area_position(&self) -> PhysicalPosition {
+    self
+      .monitor_info()
+      .map(|info| {
+        let rect = info.monitorInfo.rcWork;
+        PhysicalPosition {
+          x: rect.left,
+          y: rect.top,
+        }
+      })
+      .unwrap_or_default()
+  }
+
+  /// Size of the monitor's work area, which excludes the taskbar and other
+  /// docked toolbars.
+  pub fn work_area_size(&self) -> PhysicalSize {
+    self
+      .monitor_info()
+      .map(|info| {
+        let rect = info.monitorInfo.rcWork;
+        PhysicalSize {
+          width: (rect.right - rect.left) as u32,
+          height: (rect.bottom - rect.top) as u32,
+        }
+      })
+      .unwrap_or_default()
+  }
+
+  /// Device name of the monitor, such as `\\.\DISPLAY1`.
+  pub fn name(&self) -> String {
+    self
+      .monitor_info()
+      .map(|info| {
+        let len = info
+          .szDevice
+          .iter()
+          .position(|c| *c == 0)
+          .unwrap_or(info.szDevice.len());
+        String::from_utf16_lossy(&info.szDevice[..len])
+      })
+      .unwrap_or_default()
+  }
+
+  pub fn is_primary(&self) -> bool {
+    self
+      .monitor_info()
+      .map(|info| {
+        is_flag_set(info.monitorInfo.dwFlags, WindowsAndMessaging::MONITORINFOF_PRIMARY)
+      })
+      .unwrap_or_default()
+  }
+
   pub fn scale_factor(&self) -> f64 {
     let mut dpi_x = 0;
     let mut _dpi_y = 0;
@@ -1,20 +1,19 @@
 use std::{
   collections::VecDeque,
-  sync::{mpsc::SyncSender, Arc, Condvar, Mutex},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    mpsc::SyncSender,
+    Arc,
+    Condvar,
+    Mutex,
+  },
+  task::{Context, Poll},
   thread::JoinHandle,
+  time::{Duration, Instant},
 };
 
 use cursor_icon::CursorIcon;
-#[cfg(all(feature = "rwh_05", not(feature = "rwh_06")))]
-use rwh_05::{
-  HasRawDisplayHandle,
-  HasRawWindowHandle,
-  RawDisplayHandle,
-  RawWindowHandle,
-  Win32WindowHandle,
-  WindowsDisplayHandle,
-};
-#[cfg(all(feature = "rwh_06", not(feature = "rwh_05")))]
+#[cfg(feature = "rwh_06")]
 use rwh_06::{
   DisplayHandle,
   HandleError,
@@ -31,10 +30,11 @@ use windows::{
   Win32::{
     Foundation::*,
     Graphics::{
-      Dwm::{self, DwmSetWindowAttribute},
+      Dwm::{self, DwmFlush, DwmSetWindowAttribute},
       Gdi::{
         self,
         EnumDisplayMonitors,
+        MapWindowPoints,
         MonitorFromPoint,
         MonitorFromWindow,
         HDC,
@@ -45,44 +45,83 @@ use windows::{
     UI::{
       HiDpi::{
         AdjustWindowRectExForDpi,
+        GetWindowDpiAwarenessContext,
         SetProcessDpiAwarenessContext,
+        SetThreadDpiAwarenessContext,
         DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE,
         DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
       },
+      Input::KeyboardAndMouse::IsWindowEnabled,
+      Shell::SetCurrentProcessExplicitAppUserModelID,
       WindowsAndMessaging::{
         self,
+        ChangeWindowMessageFilterEx,
         CreateWindowExW,
         DispatchMessageW,
         GetClientRect,
         GetCursorPos,
         GetMessageW,
+        GetParent,
+        GetWindow,
         GetWindowRect,
-        LoadCursorW,
-        RegisterClassExW,
+        IsWindow,
         TranslateMessage,
         MSG,
-        WNDCLASSEXW,
       },
     },
   },
 };
 
+#[cfg(feature = "accessibility")]
+use self::accessibility::AccessibilityHandler;
 use self::{
-  command::Command,
-  data::{CursorMode, Fullscreen, PhysicalSize, Position},
-  message::LoopMessage,
+  broadcast::Receiver,
+  capture::RgbaImage,
+  command::{Command, PendingSubclass},
+  data::{
+    Backdrop,
+    Color,
+    CursorMode,
+    Fullscreen,
+    PhysicalSize,
+    Position,
+    UserAttentionType,
+  },
+  diagnostics::{DiagnosticsReport, DpiAwareness, MonitorReport},
+  dialog::{MessageBoxKind, MessageBoxResult},
+  drag::TabStrip,
+  hit_test::HitTester,
+  icon::{CustomCursor, Icon},
+  input::{layout::KeyboardLayout, state::KeyRepeat},
+  jump_list::JumpList,
+  menu::SystemCommand,
+  message::{LoopMessage, ScrollDelta},
+  paint::PaintContext,
+  power::{KeepAwake, PowerStatus},
+  proxy::EventProxy,
+  recording::{Recorder, Recording},
+  registry::WindowClass,
   settings::WindowBuilder,
+  snap::SnapZone,
   stage::Stage,
+  stream::NextMessage,
+  subclass::{Subclass, SubclassId},
+  translator::{MessageFilter, MessageReplier, MessageTranslator},
+  tray::TrayBehavior,
 };
 use crate::{
   error::WindowError,
-  prelude::{ButtonState, Key, KeyState, MouseButton},
+  prelude::{ButtonState, Key, KeyState, Modifiers, MouseButton},
   utilities::{
     get_window_ex_style,
     get_window_style,
     hwnd_dpi,
     is_dark_mode_supported,
     is_system_dark_mode_enabled,
+    set_dark_menus,
+    set_dwm_color,
+    set_system_backdrop,
+    windows_10_build_version,
     Monitor,
   },
   window::{
@@ -95,24 +134,70 @@ use crate::{
   },
 };
 
+#[cfg(feature = "accessibility")]
+pub mod accessibility;
+pub mod app;
+#[cfg(feature = "audio")]
+pub mod audio;
+pub mod broadcast;
+pub mod capture;
 mod command;
+#[cfg(feature = "composition")]
+pub mod composition;
 pub mod cursor;
 pub mod data;
+pub mod diagnostics;
+pub mod dialog;
+mod drag;
 pub mod frame;
+pub mod framebuffer;
+pub mod gesture;
+#[cfg(feature = "gl")]
+pub mod gl;
+#[cfg(feature = "headless")]
+pub mod headless;
+pub mod hit_test;
+pub mod icon;
 pub mod input;
+pub mod journal;
+pub mod jump_list;
+pub mod menu;
 pub mod message;
 pub mod monitor;
+pub mod paint;
+pub mod placement;
+pub mod power;
 pub mod procedure;
+pub mod proxy;
+pub mod queue;
+pub mod recording;
+mod registry;
+pub mod resize;
 pub mod settings;
+pub mod shell;
+pub mod snap;
 pub mod stage;
+pub mod stream;
+pub mod subclass;
+pub mod thread;
+mod timer;
+pub mod translator;
+pub mod tray;
+pub mod worker;
 
 /// Main window class. Uses internal mutability. Window is destroyed on drop. Cloning does not create a new window,
 /// but instead clones the smart pointer handle to the same window.
 #[allow(unused)]
 #[derive(Clone)]
-pub struct Window(Arc<Internal>);
+pub struct Window(pub(crate) Arc<Internal>);
 
 impl Window {
+  /// How long [`Window::pump_messages`] waits for the window thread to answer
+  /// before assuming it has no more messages queued.
+  const PUMP_DRAIN_TIMEOUT: Duration = Duration::from_millis(1);
+  /// Default number of messages buffered by a [`Receiver`] created with
+  /// [`Window::subscribe`] before the oldest ones are dropped.
+  pub const SUBSCRIBER_CAPACITY: usize = 256;
   pub const WINDOW_SUBCLASS_ID: usize = 0;
 
   /// Create a new [`WindowBuilder`] to set up a [`Window`].
@@ -129,18 +214,24 @@ impl Window {
     title: impl Into<String>,
     size: impl Into<Size>,
     position: impl Into<Option<Position>>,
-    settings: WindowSettings,
+    mut settings: WindowSettings,
   ) -> Result<Self, WindowError> {
     let title: String = title.into();
     let size: Size = size.into();
     let position: Option<Position> = position.into();
 
+    if settings.visible_after_first_draw {
+      settings.visibility = Visibility::Hidden;
+    }
+
     tracing::trace!("[`{}`]: creating window", &title);
 
     let sync = SyncData {
-      new_message: Arc::new((Mutex::new(false), Condvar::new())),
+      mailbox: Default::default(),
       next_frame: Arc::new((Mutex::new(true), Condvar::new())),
+      frame_released: Arc::new(AtomicBool::new(true)),
       skip_wait: Arc::new(Mutex::new(true)),
+      waker: Arc::new(Mutex::new(None)),
     };
 
     let create_info = CreateInfo {
@@ -148,9 +239,8 @@ impl Window {
       size,
       position,
       settings: settings.clone(),
-      class_atom: 0,
+      class: None,
       window: None,
-      message: Arc::new(Mutex::new(None)),
       sync: sync.clone(),
       style: Style {
         visibility: settings.visibility,
@@ -161,9 +251,20 @@ impl Window {
         maximized: false,
         active: false,
         focused: false,
+        no_redirection_bitmap: settings.no_redirection_bitmap,
+        child: settings.parent.is_some(),
+        skip_taskbar: settings.skip_taskbar,
+        no_activate: settings.no_activate,
       },
     };
 
+    if let Some(thread) = settings.thread.as_ref() {
+      tracing::trace!("[`{}`]: creating window on shared thread", &title);
+      let window = thread.create_window(create_info)?;
+      tracing::trace!("[`{}`]: created window", &title);
+      return Ok(window);
+    }
+
     let (window_sender, window_receiver) = std::sync::mpsc::sync_channel(0);
 
     let thread = Some(Self::window_loop(window_sender, create_info)?);
@@ -205,38 +306,40 @@ impl Window {
     Ok(thread_handle)
   }
 
-  fn create_hwnd(mut create_info: CreateInfo) -> Result<Self, WindowError> {
+  pub(crate) fn create_hwnd(mut create_info: CreateInfo) -> Result<Self, WindowError> {
     tracing::trace!("[`{}`]: creating window class", &create_info.title);
 
     let hinstance: HINSTANCE = unsafe { GetModuleHandleW(None)? }.into();
     debug_assert_ne!(hinstance.0, 0);
     let title = HSTRING::from(create_info.title.clone());
-    let window_class = title.clone();
-
-    let wc = WNDCLASSEXW {
-      cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
-      style: WindowsAndMessaging::CS_VREDRAW
-        | WindowsAndMessaging::CS_HREDRAW
-        | WindowsAndMessaging::CS_DBLCLKS
-        | WindowsAndMessaging::CS_OWNDC,
-      cbWndExtra: std::mem::size_of::<WNDCLASSEXW>() as i32,
-      lpfnWndProc: Some(procedure::wnd_proc),
-      hInstance: hinstance,
-      hCursor: unsafe { LoadCursorW(None, WindowsAndMessaging::IDC_ARROW)? },
-      lpszClassName: PCWSTR(window_class.as_ptr()),
-      ..Default::default()
-    };
-
-    tracing::trace!("[`{}`]: registering window class", &create_info.title);
-
-    {
-      create_info.class_atom = unsafe { RegisterClassExW(&wc) };
-      debug_assert_ne!(create_info.class_atom, 0);
+    let class_name = create_info
+      .settings
+      .class_name
+      .as_deref()
+      .unwrap_or(&create_info.title);
+    let window_class =
+      WindowClass::acquire(class_name, hinstance, Some(procedure::wnd_proc))?;
+    if let Some(app_id) = &create_info.settings.app_id {
+      if let Err(e) =
+        unsafe { SetCurrentProcessExplicitAppUserModelID(&HSTRING::from(app_id)) }
+      {
+        tracing::error!("{e}");
+      }
     }
+    let class_name = PCWSTR(window_class.atom() as *const u16);
+    create_info.class = Some(window_class);
 
     tracing::trace!("[`{}`]: creating window handle", &create_info.title);
 
-    if unsafe {
+    if create_info.settings.embedded {
+      let parent = create_info.settings.parent.unwrap_or_default();
+      if !unsafe { IsWindow(parent) }.as_bool() {
+        return Err(WindowError::Error("embed parent is not a window".to_owned()));
+      }
+      // the host owns the process's awareness, and child windows are sized in the
+      // parent's units
+      unsafe { SetThreadDpiAwarenessContext(GetWindowDpiAwarenessContext(parent)) };
+    } else if unsafe {
       SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2)
     }
     .is_err()
@@ -248,14 +351,18 @@ impl Window {
     let hwnd = unsafe {
       CreateWindowExW(
         get_window_ex_style(&create_info.style),
-        &window_class,
+        class_name,
         &title,
         get_window_style(&create_info.style) & !WindowsAndMessaging::WS_VISIBLE,
         WindowsAndMessaging::CW_USEDEFAULT,
         WindowsAndMessaging::CW_USEDEFAULT,
         WindowsAndMessaging::CW_USEDEFAULT,
         WindowsAndMessaging::CW_USEDEFAULT,
-        None,
+        create_info
+          .settings
+          .parent
+          .or(create_info.settings.owner)
+          .unwrap_or_default(),
         None,
         hinstance,
         Some(std::ptr::addr_of_mut!(create_info) as _),
@@ -268,6 +375,7 @@ impl Window {
       Err(WindowError::Win32Error(windows::core::Error::from_win32()))
     } else {
       let window = create_info.window.take().unwrap();
+      app::track(&window);
 
       Ok(window)
     }
@@ -286,48 +394,113 @@ impl Window {
     }
   }
 
+  /// Error reported on the main thread since the last message, if any.
+  fn take_pending_error(&self) -> Option<Message> {
+    let error = self.0.pending_errors.lock().unwrap().pop_front();
+    error.map(Message::Error)
+  }
+
   fn take_message(&self) -> Option<Message> {
-    let flow = self.0.data.lock().unwrap().flow;
-    if let Flow::Wait = flow {
-      let should_wait = self.0.message.lock().unwrap().is_none();
-      if should_wait {
-        let (lock, cvar) = self.0.sync.new_message.as_ref();
-        let mut new = cvar.wait_while(lock.lock().unwrap(), |new| !*new).unwrap();
-        *new = false;
-      }
+    if let Some(error) = self.take_pending_error() {
+      return Some(error);
     }
+    let flow = *self.0.flow.lock().unwrap();
+    let background_interval = {
+      let data = self.0.data.lock().unwrap();
+      let in_background =
+        data.style.visibility == Visibility::Hidden || data.style.minimized;
+      data.background_interval.filter(|_| in_background)
+    };
+    let mailbox = &self.0.sync.mailbox;
+    let message = match (flow, background_interval) {
+      (Flow::Wait, _) => mailbox.wait_take(None),
+      // throttle polling while nobody can see the window
+      (Flow::Poll, Some(interval)) => mailbox.wait_take(Some(interval)),
+      (Flow::Poll, None) => mailbox.take(),
+      (Flow::WaitUntil(deadline), _) => {
+        mailbox.wait_take(Some(deadline.saturating_duration_since(Instant::now())))
+      }
+    };
 
-    self
-      .0
-      .message
-      .lock()
-      .unwrap()
-      .take()
-      .or(Some(Message::Loop(LoopMessage::Empty)))
+    message.or(Some(Message::Loop(LoopMessage::Empty)))
   }
 
-  fn next_message(&self) -> Option<Message> {
+  /// Take the message sent by the window thread without waiting for one.
+  fn try_take_message(&self) -> Option<Message> {
+    self
+      .take_pending_error()
+      .or_else(|| self.0.sync.mailbox.take())
+  }
+
+  /// Release the window thread from the previous message.
+  fn prepare_next_message(&self) {
+    // the previous message has been handled, so deferred setters can apply
+    let deferred = {
+      let mut data = self.0.data.lock().unwrap();
+      data.handling_resize = false;
+      data.handling_move = false;
+      std::mem::take(&mut data.deferred_commands)
+    };
+    for command in deferred {
+      self.0.post(command);
+    }
+    self.0.input.lock().unwrap().start_frame();
+
     self.0.sync.signal_next_frame();
+  }
 
-    let current_stage = self.0.data.lock().unwrap().stage;
+  fn finish_message(&self, message: Option<Message>) -> Option<Message> {
+    if let Some(message) = message.as_ref().filter(|m| !m.is_empty()) {
+      self.0.broadcaster.send(message);
+    }
+    {
+      let mut data = self.0.data.lock().unwrap();
+      if let Some(message) = message.as_ref() {
+        self
+          .0
+          .input
+          .lock()
+          .unwrap()
+          .apply_message(message, data.scale_factor);
+      }
+      if let (Some(recorder), Some(message)) = (data.recorder.as_mut(), message.as_ref())
+      {
+        recorder.record(message);
+      }
+      data.handling_resize =
+        matches!(message, Some(Message::Resized(_) | Message::BoundsChanged { .. }));
+      data.handling_move =
+        matches!(message, Some(Message::Moved(_) | Message::BoundsChanged { .. }));
+    }
+    if matches!(message, Some(Message::Loop(LoopMessage::Empty) | Message::Paint)) {
+      self.0.input.lock().unwrap().end_frame();
+    }
+    match message {
+      Some(Message::CloseRequested) => {
+        let x = self.0.data.lock().unwrap().close_on_x;
+        if x {
+          self.close();
+        }
+      }
+      Some(Message::Loop(LoopMessage::QuitRequested)) => self.close(),
+      Some(Message::Loop(LoopMessage::Exit(_))) => {
+        *self.0.sync.skip_wait.lock().unwrap() = true;
+        self.0.stage.store(Stage::ExitLoop);
+      }
+      _ => (),
+    }
+    message
+  }
+
+  fn next_message_blocking(&self) -> Option<Message> {
+    self.prepare_next_message();
+
+    let current_stage = self.0.stage.load();
     let next = match current_stage {
       Stage::Setup | Stage::Ready | Stage::Destroyed => None,
       Stage::Looping | Stage::Closing => {
         let message = self.take_message();
-        match message {
-          Some(Message::CloseRequested) => {
-            let x = self.0.data.lock().unwrap().close_on_x;
-            if x {
-              self.close();
-            }
-          }
-          Some(Message::Loop(LoopMessage::Exit)) => {
-            *self.0.sync.skip_wait.lock().unwrap() = true;
-            self.0.data.lock().unwrap().stage = Stage::ExitLoop;
-          }
-          _ => (),
-        }
-        message
+        self.finish_message(message)
       }
       Stage::ExitLoop => {
         tracing::trace!("[`{}`]: exiting loop", self.title());
@@ -338,14 +511,239 @@ impl Window {
     next
   }
 
+  /// Non-blocking counterpart of the iterator, shared by [`NextMessage`] and
+  /// the message stream. `started` tracks whether the window thread has
+  /// already been released for the message being waited on.
+  pub(crate) fn poll_next_message(
+    &self,
+    started: &mut bool,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Message>> {
+    if self.0.stage.transition(Stage::Ready, Stage::Looping) {
+      tracing::trace!(
+        "[`{}`]: preparing to asynchronously receive messages",
+        self.title()
+      );
+    }
+
+    if !std::mem::replace(started, true) {
+      self.prepare_next_message();
+    }
+
+    let (current_stage, wait) = {
+      let mut data = self.0.data.lock().unwrap();
+      let in_background =
+        data.style.visibility == Visibility::Hidden || data.style.minimized;
+      // throttled polling has no timer to wake it, so it waits for messages
+      let throttled = data.background_interval.is_some() && in_background;
+      let flow = *self.0.flow.lock().unwrap();
+      let wait = match flow {
+        Flow::Wait => true,
+        Flow::Poll => throttled,
+        Flow::WaitUntil(deadline) if deadline <= Instant::now() => false,
+        Flow::WaitUntil(deadline) => {
+          // nothing blocks to time out, so the window thread wakes the task
+          if data.wake_deadline != Some(deadline) {
+            data.wake_deadline = Some(deadline);
+            self.0.post(Command::SetWakeTimer(deadline));
+          }
+          true
+        }
+      };
+      (self.0.stage.load(), wait)
+    };
+    match current_stage {
+      Stage::Setup | Stage::Ready | Stage::Destroyed => Poll::Ready(None),
+      Stage::Looping | Stage::Closing => {
+        let message = match self.try_take_message() {
+          Some(message) => Some(message),
+          None if !wait => Some(Message::Loop(LoopMessage::Empty)),
+          None => {
+            self
+              .0
+              .sync
+              .waker
+              .lock()
+              .unwrap()
+              .replace(cx.waker().clone());
+            // the window thread may have sent a message before the waker was
+            // stored
+            self.try_take_message()
+          }
+        };
+        match message {
+          Some(message) => {
+            *started = false;
+            Poll::Ready(self.finish_message(Some(message)))
+          }
+          None => Poll::Pending,
+        }
+      }
+      Stage::ExitLoop => {
+        tracing::trace!("[`{}`]: exiting loop", self.title());
+        Poll::Ready(None)
+      }
+    }
+  }
+
+  /// Wait for the next message without blocking the thread, for driving the
+  /// window from an async runtime such as tokio or async-std. Resolves to
+  /// `None` once the window has closed, where iterating would stop. Don't
+  /// mix this with iterating over the same window.
+  ///
+  /// While hidden or minimized with a
+  /// [background interval](Window::set_background_interval), polling windows
+  /// wait for the next message instead of waking every interval.
+  pub fn next_message(&self) -> NextMessage<'_> {
+    NextMessage::new(self)
+  }
+
+  /// Stream of the window's messages, the async counterpart of iterating over
+  /// the window. See [`Window::next_message`].
+  #[cfg(feature = "async")]
+  pub fn messages(&self) -> stream::MessageStream<'_> {
+    stream::MessageStream::new(self)
+  }
+
+  /// Process the window's messages without handing the thread over to the
+  /// iterator, for engines or frameworks that run their own main loop.
+  ///
+  /// Waits up to `timeout` for the first message, then keeps taking messages
+  /// for as long as the window thread has more queued. Pass
+  /// [`Duration::ZERO`] to only take what is already waiting. Returns an
+  /// empty list once the window has closed. Don't mix this with iterating over
+  /// the same window.
+  pub fn pump_messages(&self, timeout: Duration) -> Vec<Message> {
+    if self.0.stage.transition(Stage::Ready, Stage::Looping) {
+      tracing::trace!("[`{}`]: preparing to pump messages", self.title());
+    }
+
+    let deadline = Instant::now() + timeout;
+    let mut messages = Vec::new();
+    while let Some(error) = self.take_pending_error() {
+      messages.extend(self.finish_message(Some(error)));
+    }
+    loop {
+      let current_stage = self.0.stage.load();
+      if !matches!(current_stage, Stage::Looping | Stage::Closing) {
+        break;
+      }
+
+      let started =
+        std::mem::replace(&mut self.0.data.lock().unwrap().pump_started, true);
+      if !started {
+        self.prepare_next_message();
+      }
+
+      let wait = match messages.is_empty() {
+        true => deadline.saturating_duration_since(Instant::now()),
+        false => Self::PUMP_DRAIN_TIMEOUT,
+      };
+      let message = self.0.sync.mailbox.wait_take(Some(wait));
+      let Some(message) = message else {
+        break;
+      };
+
+      self.0.data.lock().unwrap().pump_started = false;
+      messages.extend(self.finish_message(Some(message)));
+    }
+
+    messages
+  }
+
   /// Request the window be closed
   pub fn close(&self) {
     if self.is_closing() {
       return; // already closing
     }
     tracing::trace!("[`{}`]: closing window", self.title());
-    self.0.data.lock().unwrap().stage = Stage::Closing;
-    Command::Exit.post(self.0.hwnd);
+    self.0.stage.store(Stage::Closing);
+    self.0.post(Command::Exit);
+  }
+
+  /// Close the window like [`Window::close`], ending its loop with
+  /// [`LoopMessage::Exit`] carrying `code`. Pass the code on to
+  /// [`std::process::exit`] to report success or failure to the shell.
+  pub fn exit_with_code(&self, code: i32) {
+    if self.is_closing() {
+      return;
+    }
+    self.0.data.lock().unwrap().exit_code = code;
+    self.close();
+  }
+
+  /// Code the window's loop ends with. See [`Window::exit_with_code`].
+  pub fn exit_code(&self) -> i32 {
+    self.0.data.lock().unwrap().exit_code
+  }
+
+  /// Register a callback which runs right before the window handle is
+  /// destroyed, in the order callbacks were registered. Use this to drop
+  /// swapchains or surfaces created from raw handles while the window still
+  /// exists, avoiding device-removed errors during shutdown.
+  ///
+  /// The window is destroyed when the last clone of it is dropped, so the
+  /// callback must not capture a clone of the window itself.
+  pub fn before_destroy(&self, f: impl FnOnce() + Send + 'static) {
+    self.0.before_destroy.lock().unwrap().push(Box::new(f));
+  }
+
+  /// Subscribe to the messages delivered by this window's iterator. Each
+  /// [`Receiver`] gets a clone of every non-empty message, allowing plugins or
+  /// recorders to observe the window without owning the main loop.
+  ///
+  /// Receivers buffer up to [`Window::SUBSCRIBER_CAPACITY`] messages, dropping
+  /// the oldest ones if they fall behind.
+  pub fn subscribe(&self) -> Receiver {
+    self.subscribe_with_capacity(Self::SUBSCRIBER_CAPACITY)
+  }
+
+  /// Same as [`Window::subscribe`], but with a custom buffer capacity.
+  pub fn subscribe_with_capacity(&self, capacity: usize) -> Receiver {
+    self.0.broadcaster.subscribe(capacity)
+  }
+
+  /// Scroll the window as if the mouse wheel had been turned, sending
+  /// [`Message::MouseWheel`] with `delta`, the last cursor position in the
+  /// client area, and the current modifiers after any input already waiting.
+  /// Useful for tests, or for turning other input such as MIDI controllers or
+  /// remotes into scrolling.
+  pub fn post_scroll(&self, delta: ScrollDelta) {
+    self.0.post(Command::Scroll(delta));
+  }
+
+  /// Send `message` to the window's iterator as if Windows had, after any
+  /// input already waiting, such as for integration tests or automation
+  /// driving the app's loop. Input state such as [`Window::key`] follows
+  /// injected keys, buttons, text, scrolling, and raw mouse motion, taking the
+  /// modifiers they carry. Other messages are delivered as they are without
+  /// affecting the window, so injecting [`Message::Resized`] doesn't resize it.
+  pub fn inject(&self, message: Message) {
+    self.0.post(Command::Inject(Box::new(message)));
+  }
+
+  /// Start recording the input the iterator yields, replacing any recording
+  /// already in progress. See [`Recording`].
+  pub fn start_recording(&self) {
+    self.0.data.lock().unwrap().recorder = Some(Recorder::new());
+  }
+
+  /// Stop recording, returning what was recorded since
+  /// [`Window::start_recording`], or `None` if the window wasn't recording.
+  pub fn stop_recording(&self) -> Option<Recording> {
+    let recorder = self.0.data.lock().unwrap().recorder.take();
+    recorder.map(Recorder::finish)
+  }
+
+  pub fn is_recording(&self) -> bool {
+    self.0.data.lock().unwrap().recorder.is_some()
+  }
+
+  /// Create a handle which other threads can use to send
+  /// [`Message::User`] to this window's iterator, such as for background
+  /// workers reporting completion. See [`EventProxy`].
+  pub fn event_proxy(&self) -> EventProxy {
+    EventProxy::new(self.0.hwnd)
   }
 
   // GETTERS
@@ -358,12 +756,146 @@ impl Window {
     self.0.data.lock().unwrap().style.visibility
   }
 
+  pub fn decorations(&self) -> Visibility {
+    self.0.data.lock().unwrap().style.decorations
+  }
+
+  pub fn is_resizable(&self) -> bool {
+    self.0.data.lock().unwrap().style.resizeable
+  }
+
+  /// Whether the window accepts mouse and keyboard input. Reflects
+  /// [`Window::set_enabled`] once the window thread has applied it, and is
+  /// `false` while a dialog shown with [`Window::show_modal`] is open.
+  pub fn is_enabled(&self) -> bool {
+    unsafe { IsWindowEnabled(self.0.hwnd) }.as_bool()
+  }
+
+  pub fn skips_taskbar(&self) -> bool {
+    self.0.data.lock().unwrap().style.skip_taskbar
+  }
+
   pub fn theme(&self) -> Theme {
     self.0.data.lock().unwrap().theme
   }
 
+  pub fn system_backdrop(&self) -> Backdrop {
+    self.0.data.lock().unwrap().backdrop
+  }
+
+  pub fn caption_color(&self) -> Option<Color> {
+    self.0.data.lock().unwrap().caption_color
+  }
+
+  pub fn caption_text_color(&self) -> Option<Color> {
+    self.0.data.lock().unwrap().caption_text_color
+  }
+
+  pub fn border_color(&self) -> Option<Color> {
+    self.0.data.lock().unwrap().border_color
+  }
+
+  pub fn icon(&self) -> Option<Icon> {
+    self.0.data.lock().unwrap().icon.clone()
+  }
+
+  pub fn tray_behavior(&self) -> TrayBehavior {
+    self.0.data.lock().unwrap().tray_behavior
+  }
+
+  /// Whether the window is currently hidden to the notification area.
+  pub fn is_in_tray(&self) -> bool {
+    self.0.data.lock().unwrap().in_tray
+  }
+
+  /// Whether an input method editor is currently composing text in the window.
+  pub fn is_ime_composing(&self) -> bool {
+    self.0.data.lock().unwrap().ime_composing
+  }
+
+  /// Keyboard layout currently active for the window. Changes are reported
+  /// with [`Message::KeyboardLayoutChanged`].
+  pub fn keyboard_layout(&self) -> KeyboardLayout {
+    self.0.data.lock().unwrap().keyboard_layout
+  }
+
+  /// Text `key` produces with the currently held modifiers under the window's
+  /// keyboard layout. Useful for showing keybindings as the user would type
+  /// them. See [`Key::to_text_with_layout`].
+  pub fn key_to_text(&self, key: Key) -> Option<String> {
+    let modifiers = self.0.input.lock().unwrap().modifiers();
+    let layout = self.0.data.lock().unwrap().keyboard_layout;
+    key.to_text_with_layout(modifiers, layout)
+  }
+
+  /// See [`Window::set_key_repeat`].
+  pub fn key_repeat(&self) -> KeyRepeat {
+    self.0.data.lock().unwrap().key_repeat
+  }
+
+  /// Current power and battery state of the computer. Changes are sent with
+  /// [`Message::PowerStatusChanged`]. Same as [`power::power_status`].
+  pub fn power_status(&self) -> PowerStatus {
+    power::power_status()
+  }
+
+  /// Whether high contrast mode is on, reported with
+  /// [`Message::HighContrastChanged`] when it changes. Same as
+  /// [`utilities::is_high_contrast`](crate::utilities::is_high_contrast).
+  pub fn is_high_contrast(&self) -> bool {
+    self.0.data.lock().unwrap().high_contrast
+  }
+
+  /// Whether the system animates elements inside windows, which users turn
+  /// off to reduce motion. Reported with [`Message::AnimationsChanged`] when it
+  /// changes. Same as
+  /// [`utilities::animations_enabled`](crate::utilities::animations_enabled).
+  pub fn animations_enabled(&self) -> bool {
+    self.0.data.lock().unwrap().animations
+  }
+
+  /// Whether transparency effects are on, reported with
+  /// [`Message::TransparencyChanged`] when it changes. Same as
+  /// [`utilities::transparency_enabled`](crate::utilities::transparency_enabled).
+  pub fn transparency_enabled(&self) -> bool {
+    self.0.data.lock().unwrap().transparency
+  }
+
+  /// The user's accent color, such as to match a custom titlebar to the
+  /// system. Reported with [`Message::AccentColorChanged`] when it changes.
+  /// Same as [`utilities::accent_color`](crate::utilities::accent_color).
+  pub fn accent_color(&self) -> Color {
+    self.0.data.lock().unwrap().accent_color
+  }
+
+  /// See [`Window::set_frame_pacing`].
+  pub fn frame_pacing(&self) -> bool {
+    self.0.data.lock().unwrap().frame_pacing
+  }
+
+  /// See [`Window::set_idle_threshold`].
+  pub fn idle_threshold(&self) -> Option<Duration> {
+    self.0.data.lock().unwrap().idle_threshold
+  }
+
+  /// See [`Window::set_background_interval`].
+  pub fn background_interval(&self) -> Option<Duration> {
+    self.0.data.lock().unwrap().background_interval
+  }
+
+  /// The region of the monitor the window is snapped to, either by the user
+  /// or with [`Window::snap`]. `None` if the window is maximized, minimized,
+  /// or not aligned to any [`SnapZone`].
+  pub fn snap_zone(&self) -> Option<SnapZone> {
+    snap::snap_zone(self.0.hwnd)
+  }
+
+  pub fn is_snapped(&self) -> bool {
+    self.snap_zone().is_some()
+  }
+
   pub fn flow(&self) -> Flow {
-    self.0.data.lock().unwrap().flow
+    *self.0.flow.lock().unwrap()
   }
 
   pub fn title(&self) -> String {
@@ -392,12 +924,22 @@ impl Window {
     }
   }
 
+  /// Position of the window on the desktop, or in its parent's client area if
+  /// it's a child window.
   pub fn outer_position(&self) -> PhysicalPosition {
     let mut window_rect = RECT::default();
     let _ = unsafe { GetWindowRect(self.0.hwnd, &mut window_rect) };
-    PhysicalPosition {
+    let mut position = POINT {
       x: window_rect.left,
       y: window_rect.top,
+    };
+    if self.0.data.lock().unwrap().style.child {
+      let parent = unsafe { GetParent(self.0.hwnd) };
+      unsafe { MapWindowPoints(None, parent, std::slice::from_mut(&mut position)) };
+    }
+    PhysicalPosition {
+      x: position.x,
+      y: position.y,
     }
   }
 
@@ -420,9 +962,16 @@ impl Window {
     PhysicalPosition { x: pt.x, y: pt.y }
   }
 
-  pub fn has_focus(&self) -> bool {
+  /// Whether the window has keyboard focus.
+  pub fn is_focused(&self) -> bool {
     let style = &self.0.data.lock().unwrap().style;
-    style.focused && style.active
+    // child windows are never activated themselves
+    style.focused && (style.active || style.child)
+  }
+
+  /// Same as [`Window::is_focused`].
+  pub fn has_focus(&self) -> bool {
+    self.is_focused()
   }
 
   pub fn scale_factor(&self) -> f64 {
@@ -466,28 +1015,105 @@ impl Window {
     Monitor::new(hmonitor)
   }
 
+  /// Take a snapshot of the window and its environment (OS build, DPI
+  /// awareness, monitor layout, style flags, stage, flow, and queue state)
+  /// suitable for attaching to bug reports.
+  pub fn diagnostics_report(&self) -> DiagnosticsReport {
+    let (style, ex_style) = diagnostics::window_styles(self.0.hwnd);
+    let monitors = self
+      .available_monitors()
+      .iter()
+      .map(MonitorReport::from)
+      .collect();
+    let current_monitor = MonitorReport::from(&self.current_monitor());
+    let outer_position = self.outer_position();
+    let outer_size = self.outer_size();
+    let inner_size = self.inner_size();
+    let pending_message = self.0.sync.mailbox.is_occupied();
+    let subscribers = self.0.broadcaster.subscriber_count();
+
+    let data = self.0.data.lock().unwrap();
+    DiagnosticsReport {
+      crate_version: env!("CARGO_PKG_VERSION"),
+      os_build: windows_10_build_version(),
+      dpi_awareness: DpiAwareness::of_window(self.0.hwnd),
+      scale_factor: data.scale_factor,
+      monitors,
+      current_monitor,
+      style,
+      ex_style,
+      visibility: data.style.visibility,
+      decorations: data.style.decorations,
+      fullscreen: data.style.fullscreen,
+      resizeable: data.style.resizeable,
+      minimized: data.style.minimized,
+      maximized: data.style.maximized,
+      focused: data.style.focused && data.style.active,
+      theme: data.theme,
+      backdrop: data.backdrop,
+      cursor_mode: data.cursor.mode,
+      outer_position,
+      outer_size,
+      inner_size,
+      stage: self.0.stage.load(),
+      flow: *self.0.flow.lock().unwrap(),
+      pending_message,
+      requested_redraw: self.0.requested_redraw.load(Ordering::Acquire),
+      subscribers,
+    }
+  }
+
   pub fn key(&self, keycode: Key) -> KeyState {
-    self.0.data.lock().unwrap().input.key(keycode)
+    self.0.input.lock().unwrap().key(keycode)
+  }
+
+  /// See [`Input::just_pressed`](crate::Input::just_pressed).
+  pub fn just_pressed(&self, keycode: Key) -> bool {
+    self.0.input.lock().unwrap().just_pressed(keycode)
+  }
+
+  /// See [`Input::just_released`](crate::Input::just_released).
+  pub fn just_released(&self, keycode: Key) -> bool {
+    self.0.input.lock().unwrap().just_released(keycode)
+  }
+
+  /// See [`Input::take_text`](crate::Input::take_text).
+  pub fn take_text(&self) -> String {
+    self.0.input.lock().unwrap().take_text()
   }
 
   pub fn mouse(&self, button: MouseButton) -> ButtonState {
-    self.0.data.lock().unwrap().input.mouse(button)
+    self.0.input.lock().unwrap().mouse(button)
+  }
+
+  /// See [`Input::mouse_delta`](crate::Input::mouse_delta).
+  pub fn mouse_delta(&self) -> (f32, f32) {
+    self.0.input.lock().unwrap().mouse_delta()
+  }
+
+  /// See [`Input::scroll_delta`](crate::Input::scroll_delta).
+  pub fn scroll_delta(&self) -> (f32, f32) {
+    self.0.input.lock().unwrap().scroll_delta()
+  }
+
+  pub fn modifiers(&self) -> Modifiers {
+    self.0.input.lock().unwrap().modifiers()
   }
 
   pub fn shift(&self) -> ButtonState {
-    self.0.data.lock().unwrap().input.shift()
+    self.0.input.lock().unwrap().shift()
   }
 
   pub fn ctrl(&self) -> ButtonState {
-    self.0.data.lock().unwrap().input.ctrl()
+    self.0.input.lock().unwrap().ctrl()
   }
 
   pub fn alt(&self) -> ButtonState {
-    self.0.data.lock().unwrap().input.alt()
+    self.0.input.lock().unwrap().alt()
   }
 
   pub fn win(&self) -> ButtonState {
-    self.0.data.lock().unwrap().input.win()
+    self.0.input.lock().unwrap().win()
   }
 
   pub fn is_minimized(&self) -> bool {
@@ -502,7 +1128,7 @@ impl Window {
 
   fn force_set_cursor_icon(&self, cursor_icon: CursorIcon) {
     // self.state.write_lock().position = position;
-    Command::SetCursorIcon(cursor_icon).post(self.0.hwnd);
+    self.0.post(Command::SetCursorIcon(cursor_icon));
   }
 
   pub fn set_cursor_icon(&self, cursor_icon: CursorIcon) {
@@ -513,9 +1139,53 @@ impl Window {
     self.force_set_cursor_icon(cursor_icon)
   }
 
+  /// Show `cursor` in the client area instead of the icon set with
+  /// [`Window::set_cursor_icon`]. `None` goes back to that icon.
+  pub fn set_custom_cursor(&self, cursor: impl Into<Option<CustomCursor>>) {
+    let cursor = cursor.into();
+    {
+      let mut data = self.0.data.lock().unwrap();
+      if data.cursor.custom == cursor {
+        return;
+      }
+      data.cursor.custom = cursor.clone();
+    }
+    self.0.post(Command::SetCustomCursor(cursor));
+  }
+
+  pub fn custom_cursor(&self) -> Option<CustomCursor> {
+    self.0.data.lock().unwrap().cursor.custom.clone()
+  }
+
+  /// Post a command, unless it would cause another of the resize or move
+  /// message currently being handled. Resizing in response to `Resized` (and
+  /// so on) would otherwise feed back into itself, flooding the queue. Such
+  /// commands are deferred to the start of the next iteration instead, with
+  /// later calls replacing earlier ones.
+  fn post_or_defer(&self, command: Command) {
+    let mut data = self.0.data.lock().unwrap();
+    let defer = match command {
+      Command::SetSize(_) => data.handling_resize,
+      Command::SetPosition(_) => data.handling_move,
+      _ => false,
+    };
+    if !defer {
+      drop(data);
+      self.0.post(command);
+      return;
+    }
+
+    tracing::trace!("[`{}`]: deferring {command:?}", data.title);
+    let kind = std::mem::discriminant(&command);
+    data
+      .deferred_commands
+      .retain(|deferred| std::mem::discriminant(deferred) != kind);
+    data.deferred_commands.push(command);
+  }
+
   fn force_set_outer_position(&self, position: Position) {
     // self.state.write_lock().position = position;
-    Command::SetPosition(position).post(self.0.hwnd);
+    self.post_or_defer(Command::SetPosition(position));
   }
 
   pub fn set_outer_position(&self, position: Position) {
@@ -528,7 +1198,7 @@ impl Window {
 
   fn force_set_outer_size(&self, size: Size) {
     // self.state.write_lock().size = size;
-    Command::SetSize(size).post(self.0.hwnd);
+    self.post_or_defer(Command::SetSize(size));
   }
 
   pub fn set_outer_size(&self, size: impl Into<Size>) {
@@ -566,7 +1236,7 @@ impl Window {
       height: (window_rect.bottom - window_rect.top) as u32,
     };
 
-    Command::SetSize(adjusted_size.into()).post(self.0.hwnd);
+    self.post_or_defer(Command::SetSize(adjusted_size.into()));
   }
 
   pub fn set_inner_size(&self, size: impl Into<Size>) {
@@ -580,7 +1250,7 @@ impl Window {
 
   fn force_set_visibility(&self, visibility: Visibility) {
     self.0.data.lock().unwrap().style.visibility = visibility;
-    Command::SetVisibility(visibility).post(self.0.hwnd);
+    self.0.post(Command::SetVisibility(visibility));
   }
 
   pub fn set_visibility(&self, visibility: Visibility) {
@@ -590,9 +1260,27 @@ impl Window {
     self.force_set_visibility(visibility)
   }
 
+  /// Keep the window hidden until [`Window::notify_first_frame`] is called,
+  /// then show it. Avoids briefly showing an empty window while the first
+  /// frame is being rendered.
+  pub fn set_visible_after_first_draw(&self, visible_after_first_draw: bool) {
+    self.0.data.lock().unwrap().visible_after_first_draw = visible_after_first_draw;
+  }
+
+  /// Signal that the first frame has been presented, showing the window if it
+  /// was waiting on it. Does nothing otherwise, so it's safe to call every
+  /// frame.
+  pub fn notify_first_frame(&self) {
+    let pending =
+      std::mem::take(&mut self.0.data.lock().unwrap().visible_after_first_draw);
+    if pending {
+      self.set_visibility(Visibility::Shown);
+    }
+  }
+
   fn force_set_decorations(&self, visibility: Visibility) {
     self.0.data.lock().unwrap().style.decorations = visibility;
-    Command::SetDecorations(visibility).post(self.0.hwnd);
+    self.0.post(Command::SetDecorations(visibility));
   }
 
   pub fn set_decorations(&self, visibility: Visibility) {
@@ -602,6 +1290,85 @@ impl Window {
     self.force_set_decorations(visibility)
   }
 
+  /// Enable or disable mouse and keyboard input to the window, such as to grey
+  /// it out while a blocking operation or a modal dialog is running. Clicking
+  /// a disabled window does nothing, and it can't be focused. Messages other
+  /// than input keep arriving.
+  pub fn set_enabled(&self, enabled: bool) {
+    self.0.post(Command::SetEnabled(enabled));
+  }
+
+  /// Leave the window out of the taskbar and Alt+Tab, or add it back. See
+  /// [`WindowBuilder::with_skip_taskbar`].
+  pub fn set_skip_taskbar(&self, skip_taskbar: bool) {
+    {
+      let mut data = self.0.data.lock().unwrap();
+      if data.style.skip_taskbar == skip_taskbar {
+        return;
+      }
+      data.style.skip_taskbar = skip_taskbar;
+    }
+    self.0.post(Command::SetSkipTaskbar(skip_taskbar));
+  }
+
+  /// Keep the computer or display from going to sleep while the window is
+  /// open, such as while playing a video or rendering, or allow it again with
+  /// [`KeepAwake::None`]. Windows sharing a
+  /// [`WindowThread`](crate::WindowThread) share the last value set by any of
+  /// them.
+  pub fn set_keep_awake(&self, keep_awake: KeepAwake) {
+    {
+      let mut data = self.0.data.lock().unwrap();
+      if data.keep_awake == keep_awake {
+        return;
+      }
+      data.keep_awake = keep_awake;
+    }
+    self.0.post(Command::SetKeepAwake(keep_awake));
+  }
+
+  pub fn keep_awake(&self) -> KeepAwake {
+    self.0.data.lock().unwrap().keep_awake
+  }
+
+  /// Show the window as a modal dialog of `owner`, which is disabled until the
+  /// window closes, such as for settings dialogs and confirmation prompts.
+  /// The window becomes owned by `owner`, staying above it. Once the window is
+  /// closed or dropped, `owner` is enabled and brought back to the foreground.
+  ///
+  /// Keep iterating the messages of `owner` meanwhile, it can't react to
+  /// being disabled and enabled otherwise.
+  pub fn show_modal(&self, owner: &Window) {
+    let previous = self
+      .0
+      .data
+      .lock()
+      .unwrap()
+      .modal_owner
+      .replace(owner.0.hwnd);
+    if let Some(previous) = previous.filter(|&previous| previous != owner.0.hwnd) {
+      Command::SetEnabled(true).post(previous);
+    }
+    owner.0.post(Command::SetEnabled(false));
+    self.0.post(Command::SetOwner(owner.0.hwnd));
+    self.set_visibility(Visibility::Shown);
+    self.0.post(Command::StealForeground);
+  }
+
+  /// Allow or prevent resizing the window by dragging its border, along with
+  /// its maximize and minimize buttons, such as for a fixed-size dialog which
+  /// later becomes resizable. Setting the size programmatically is unaffected.
+  pub fn set_resizable(&self, resizable: bool) {
+    {
+      let mut data = self.0.data.lock().unwrap();
+      if data.style.resizeable == resizable {
+        return;
+      }
+      data.style.resizeable = resizable;
+    }
+    self.0.post(Command::SetResizable(resizable));
+  }
+
   fn force_set_theme(&self, theme: Theme) {
     let theme = match theme {
       Theme::Auto => {
@@ -623,7 +1390,7 @@ impl Window {
 
     self.0.data.lock().unwrap().theme = theme;
     let dark_mode = BOOL::from(theme == Theme::Dark);
-    if let Err(_error) = unsafe {
+    if let Err(error) = unsafe {
       DwmSetWindowAttribute(
         self.0.hwnd,
         Dwm::DWMWA_USE_IMMERSIVE_DARK_MODE,
@@ -631,8 +1398,9 @@ impl Window {
         std::mem::size_of::<BOOL>() as u32,
       )
     } {
-      tracing::error!("{_error}");
+      self.0.report_error(error);
     };
+    set_dark_menus(self.0.hwnd, theme == Theme::Dark);
   }
 
   pub fn set_theme(&self, theme: Theme) {
@@ -642,9 +1410,440 @@ impl Window {
     self.force_set_theme(theme)
   }
 
+  fn force_set_system_backdrop(&self, backdrop: Backdrop) {
+    self.0.data.lock().unwrap().backdrop = backdrop;
+    if let Err(error) = set_system_backdrop(self.0.hwnd, backdrop) {
+      self.0.report_error(error);
+    }
+  }
+
+  /// Set the system-drawn material behind the window, such as Mica or Acrylic.
+  pub fn set_system_backdrop(&self, backdrop: Backdrop) {
+    if backdrop == self.0.data.lock().unwrap().backdrop {
+      return;
+    }
+    self.force_set_system_backdrop(backdrop)
+  }
+
+  fn force_set_caption_color(&self, color: Option<Color>) {
+    self.0.data.lock().unwrap().caption_color = color;
+    if let Err(error) = set_dwm_color(self.0.hwnd, Dwm::DWMWA_CAPTION_COLOR, color) {
+      self.0.report_error(error);
+    }
+  }
+
+  /// Tint the native titlebar. `None` restores the system default. Requires
+  /// Windows 11.
+  pub fn set_caption_color(&self, color: impl Into<Option<Color>>) {
+    let color = color.into();
+    if color == self.0.data.lock().unwrap().caption_color {
+      return;
+    }
+    self.force_set_caption_color(color)
+  }
+
+  fn force_set_caption_text_color(&self, color: Option<Color>) {
+    self.0.data.lock().unwrap().caption_text_color = color;
+    if let Err(error) = set_dwm_color(self.0.hwnd, Dwm::DWMWA_TEXT_COLOR, color) {
+      self.0.report_error(error);
+    }
+  }
+
+  /// Set the color of the titlebar text. `None` restores the system default.
+  /// Requires Windows 11.
+  pub fn set_caption_text_color(&self, color: impl Into<Option<Color>>) {
+    let color = color.into();
+    if color == self.0.data.lock().unwrap().caption_text_color {
+      return;
+    }
+    self.force_set_caption_text_color(color)
+  }
+
+  fn force_set_border_color(&self, color: Option<Color>) {
+    self.0.data.lock().unwrap().border_color = color;
+    if let Err(error) = set_dwm_color(self.0.hwnd, Dwm::DWMWA_BORDER_COLOR, color) {
+      self.0.report_error(error);
+    }
+  }
+
+  /// Set the color of the window border. `None` restores the system default.
+  /// Requires Windows 11.
+  pub fn set_border_color(&self, color: impl Into<Option<Color>>) {
+    let color = color.into();
+    if color == self.0.data.lock().unwrap().border_color {
+      return;
+    }
+    self.force_set_border_color(color)
+  }
+
+  fn force_set_icon(&self, icon: Option<Icon>) {
+    self.0.data.lock().unwrap().icon = icon.clone();
+    self.0.post(Command::SetIcon(icon));
+  }
+
+  /// Set the icon shown in the titlebar and taskbar. `None` restores the
+  /// default icon.
+  pub fn set_icon(&self, icon: impl Into<Option<Icon>>) {
+    let icon = icon.into();
+    if icon == self.0.data.lock().unwrap().icon {
+      return;
+    }
+    self.force_set_icon(icon)
+  }
+
+  /// Set whether minimizing and/or closing hides the window to the
+  /// notification area instead.
+  pub fn set_tray_behavior(&self, tray_behavior: TrayBehavior) {
+    self.0.data.lock().unwrap().tray_behavior = tray_behavior;
+  }
+
+  /// Replace the window's [`MessageTranslator`].
+  pub fn set_message_translator(&self, translator: impl MessageTranslator + 'static) {
+    self.0.data.lock().unwrap().message_translator = Some(Arc::new(translator));
+  }
+
+  /// Remove the window's [`MessageTranslator`], restoring the built-in
+  /// translation for every message.
+  pub fn clear_message_translator(&self) {
+    self.0.data.lock().unwrap().message_translator = None;
+  }
+
+  /// Chain `subclass` in front of the window procedure, on the window thread,
+  /// ahead of any subclass added before it. See [`Subclass`].
+  pub fn add_subclass(&self, subclass: impl Subclass + 'static) -> SubclassId {
+    let id = SubclassId::next();
+    self.0.post(Command::AddSubclass(PendingSubclass {
+      id,
+      subclass: Arc::new(subclass),
+    }));
+    id
+  }
+
+  /// Remove a subclass added with [`Window::add_subclass`]. Subclasses are
+  /// removed on their own once the window is destroyed.
+  pub fn remove_subclass(&self, id: SubclassId) {
+    self.0.post(Command::RemoveSubclass(id));
+  }
+
+  /// Replace the window's [`MessageFilter`].
+  pub fn set_message_filter(&self, filter: impl MessageFilter + 'static) {
+    self.0.data.lock().unwrap().message_filter = Some(Arc::new(filter));
+  }
+
+  /// Remove the window's [`MessageFilter`], letting the crate handle every
+  /// message again.
+  pub fn clear_message_filter(&self) {
+    self.0.data.lock().unwrap().message_filter = None;
+  }
+
+  /// Replace the window's [`MessageReplier`]. Frame changes it makes through
+  /// `WM_NCCALCSIZE` apply once Windows next recalculates the frame, such as
+  /// after [`Window::set_decorations`].
+  pub fn set_message_replier(&self, replier: impl MessageReplier + 'static) {
+    self.0.data.lock().unwrap().message_replier = Some(Arc::new(replier));
+  }
+
+  /// Remove the window's [`MessageReplier`], leaving every message the crate
+  /// doesn't handle to `DefWindowProc` again.
+  pub fn clear_message_replier(&self) {
+    self.0.data.lock().unwrap().message_replier = None;
+  }
+
+  /// Name screen readers announce for the window, instead of its title, or
+  /// the title again with `None`.
+  #[cfg(feature = "accessibility")]
+  pub fn set_accessible_name(&self, name: Option<&str>) {
+    let data = self.0.data.lock().unwrap();
+    data.accessible.lock().unwrap().name = name.map(str::to_owned);
+  }
+
+  /// Longer description screen readers offer for the window, such as what it
+  /// is for.
+  #[cfg(feature = "accessibility")]
+  pub fn set_accessible_description(&self, description: Option<&str>) {
+    let data = self.0.data.lock().unwrap();
+    data.accessible.lock().unwrap().description = description.map(str::to_owned);
+  }
+
+  /// Answer UI Automation and accessibility requests with `handler` before
+  /// the built-in provider, such as to expose the window's widgets through
+  /// accesskit. See [`AccessibilityHandler`].
+  #[cfg(feature = "accessibility")]
+  pub fn set_accessibility_handler(&self, handler: impl AccessibilityHandler + 'static) {
+    self.0.data.lock().unwrap().accessibility_handler = Some(Arc::new(handler));
+  }
+
+  /// Remove the window's [`AccessibilityHandler`], leaving requests to the
+  /// built-in provider.
+  #[cfg(feature = "accessibility")]
+  pub fn clear_accessibility_handler(&self) {
+    self.0.data.lock().unwrap().accessibility_handler = None;
+  }
+
+  /// Bring the window to the foreground and give it focus, even when another
+  /// app is active. A plain `SetForegroundWindow` is usually refused by
+  /// Windows' foreground lock in that case; this works around it the way
+  /// Windows allows. If Windows still refuses, such as while the user is
+  /// typing into another app, the taskbar button flashes until the window is
+  /// activated.
+  pub fn steal_foreground(&self) {
+    self.0.post(Command::StealForeground);
+  }
+
+  /// Bring the window to the foreground and give it focus, if Windows allows
+  /// it. Windows only does while the app is in the foreground or was the last
+  /// to receive input, flashing the taskbar button instead otherwise. Use
+  /// [`Window::steal_foreground`] to get around that, or
+  /// [`Window::request_user_attention`] to leave it up to the user.
+  pub fn focus(&self) {
+    self.0.post(Command::Focus);
+  }
+
+  /// Move the window above every other window, without activating it.
+  /// Windows which are always on top stay above it.
+  pub fn bring_to_front(&self) {
+    self
+      .0
+      .post(Command::SetZOrder(WindowsAndMessaging::HWND_TOP));
+  }
+
+  /// Move the window below every other window.
+  pub fn send_to_back(&self) {
+    self
+      .0
+      .post(Command::SetZOrder(WindowsAndMessaging::HWND_BOTTOM));
+  }
+
+  /// Move the window right below `other`, such as to keep a palette stacked
+  /// under another.
+  pub fn place_below(&self, other: &Window) {
+    self.0.post(Command::SetZOrder(other.0.hwnd));
+  }
+
+  /// Move the window right above `other`, such as to keep a palette stacked
+  /// over the main window.
+  pub fn place_above(&self, other: &Window) {
+    // placed below whichever window is right above `other`
+    let above = unsafe { GetWindow(other.0.hwnd, WindowsAndMessaging::GW_HWNDPREV) };
+    if above == self.0.hwnd {
+      return;
+    }
+    // nothing above, so `other` is at the top
+    let insert_after = match above.0 {
+      0 => WindowsAndMessaging::HWND_TOP,
+      _ => above,
+    };
+    self.0.post(Command::SetZOrder(insert_after));
+  }
+
+  /// Replace the app's jump list, shown when right-clicking its taskbar
+  /// button. Clicked tasks forwarded with
+  /// [`forward_launched_task`](crate::window::jump_list::forward_launched_task)
+  /// are reported to this window with [`Message::JumpTask`]. The jump list
+  /// stays after the app exits, until it's replaced.
+  pub fn set_jump_list(&self, jump_list: &JumpList) -> Result<(), WindowError> {
+    jump_list.apply()?;
+    jump_list::set_target(self.0.hwnd);
+    Ok(())
+  }
+
+  /// Flash the window's taskbar button, such as when a long task finishes
+  /// while the app is in the background, or stop flashing with `None`. Does
+  /// nothing while the window is in the foreground.
+  pub fn request_user_attention(&self, attention: Option<UserAttentionType>) {
+    self.0.post(Command::RequestUserAttention(attention));
+  }
+
+  /// Show a message box owned by the window, such as for errors and
+  /// confirmations, returning the button the user picked once it's closed.
+  /// The window is disabled meanwhile. Messages the window receives while the
+  /// box is open aren't waited on, so only the latest one is left for the next
+  /// iteration.
+  pub fn message_box(
+    &self,
+    title: impl AsRef<str>,
+    text: impl AsRef<str>,
+    kind: MessageBoxKind,
+  ) -> MessageBoxResult {
+    // the box disables the window, which the window thread has to answer
+    let previous = self.0.sync.suspend_lockstep();
+    let result = dialog::message_box(self.0.hwnd, title.as_ref(), text.as_ref(), kind);
+    self.0.sync.resume_lockstep(previous);
+    result
+  }
+
+  /// Copy what the window shows in its client area, such as for a "copy
+  /// screenshot" command or visual regression tests. Content drawn with
+  /// DirectX or OpenGL is included.
+  pub fn capture_client(&self) -> Result<RgbaImage, WindowError> {
+    // the window thread draws the capture
+    let previous = self.0.sync.suspend_lockstep();
+    let result = capture::capture_client(self.0.hwnd, self.inner_size());
+    self.0.sync.resume_lockstep(previous);
+    result
+  }
+
+  /// Start moving the window as if its titlebar was dragged, such as from a
+  /// custom titlebar drawn by the app. Call this while the left mouse button
+  /// is held; the move ends when it's released.
+  pub fn drag_window(&self) {
+    self.0.post(Command::DragWindow);
+  }
+
+  /// Declare an area of the client area, such as a row of browser-style tabs,
+  /// where clicks go to the app as usual but dragging further than the system
+  /// drag threshold moves the window instead. A left button release is sent
+  /// once the move ends. Replaces any previous tab strip.
+  pub fn set_tab_strip(&self, position: PhysicalPosition, size: PhysicalSize) {
+    self.0.data.lock().unwrap().tab_strip = Some(TabStrip::new(position, size));
+  }
+
+  /// Remove the area set with [`Window::set_tab_strip`].
+  pub fn clear_tab_strip(&self) {
+    self.0.data.lock().unwrap().tab_strip = None;
+  }
+
+  /// Decide which parts of the window move or resize it, and which go to the
+  /// app, such as for a titlebar drawn by the app. See [`HitTester`].
+  pub fn set_hit_tester(&self, hit_tester: impl HitTester + 'static) {
+    self.0.data.lock().unwrap().hit_tester = Some(Arc::new(hit_tester));
+  }
+
+  /// Remove the window's [`HitTester`], leaving hit testing to Windows.
+  pub fn clear_hit_tester(&self) {
+    self.0.data.lock().unwrap().hit_tester = None;
+  }
+
+  /// Set the speed of the low-frequency (left) and high-frequency (right)
+  /// rumble motors of the gamepad `id` reported in [`Message::Gamepad`], each
+  /// from `0.0` to `1.0`. Rumble keeps going until set back to zero.
+  #[cfg(feature = "gamepad")]
+  pub fn set_gamepad_rumble(&self, id: u32, low_frequency: f32, high_frequency: f32) {
+    input::gamepad::set_rumble(id, low_frequency, high_frequency);
+  }
+
+  /// Report default audio device changes and this process's volume and mute
+  /// changes to the window with [`Message::Audio`]. Off by default; enable it
+  /// on one window to avoid receiving each event once per window.
+  #[cfg(feature = "audio")]
+  pub fn set_audio_notifications(&self, enabled: bool) {
+    self.0.post(Command::SetAudioNotifications(enabled));
+  }
+
+  /// Let processes running at a lower integrity level send `msg_id` to this
+  /// window. Windows blocks such messages by default when the app is elevated,
+  /// which silently breaks things like `WM_COPYDATA` or file drops coming
+  /// from non-elevated processes (file drops also need `WM_COPYGLOBALDATA`,
+  /// `0x0049`).
+  pub fn allow_message_from_lower_integrity(&self, msg_id: u32) {
+    if let Err(e) = unsafe {
+      ChangeWindowMessageFilterEx(
+        self.0.hwnd,
+        msg_id,
+        WindowsAndMessaging::MSGFLT_ALLOW,
+        None,
+      )
+    } {
+      self.0.report_error(e);
+    }
+  }
+
+  /// Append an item to the window menu (opened from the titlebar icon or with
+  /// Alt+Space). Selecting it sends
+  /// [`Message::SystemCommand`] with [`SystemCommand::Custom`] containing `id`,
+  /// which must be at most [`SystemCommand::MAX_CUSTOM_ID`].
+  pub fn add_system_menu_item(&self, id: u16, label: impl AsRef<str>) {
+    debug_assert!(id <= SystemCommand::MAX_CUSTOM_ID);
+    self
+      .0
+      .post(Command::AppendSystemMenuItem(id, HSTRING::from(label.as_ref())));
+  }
+
+  pub fn add_system_menu_separator(&self) {
+    self.0.post(Command::AppendSystemMenuSeparator);
+  }
+
+  /// Show or hide a check mark next to a custom window menu item.
+  pub fn set_system_menu_item_checked(&self, id: u16, checked: bool) {
+    self.0.post(Command::SetSystemMenuItemChecked(id, checked));
+  }
+
+  pub fn set_system_menu_item_enabled(&self, id: u16, enabled: bool) {
+    self.0.post(Command::SetSystemMenuItemEnabled(id, enabled));
+  }
+
+  pub fn remove_system_menu_item(&self, id: u16) {
+    self.0.post(Command::RemoveSystemMenuItem(id));
+  }
+
+  /// Change how the iterator waits for messages, such as to schedule the next
+  /// wake up with [`Flow::WaitUntil`].
+  pub fn set_flow(&self, flow: Flow) {
+    *self.0.flow.lock().unwrap() = flow;
+  }
+
+  /// While the window is hidden or minimized with [`Flow::Poll`], wait up to
+  /// `interval` for a message before yielding [`LoopMessage::Empty`] instead
+  /// of returning immediately. This keeps background windows, such as those
+  /// in the tray, from spinning the CPU. Full-rate polling resumes as soon as
+  /// the window is shown or restored. `None` always polls at full rate.
+  pub fn set_background_interval(&self, interval: impl Into<Option<Duration>>) {
+    self.0.data.lock().unwrap().background_interval = interval.into();
+  }
+
+  /// Filter auto-repeat [`KeyState::Held`] messages, such as for game input
+  /// which only cares about presses and releases. See [`KeyRepeat`].
+  pub fn set_key_repeat(&self, key_repeat: KeyRepeat) {
+    self.0.data.lock().unwrap().key_repeat = key_repeat;
+  }
+
+  /// Send [`Message::Timer`] with `id` after `interval`, and every `interval`
+  /// after that if `repeating`, such as for blinking carets or autosaving.
+  /// Setting a timer whose id is already running restarts it. Timers are
+  /// limited to the Windows timer resolution of around 10-16ms.
+  pub fn set_timer(&self, id: u32, interval: Duration, repeating: bool) {
+    self.0.post(Command::SetTimer {
+      id,
+      interval,
+      repeating,
+    });
+  }
+
+  /// Stop a timer started with [`Window::set_timer`]. A message from it may
+  /// still be on its way.
+  pub fn cancel_timer(&self, id: u32) {
+    self.0.post(Command::CancelTimer(id));
+  }
+
+  /// Send [`Message::UserIdle`] once there has been no keyboard or mouse input
+  /// to any app for `threshold`, and [`Message::UserActive`] when input
+  /// resumes, such as for screensavers or presence indicators. `None` stops
+  /// watching for inactivity. Use
+  /// [`idle_time`](crate::utilities::idle_time) to check once.
+  pub fn set_idle_threshold(&self, threshold: impl Into<Option<Duration>>) {
+    self.0.post(Command::SetIdleThreshold(threshold.into()));
+  }
+
+  /// Snap the window to a region of the current monitor's work area. Does
+  /// nothing while the window is fullscreen.
+  pub fn snap(&self, zone: SnapZone) {
+    self.0.post(Command::Snap(zone));
+  }
+
+  /// Hide the window and show its icon in the notification area.
+  pub fn hide_to_tray(&self) {
+    self.0.post(Command::HideToTray);
+  }
+
+  /// Remove the window's tray icon and show the window again. Does nothing if
+  /// the window isn't in the tray.
+  pub fn restore_from_tray(&self) {
+    self.0.post(Command::RestoreFromTray);
+  }
+
   fn force_set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
     self.0.data.lock().unwrap().style.fullscreen = fullscreen;
-    Command::SetFullscreen(fullscreen).post(self.0.hwnd);
+    self.0.post(Command::SetFullscreen(fullscreen));
   }
 
   pub fn set_fullscreen(&self, fullscreen: Option<Fullscreen>) {
@@ -654,17 +1853,36 @@ impl Window {
     self.force_set_fullscreen(fullscreen)
   }
 
+  /// Ask the window thread to apply the encoded title and subtitle unless an
+  /// earlier request is still waiting, in which case that one picks up the
+  /// new text.
+  fn update_window_text(&self) {
+    if std::mem::replace(&mut self.0.data.lock().unwrap().window_text_pending, true) {
+      return;
+    }
+    if let Err(e) = Command::UpdateWindowText.try_post(self.0.hwnd) {
+      // nothing is waiting after all, so the next change has to post again
+      self.0.data.lock().unwrap().window_text_pending = false;
+      self.0.report_error(e);
+    }
+  }
+
   fn force_set_title(&self, title: impl AsRef<str>) {
-    self.0.data.lock().unwrap().title = title.as_ref().into();
-    let title = HSTRING::from(format!(
-      "{}{}",
-      title.as_ref(),
-      self.0.data.lock().unwrap().subtitle
-    ));
-    Command::SetWindowText(title).post(self.0.hwnd);
+    {
+      let mut data = self.0.data.lock().unwrap();
+      let data = &mut *data;
+      data.title.clear();
+      data.title.push_str(title.as_ref());
+      data.title_wide.clear();
+      data.title_wide.extend(data.title.encode_utf16());
+    }
+    self.update_window_text();
   }
 
-  /// Set the title of the window
+  /// Set the title of the window. Cheap enough to call every frame (e.g. to
+  /// show a frame counter): unchanged titles return immediately, changes are
+  /// encoded into buffers reused between calls, and changes made faster than
+  /// the window thread applies them are coalesced.
   pub fn set_title(&self, title: impl AsRef<str>) {
     if title.as_ref() == self.0.data.lock().unwrap().title {
       return;
@@ -674,7 +1892,7 @@ impl Window {
 
   fn force_set_cursor_mode(&self, cursor_mode: CursorMode) {
     self.0.data.lock().unwrap().cursor.mode = cursor_mode;
-    Command::SetCursorMode(cursor_mode).post(self.0.hwnd);
+    self.0.post(Command::SetCursorMode(cursor_mode));
   }
 
   pub fn set_cursor_mode(&self, cursor_mode: CursorMode) {
@@ -686,7 +1904,7 @@ impl Window {
 
   fn force_set_cursor_visibility(&self, cursor_visibility: Visibility) {
     self.0.data.lock().unwrap().cursor.visibility = cursor_visibility;
-    Command::SetCursorVisibility(cursor_visibility).post(self.0.hwnd);
+    self.0.post(Command::SetCursorVisibility(cursor_visibility));
   }
 
   pub fn set_cursor_visibility(&self, cursor_visibility: Visibility) {
@@ -697,16 +1915,19 @@ impl Window {
   }
 
   fn force_set_subtitle(&self, subtitle: impl AsRef<str>) {
-    self.0.data.lock().unwrap().subtitle = subtitle.as_ref().into();
-    let title = HSTRING::from(format!(
-      "{}{}",
-      self.0.data.lock().unwrap().title,
-      subtitle.as_ref()
-    ));
-    Command::SetWindowText(title).post(self.0.hwnd);
+    {
+      let mut data = self.0.data.lock().unwrap();
+      let data = &mut *data;
+      data.subtitle.clear();
+      data.subtitle.push_str(subtitle.as_ref());
+      data.subtitle_wide.clear();
+      data.subtitle_wide.extend(data.subtitle.encode_utf16());
+    }
+    self.update_window_text();
   }
 
-  /// Set text to appear after the title of the window
+  /// Set text to appear after the title of the window. See
+  /// [`Window::set_title`].
   pub fn set_subtitle(&self, subtitle: impl AsRef<str>) {
     if subtitle.as_ref() == self.0.data.lock().unwrap().subtitle {
       return;
@@ -715,19 +1936,62 @@ impl Window {
   }
 
   fn force_request_redraw(&self) {
-    self.0.data.lock().unwrap().requested_redraw = true;
-    Command::Redraw.post(self.0.hwnd);
+    self.0.requested_redraw.store(true, Ordering::Release);
+    self.0.post(Command::Redraw);
   }
 
   /// Request a new Draw event
   pub fn request_redraw(&self) {
-    if self.0.data.lock().unwrap().requested_redraw {
+    if self.0.requested_redraw.load(Ordering::Acquire) {
       return;
     }
     self.force_request_redraw()
   }
 
-  #[cfg(all(feature = "rwh_06", not(feature = "rwh_05")))]
+  /// Deliver [`Message::Paint`] at most once per display refresh. Redraws
+  /// requested again before the next refresh wait for it, so rendering as
+  /// fast as possible settles at the refresh rate instead of piling up
+  /// messages. Redraws requested while one is pending are always merged.
+  pub fn set_frame_pacing(&self, frame_pacing: bool) {
+    let mut data = self.0.data.lock().unwrap();
+    data.frame_pacing = frame_pacing;
+    data.last_paint_refresh = None;
+  }
+
+  /// Block until the compositor presents its next frame, which happens once
+  /// per display refresh. Software renderers and swapchains presenting
+  /// without vsync can call this before presenting to pace themselves to the
+  /// display without tearing or spinning. Can be called from any thread.
+  pub fn wait_for_vblank(&self) -> Result<(), WindowError> {
+    unsafe { DwmFlush() }?;
+    Ok(())
+  }
+
+  /// Immediately draw to the client area using GDI. This is intended for quick
+  /// prototypes, examples, and debugging tools rather than as a replacement
+  /// for a proper renderer. Best called in response to [`Message::Paint`],
+  /// which paints within the window's `BeginPaint` and `EndPaint`.
+  ///
+  /// ```no_run
+  /// # use witer::prelude::*;
+  /// # let window = Window::builder().build()?;
+  /// for message in &window {
+  ///   if let Message::Paint = message {
+  ///     window.paint(|ctx| {
+  ///       ctx.fill(Color::rgb(30, 30, 30));
+  ///       ctx.set_text_color(Color::WHITE);
+  ///       ctx.text((10, 10), "Hello, world!");
+  ///     });
+  ///   }
+  /// }
+  /// # Ok::<(), witer::error::WindowError>(())
+  /// ```
+  pub fn paint(&self, f: impl FnOnce(&mut PaintContext)) {
+    let paint_dc = self.0.data.lock().unwrap().paint_dc;
+    paint::paint(self.0.hwnd, paint_dc, self.inner_size(), f)
+  }
+
+  #[cfg(feature = "rwh_06")]
   pub fn raw_window_handle(&self) -> RawWindowHandle {
     let mut handle = Win32WindowHandle::new(
       std::num::NonZeroIsize::new(self.0.hwnd.0)
@@ -739,54 +2003,56 @@ impl Window {
     RawWindowHandle::from(handle)
   }
 
-  #[cfg(all(feature = "rwh_06", not(feature = "rwh_05")))]
+  #[cfg(feature = "rwh_06")]
   pub fn raw_display_handle(&self) -> RawDisplayHandle {
     let handle = WindowsDisplayHandle::new();
     RawDisplayHandle::from(handle)
   }
 }
 
-#[cfg(all(feature = "rwh_06", not(feature = "rwh_05")))]
+#[cfg(feature = "rwh_06")]
 impl HasWindowHandle for Window {
   fn window_handle(&self) -> Result<WindowHandle<'_>, HandleError> {
     Ok(unsafe { WindowHandle::borrow_raw(self.raw_window_handle()) })
   }
 }
 
-#[cfg(all(feature = "rwh_05", not(feature = "rwh_06")))]
-unsafe impl HasRawWindowHandle for Window {
-  fn raw_window_handle(&self) -> RawWindowHandle {
-    let mut handle = Win32WindowHandle::empty();
+// With `rwh_06` also enabled, the inherent methods of the same name shadow these, so
+// call them as `HasRawWindowHandle::raw_window_handle(&window)`.
+#[cfg(feature = "rwh_05")]
+unsafe impl rwh_05::HasRawWindowHandle for Window {
+  fn raw_window_handle(&self) -> rwh_05::RawWindowHandle {
+    let mut handle = rwh_05::Win32WindowHandle::empty();
     handle.hwnd = self.0.hwnd.0 as *mut std::ffi::c_void;
     handle.hinstance = self.0.hinstance.0 as *mut std::ffi::c_void;
-    RawWindowHandle::Win32(handle)
+    rwh_05::RawWindowHandle::Win32(handle)
   }
 }
 
-#[cfg(all(feature = "rwh_06", not(feature = "rwh_05")))]
+#[cfg(feature = "rwh_06")]
 impl HasDisplayHandle for Window {
   fn display_handle(&self) -> Result<DisplayHandle<'_>, HandleError> {
     Ok(unsafe { DisplayHandle::borrow_raw(self.raw_display_handle()) })
   }
 }
 
-#[cfg(all(feature = "rwh_05", not(feature = "rwh_06")))]
-unsafe impl HasRawDisplayHandle for Window {
-  fn raw_display_handle(&self) -> RawDisplayHandle {
-    RawDisplayHandle::Windows(WindowsDisplayHandle::empty())
+#[cfg(feature = "rwh_05")]
+unsafe impl rwh_05::HasRawDisplayHandle for Window {
+  fn raw_display_handle(&self) -> rwh_05::RawDisplayHandle {
+    rwh_05::RawDisplayHandle::Windows(rwh_05::WindowsDisplayHandle::empty())
   }
 }
 
 impl Window {
   fn iter(&self) -> MessageIterator {
-    let current_stage = self.0.data.lock().unwrap().stage;
+    let current_stage = self.0.stage.load();
     match current_stage {
       Stage::Ready => {
         tracing::trace!(
           "[`{}`]: preparing to immutably iterate over messages",
           self.title()
         );
-        self.0.data.lock().unwrap().stage = Stage::Looping;
+        self.0.stage.store(Stage::Looping);
       }
       Stage::ExitLoop => {
         tracing::error!(
@@ -803,14 +2069,14 @@ impl Window {
   }
 
   fn iter_mut(&mut self) -> MessageIteratorMut {
-    let current_stage = self.0.data.lock().unwrap().stage;
+    let current_stage = self.0.stage.load();
     match current_stage {
       Stage::Ready => {
         tracing::trace!(
           "[`{}`]: preparing to mutably iterate over messages",
           self.title()
         );
-        self.0.data.lock().unwrap().stage = Stage::Looping;
+        self.0.stage.store(Stage::Looping);
       }
       Stage::ExitLoop => {
         tracing::error!(
@@ -825,6 +2091,22 @@ impl Window {
     }
     MessageIteratorMut { window: self }
   }
+
+  /// Run the message loop, calling `f` with each message until the window
+  /// closes. An alternative to iterating over the window for code structured
+  /// around an event callback. The window is destroyed when this returns,
+  /// unless other clones of it are still alive.
+  pub fn run(self, f: impl FnMut(&Window, Message)) {
+    self.run_return(f);
+  }
+
+  /// Like [`Window::run`], but borrows the window so it can still be queried
+  /// after the loop exits.
+  pub fn run_return(&self, mut f: impl FnMut(&Window, Message)) {
+    for message in self.iter() {
+      f(self, message);
+    }
+  }
 }
 
 pub struct MessageIterator<'a> {
@@ -835,7 +2117,7 @@ impl<'a> Iterator for MessageIterator<'a> {
   type Item = Message;
 
   fn next(&mut self) -> Option<Self::Item> {
-    self.window.next_message()
+    self.window.next_message_blocking()
   }
 }
 
@@ -856,7 +2138,7 @@ impl<'a> Iterator for MessageIteratorMut<'a> {
   type Item = Message;
 
   fn next(&mut self) -> Option<Self::Item> {
-    self.window.next_message()
+    self.window.next_message_blocking()
   }
 }
 
@@ -0,0 +1,150 @@
+use std::sync::{Arc, Mutex};
+
+use windows::{
+  core::{implement, IUnknown, VARIANT},
+  Win32::{
+    Foundation::{HWND, LPARAM, LRESULT, S_OK, WPARAM},
+    UI::Accessibility::{
+      self,
+      IRawElementProviderSimple,
+      IRawElementProviderSimple_Impl,
+      ProviderOptions,
+      UiaClientsAreListening,
+      UiaHostProviderFromHwnd,
+      UiaRaiseAutomationEvent,
+      UiaReturnRawElementProvider,
+      UIA_PATTERN_ID,
+      UIA_PROPERTY_ID,
+    },
+  },
+};
+
+/*
+  Windows already exposes every window to UI Automation through a default provider for
+  its HWND, which reports the title as the name along with the window pattern (minimize,
+  maximize, close) and focus. The provider here is layered on top of it, only overriding
+  the name and help text when the app set them, and deferring everything else to the
+  default provider.
+
+  Apps describing the widgets inside the window, such as through accesskit, need to
+  answer `WM_GETOBJECT` themselves. An `AccessibilityHandler` is given the message first,
+  replacing this provider whenever it answers.
+*/
+
+/// Answers `WM_GETOBJECT` for a window instead of the built-in provider, such
+/// as an accesskit adapter exposing the window's widgets to screen readers.
+/// Set with
+/// [`Window::set_accessibility_handler`](crate::Window::set_accessibility_handler).
+///
+/// Returning `None` falls back to the built-in provider. Handlers are called
+/// on the window thread, and must not block on the window's iterator.
+///
+/// Closures with the same signature as [`AccessibilityHandler::get_object`]
+/// implement this trait.
+pub trait AccessibilityHandler: Send + Sync {
+  fn get_object(&self, hwnd: HWND, wparam: WPARAM, lparam: LPARAM) -> Option<LRESULT>;
+}
+
+impl<F> AccessibilityHandler for F
+where
+  F: Fn(HWND, WPARAM, LPARAM) -> Option<LRESULT> + Send + Sync,
+{
+  fn get_object(&self, hwnd: HWND, wparam: WPARAM, lparam: LPARAM) -> Option<LRESULT> {
+    self(hwnd, wparam, lparam)
+  }
+}
+
+impl std::fmt::Debug for dyn AccessibilityHandler {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("AccessibilityHandler")
+  }
+}
+
+/// What the built-in provider reports, shared with it as UI Automation may
+/// query it from any thread.
+#[derive(Debug, Default)]
+pub(crate) struct AccessibleInfo {
+  pub name: Option<String>,
+  pub description: Option<String>,
+}
+
+/// Answer `WM_GETOBJECT` with the built-in provider, if it asks for the UI
+/// Automation root of the window.
+pub(crate) fn get_object(
+  hwnd: HWND,
+  wparam: WPARAM,
+  lparam: LPARAM,
+  info: &Arc<Mutex<AccessibleInfo>>,
+) -> Option<LRESULT> {
+  if lparam.0 as i32 != Accessibility::UiaRootObjectId {
+    return None;
+  }
+  let provider: IRawElementProviderSimple = WindowProvider {
+    hwnd,
+    info: info.clone(),
+  }
+  .into();
+  Some(unsafe { UiaReturnRawElementProvider(hwnd, wparam, lparam, &provider) })
+}
+
+/// Let screen readers announce the window with its accessible name once it
+/// gains focus.
+pub(crate) fn raise_focus_changed(hwnd: HWND, info: &Arc<Mutex<AccessibleInfo>>) {
+  if !unsafe { UiaClientsAreListening() }.as_bool() {
+    return;
+  }
+  let provider: IRawElementProviderSimple = WindowProvider {
+    hwnd,
+    info: info.clone(),
+  }
+  .into();
+  if let Err(e) = unsafe {
+    UiaRaiseAutomationEvent(&provider, Accessibility::UIA_AutomationFocusChangedEventId)
+  } {
+    tracing::error!("{e}");
+  }
+}
+
+/// Release the providers UI Automation still holds for a window being
+/// destroyed.
+pub(crate) fn disconnect(hwnd: HWND) {
+  unsafe { UiaReturnRawElementProvider(hwnd, WPARAM(0), LPARAM(0), None) };
+}
+
+#[implement(IRawElementProviderSimple)]
+struct WindowProvider {
+  hwnd: HWND,
+  info: Arc<Mutex<AccessibleInfo>>,
+}
+
+impl IRawElementProviderSimple_Impl for WindowProvider {
+  fn ProviderOptions(&self) -> windows::core::Result<ProviderOptions> {
+    Ok(Accessibility::ProviderOptions_ServerSideProvider)
+  }
+
+  fn GetPatternProvider(
+    &self,
+    _patternid: UIA_PATTERN_ID,
+  ) -> windows::core::Result<IUnknown> {
+    // succeeding without a provider leaves patterns to the default provider
+    Err(S_OK.into())
+  }
+
+  fn GetPropertyValue(
+    &self,
+    propertyid: UIA_PROPERTY_ID,
+  ) -> windows::core::Result<VARIANT> {
+    let info = self.info.lock().unwrap();
+    let value = match propertyid {
+      Accessibility::UIA_NamePropertyId => info.name.as_deref(),
+      Accessibility::UIA_HelpTextPropertyId => info.description.as_deref(),
+      _ => None,
+    };
+    // empty values are answered by the default provider
+    Ok(value.map(VARIANT::from).unwrap_or_default())
+  }
+
+  fn HostRawElementProvider(&self) -> windows::core::Result<IRawElementProviderSimple> {
+    unsafe { UiaHostProviderFromHwnd(self.hwnd) }
+  }
+}
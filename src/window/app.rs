@@ -0,0 +1,107 @@
+use std::{
+  sync::{Arc, Mutex, Once, Weak},
+  time::{Duration, Instant},
+};
+
+use windows::Win32::{
+  Foundation::{BOOL, FALSE},
+  System::Console::SetConsoleCtrlHandler,
+};
+
+use super::{command::Command, data::Internal, stage::Stage, Window};
+
+/*
+  Every window is tracked here from creation so a quit can reach all of them without
+  the app holding on to each one. Only weak handles are kept, so tracking a window
+  never keeps it alive.
+*/
+
+static WINDOWS: Mutex<Vec<Weak<Internal>>> = Mutex::new(Vec::new());
+
+pub(crate) fn track(window: &Window) {
+  let mut windows = WINDOWS.lock().unwrap();
+  windows.retain(|window| window.strong_count() > 0);
+  windows.push(Arc::downgrade(&window.0));
+}
+
+/// Ask every live window to quit, such as for a "File → Exit" menu item.
+///
+/// Each window's iterator yields
+/// [`LoopMessage::QuitRequested`](crate::LoopMessage::QuitRequested) and then
+/// closes the window, ending its loop as if [`Window::close`] had been called.
+/// Requests are sent newest window first, so secondary windows are asked to
+/// quit before the window that opened them. Windows which are already closing
+/// are skipped.
+pub fn quit() {
+  let windows: Vec<Arc<Internal>> = {
+    let mut windows = WINDOWS.lock().unwrap();
+    windows.retain(|window| window.strong_count() > 0);
+    windows.iter().rev().filter_map(Weak::upgrade).collect()
+  };
+
+  for window in windows {
+    if window.is_closing() {
+      continue;
+    }
+    tracing::trace!("requesting window quit");
+    Command::Quit.post(window.hwnd);
+  }
+}
+
+/// Longest the console handler waits for a window's loop to end before
+/// giving up on it. Windows kills the process 5 seconds after the console is
+/// closed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Close every live window and join its thread when the console the app runs
+/// in is interrupted with Ctrl+C or Ctrl+Break, closed, or the user logs off,
+/// before the process is terminated. Without this, console-hosted tools can
+/// be torn down while the window thread is in the middle of a Win32 call.
+///
+/// Each window's iterator yields [`LoopMessage::Exit`](crate::LoopMessage::Exit)
+/// as if [`Window::close`] had been called, and the window is destroyed once
+/// its loop has ended, even if the app still holds on to it. The process then
+/// terminates as it would have without the handler. Windows whose loop
+/// doesn't end in time are left alone.
+///
+/// Only the first call installs the handler.
+pub fn shutdown_on_console_exit() {
+  static INSTALL: Once = Once::new();
+  INSTALL.call_once(|| {
+    if let Err(e) = unsafe { SetConsoleCtrlHandler(Some(console_ctrl_handler), true) } {
+      tracing::error!("{e}");
+    }
+  });
+}
+
+unsafe extern "system" fn console_ctrl_handler(_ctrl_type: u32) -> BOOL {
+  let windows: Vec<Arc<Internal>> = {
+    let mut windows = WINDOWS.lock().unwrap();
+    windows.retain(|window| window.strong_count() > 0);
+    windows.iter().rev().filter_map(Weak::upgrade).collect()
+  };
+
+  tracing::trace!("console is exiting, closing windows");
+  for window in &windows {
+    Window(window.clone()).close();
+  }
+
+  let deadline = Instant::now() + SHUTDOWN_TIMEOUT;
+  for window in windows {
+    // the window thread can only be joined once the loop has taken its last message
+    while window.stage.load() == Stage::Closing && Instant::now() < deadline {
+      std::thread::sleep(Duration::from_millis(10));
+    }
+    match window.stage.load() {
+      Stage::ExitLoop | Stage::Destroyed => (),
+      _ => {
+        tracing::warn!("window loop did not end in time, skipping it");
+        continue;
+      }
+    }
+    window.destroy();
+  }
+
+  // carry on with the default handling, which terminates the process
+  FALSE
+}
@@ -0,0 +1,351 @@
+use std::{
+  cell::RefCell,
+  collections::HashMap,
+  sync::mpsc::{self, Sender},
+  thread::JoinHandle,
+};
+
+use windows::{
+  core::{implement, GUID, PCWSTR},
+  Win32::{
+    Foundation::{BOOL, HWND},
+    Media::Audio::{
+      eCapture,
+      eConsole,
+      eRender,
+      AudioSessionDisconnectReason,
+      AudioSessionState,
+      EDataFlow,
+      ERole,
+      IAudioSessionControl,
+      IAudioSessionEvents,
+      IAudioSessionEvents_Impl,
+      IAudioSessionManager,
+      IMMDeviceEnumerator,
+      IMMNotificationClient,
+      IMMNotificationClient_Impl,
+      MMDeviceEnumerator,
+      DEVICE_STATE,
+    },
+    System::Com::{CoCreateInstance, CLSCTX_ALL, COINIT_MULTITHREADED},
+    UI::Shell::PropertiesSystem::PROPERTYKEY,
+  },
+};
+
+use super::command::Command;
+use crate::{error::WindowError, utilities::ComGuard};
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum AudioFlow {
+  Render,
+  Capture,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum AudioEvent {
+  /// The default console device changed, or was removed if `device_id` is
+  /// `None`.
+  DefaultDeviceChanged {
+    flow: AudioFlow,
+    device_id: Option<String>,
+  },
+  DeviceAdded {
+    device_id: String,
+  },
+  DeviceRemoved {
+    device_id: String,
+  },
+  /// Volume or mute of this process's audio session on the default render
+  /// device changed, whether from the app or the system volume mixer.
+  VolumeChanged {
+    volume: f32,
+    muted: bool,
+  },
+}
+
+thread_local! {
+  /// Listener threads of the windows on this thread.
+  static LISTENERS: RefCell<HashMap<isize, ListenerThread>> = RefCell::new(HashMap::new());
+}
+
+/// Keeps the audio objects in their own multithreaded apartment, away from
+/// the window thread's.
+struct ListenerThread {
+  /// Asks for the session to be refreshed, stops the thread when dropped.
+  refresh: Option<Sender<()>>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl Drop for ListenerThread {
+  fn drop(&mut self) {
+    self.refresh.take();
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+  }
+}
+
+struct AudioListener {
+  enumerator: IMMDeviceEnumerator,
+  device_client: IMMNotificationClient,
+  session: Option<(IAudioSessionControl, IAudioSessionEvents)>,
+}
+
+impl Drop for AudioListener {
+  fn drop(&mut self) {
+    if let Some((control, events)) = self.session.take() {
+      if let Err(e) = unsafe { control.UnregisterAudioSessionNotification(&events) } {
+        tracing::error!("{e}");
+      }
+    }
+    if let Err(e) = unsafe {
+      self
+        .enumerator
+        .UnregisterEndpointNotificationCallback(&self.device_client)
+    } {
+      tracing::error!("{e}");
+    }
+  }
+}
+
+/// Start reporting audio events to the window. Must be called on the window
+/// thread.
+pub(crate) fn listen(hwnd: HWND) -> Result<(), WindowError> {
+  if LISTENERS.with_borrow(|listeners| listeners.contains_key(&hwnd.0)) {
+    return Ok(());
+  }
+
+  let (refresh, refresh_requests) = mpsc::channel();
+  let (sender, receiver) = mpsc::sync_channel(0);
+  let hwnd_id = hwnd.0;
+  let thread = std::thread::Builder::new()
+    .name("audio listener".to_owned())
+    .spawn(move || {
+      let hwnd = HWND(hwnd_id);
+      // the audio objects stay in this thread's apartment, notifications
+      // arrive on threads owned by the audio service either way
+      let started = ComGuard::new(COINIT_MULTITHREADED)
+        .and_then(|com| Ok((com, AudioListener::new(hwnd)?)));
+      let (_com, mut listener) = match started {
+        Ok(started) => {
+          let _ = sender.send(Ok(()));
+          started
+        }
+        Err(e) => {
+          let _ = sender.send(Err(e));
+          return;
+        }
+      };
+
+      // ends once the window stops listening
+      for () in refresh_requests {
+        listener.refresh_session(hwnd);
+      }
+      // released before COM is uninitialized
+      drop(listener);
+    })?;
+
+  let listener = ListenerThread {
+    refresh: Some(refresh),
+    thread: Some(thread),
+  };
+  receiver
+    .recv()
+    .map_err(|e| WindowError::Error(e.to_string()))??;
+
+  LISTENERS.with_borrow_mut(|listeners| listeners.insert(hwnd.0, listener));
+  Ok(())
+}
+
+/// Stop reporting audio events to the window. Must be called on the window
+/// thread.
+pub(crate) fn stop(hwnd: HWND) {
+  LISTENERS.with_borrow_mut(|listeners| listeners.remove(&hwnd.0));
+}
+
+/// Follow the process's audio session to the new default render device.
+pub(crate) fn refresh_session(hwnd: HWND) {
+  LISTENERS.with_borrow(|listeners| {
+    if let Some(refresh) = listeners.get(&hwnd.0).and_then(|l| l.refresh.as_ref()) {
+      let _ = refresh.send(());
+    }
+  });
+}
+
+impl AudioListener {
+  fn new(hwnd: HWND) -> windows::core::Result<Self> {
+    let enumerator: IMMDeviceEnumerator =
+      unsafe { CoCreateInstance(&MMDeviceEnumerator, None, CLSCTX_ALL) }?;
+    let device_client: IMMNotificationClient = DeviceListener { hwnd }.into();
+    unsafe { enumerator.RegisterEndpointNotificationCallback(&device_client) }?;
+
+    let mut listener = Self {
+      enumerator,
+      device_client,
+      session: None,
+    };
+    listener.session = register_session(hwnd, &listener.enumerator)
+      .inspect_err(|e| tracing::error!("{e}"))
+      .ok();
+    Ok(listener)
+  }
+
+  fn refresh_session(&mut self, hwnd: HWND) {
+    if let Some((control, events)) = self.session.take() {
+      if let Err(e) = unsafe { control.UnregisterAudioSessionNotification(&events) } {
+        tracing::error!("{e}");
+      }
+    }
+    self.session = register_session(hwnd, &self.enumerator)
+      .inspect_err(|e| tracing::error!("{e}"))
+      .ok();
+  }
+}
+
+fn register_session(
+  hwnd: HWND,
+  enumerator: &IMMDeviceEnumerator,
+) -> windows::core::Result<(IAudioSessionControl, IAudioSessionEvents)> {
+  let device = unsafe { enumerator.GetDefaultAudioEndpoint(eRender, eConsole) }?;
+  let manager: IAudioSessionManager = unsafe { device.Activate(CLSCTX_ALL, None) }?;
+  let control = unsafe { manager.GetAudioSessionControl(None, 0) }?;
+  let events: IAudioSessionEvents = SessionListener { hwnd }.into();
+  unsafe { control.RegisterAudioSessionNotification(&events) }?;
+  Ok((control, events))
+}
+
+fn device_id(id: &PCWSTR) -> Option<String> {
+  if id.is_null() {
+    return None;
+  }
+  unsafe { id.to_string() }.ok()
+}
+
+#[implement(IMMNotificationClient)]
+struct DeviceListener {
+  hwnd: HWND,
+}
+
+impl IMMNotificationClient_Impl for DeviceListener {
+  fn OnDeviceStateChanged(
+    &self,
+    _pwstrdeviceid: &PCWSTR,
+    _dwnewstate: DEVICE_STATE,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+
+  fn OnDeviceAdded(&self, pwstrdeviceid: &PCWSTR) -> windows::core::Result<()> {
+    if let Some(device_id) = device_id(pwstrdeviceid) {
+      Command::Audio(AudioEvent::DeviceAdded { device_id }).post(self.hwnd);
+    }
+    Ok(())
+  }
+
+  fn OnDeviceRemoved(&self, pwstrdeviceid: &PCWSTR) -> windows::core::Result<()> {
+    if let Some(device_id) = device_id(pwstrdeviceid) {
+      Command::Audio(AudioEvent::DeviceRemoved { device_id }).post(self.hwnd);
+    }
+    Ok(())
+  }
+
+  fn OnDefaultDeviceChanged(
+    &self,
+    flow: EDataFlow,
+    role: ERole,
+    pwstrdefaultdeviceid: &PCWSTR,
+  ) -> windows::core::Result<()> {
+    // each role reports its own change, only the console one is forwarded
+    if role != eConsole {
+      return Ok(());
+    }
+    let flow = if flow == eRender {
+      AudioFlow::Render
+    } else if flow == eCapture {
+      AudioFlow::Capture
+    } else {
+      return Ok(());
+    };
+    Command::Audio(AudioEvent::DefaultDeviceChanged {
+      flow,
+      device_id: device_id(pwstrdefaultdeviceid),
+    })
+    .post(self.hwnd);
+    Ok(())
+  }
+
+  fn OnPropertyValueChanged(
+    &self,
+    _pwstrdeviceid: &PCWSTR,
+    _key: &PROPERTYKEY,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+}
+
+#[implement(IAudioSessionEvents)]
+struct SessionListener {
+  hwnd: HWND,
+}
+
+impl IAudioSessionEvents_Impl for SessionListener {
+  fn OnDisplayNameChanged(
+    &self,
+    _newdisplayname: &PCWSTR,
+    _eventcontext: *const GUID,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+
+  fn OnIconPathChanged(
+    &self,
+    _newiconpath: &PCWSTR,
+    _eventcontext: *const GUID,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+
+  fn OnSimpleVolumeChanged(
+    &self,
+    newvolume: f32,
+    newmute: BOOL,
+    _eventcontext: *const GUID,
+  ) -> windows::core::Result<()> {
+    Command::Audio(AudioEvent::VolumeChanged {
+      volume: newvolume,
+      muted: newmute.as_bool(),
+    })
+    .post(self.hwnd);
+    Ok(())
+  }
+
+  fn OnChannelVolumeChanged(
+    &self,
+    _channelcount: u32,
+    _newchannelvolumearray: *const f32,
+    _changedchannel: u32,
+    _eventcontext: *const GUID,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+
+  fn OnGroupingParamChanged(
+    &self,
+    _newgroupingparam: *const GUID,
+    _eventcontext: *const GUID,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+
+  fn OnStateChanged(&self, _newstate: AudioSessionState) -> windows::core::Result<()> {
+    Ok(())
+  }
+
+  fn OnSessionDisconnected(
+    &self,
+    _disconnectreason: AudioSessionDisconnectReason,
+  ) -> windows::core::Result<()> {
+    Ok(())
+  }
+}
@@ -0,0 +1,159 @@
+use std::{
+  collections::VecDeque,
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+    Condvar,
+    Mutex,
+  },
+  time::Duration,
+};
+
+use super::message::Message;
+
+pub(crate) struct Channel {
+  queue: Mutex<VecDeque<Message>>,
+  condvar: Condvar,
+  capacity: usize,
+  disconnected: AtomicBool,
+}
+
+impl Channel {
+  fn new(capacity: usize) -> Self {
+    Self {
+      queue: Mutex::new(VecDeque::with_capacity(capacity)),
+      condvar: Condvar::new(),
+      capacity: capacity.max(1),
+      disconnected: AtomicBool::new(false),
+    }
+  }
+
+  fn push(&self, message: Message) {
+    let mut queue = self.queue.lock().unwrap();
+    if queue.len() >= self.capacity {
+      queue.pop_front();
+    }
+    queue.push_back(message);
+    self.condvar.notify_all();
+  }
+
+  fn disconnect(&self) {
+    self.disconnected.store(true, Ordering::SeqCst);
+    let _queue = self.queue.lock().unwrap();
+    self.condvar.notify_all();
+  }
+
+  fn is_disconnected(&self) -> bool {
+    self.disconnected.load(Ordering::SeqCst)
+  }
+}
+
+/// Fans out clones of every message delivered by the window's iterator to all
+/// subscribed [`Receiver`]s.
+#[derive(Default)]
+pub(crate) struct Broadcaster {
+  channels: Mutex<Vec<Arc<Channel>>>,
+}
+
+impl Broadcaster {
+  pub fn subscribe(&self, capacity: usize) -> Receiver {
+    let channel = Arc::new(Channel::new(capacity));
+    self.channels.lock().unwrap().push(channel.clone());
+    Receiver { channel }
+  }
+
+  pub fn send(&self, message: &Message) {
+    let mut channels = self.channels.lock().unwrap();
+    // drop channels whose receiver no longer exists
+    channels.retain(|channel| Arc::strong_count(channel) > 1);
+    for channel in channels.iter() {
+      channel.push(message.clone());
+    }
+  }
+
+  pub fn subscriber_count(&self) -> usize {
+    let channels = self.channels.lock().unwrap();
+    channels
+      .iter()
+      .filter(|channel| Arc::strong_count(channel) > 1)
+      .count()
+  }
+
+  pub fn disconnect(&self) {
+    for channel in self.channels.lock().unwrap().drain(..) {
+      channel.disconnect();
+    }
+  }
+}
+
+/// Receiving half of a window subscription created by
+/// [`Window::subscribe`](crate::Window::subscribe).
+///
+/// The receiver is bounded; once full, the oldest message is dropped to make
+/// room for the newest one. Receivers never block the window or its iterator.
+pub struct Receiver {
+  channel: Arc<Channel>,
+}
+
+impl Receiver {
+  /// Returns the next message if one is available without blocking.
+  pub fn try_recv(&self) -> Option<Message> {
+    self.channel.queue.lock().unwrap().pop_front()
+  }
+
+  /// Blocks until a message is available. Returns `None` once the window has
+  /// been destroyed and all remaining messages have been received.
+  pub fn recv(&self) -> Option<Message> {
+    let mut queue = self.channel.queue.lock().unwrap();
+    loop {
+      if let Some(message) = queue.pop_front() {
+        return Some(message);
+      }
+      if self.channel.is_disconnected() {
+        return None;
+      }
+      queue = self.channel.condvar.wait(queue).unwrap();
+    }
+  }
+
+  /// Blocks until a message is available or the timeout elapses.
+  pub fn recv_timeout(&self, timeout: Duration) -> Option<Message> {
+    let queue = self.channel.queue.lock().unwrap();
+    let (mut queue, _) = self
+      .channel
+      .condvar
+      .wait_timeout_while(queue, timeout, |queue| {
+        queue.is_empty() && !self.channel.is_disconnected()
+      })
+      .unwrap();
+    queue.pop_front()
+  }
+
+  /// Iterates over all currently available messages without blocking.
+  pub fn try_iter(&self) -> impl Iterator<Item = Message> + '_ {
+    std::iter::from_fn(|| self.try_recv())
+  }
+
+  /// Returns `true` if the window this receiver is subscribed to has been
+  /// destroyed.
+  pub fn is_disconnected(&self) -> bool {
+    self.channel.is_disconnected()
+  }
+
+  /// Number of messages currently buffered.
+  pub fn len(&self) -> usize {
+    self.channel.queue.lock().unwrap().len()
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.len() == 0
+  }
+}
+
+impl Iterator for Receiver {
+  type Item = Message;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.recv()
+  }
+}
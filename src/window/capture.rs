@@ -0,0 +1,121 @@
+use windows::Win32::{
+  Foundation::HWND,
+  Graphics::Gdi::{
+    self,
+    BitBlt,
+    CreateCompatibleDC,
+    CreateDIBSection,
+    DeleteDC,
+    DeleteObject,
+    GetDC,
+    ReleaseDC,
+    SelectObject,
+    BITMAPINFO,
+    BITMAPINFOHEADER,
+    HDC,
+  },
+  Storage::Xps::{PrintWindow, PRINT_WINDOW_FLAGS, PW_CLIENTONLY},
+  UI::WindowsAndMessaging,
+};
+
+use super::data::PhysicalSize;
+use crate::error::WindowError;
+
+/// Tightly packed, non-premultiplied RGBA8 pixels, row by row from the top,
+/// such as returned by
+/// [`Window::capture_client`](crate::Window::capture_client).
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RgbaImage {
+  pub size: PhysicalSize,
+  pub pixels: Vec<u8>,
+}
+
+/// Copy what `hwnd` shows in its client area, `size` pixels large.
+pub(crate) fn capture_client(
+  hwnd: HWND,
+  size: PhysicalSize,
+) -> Result<RgbaImage, WindowError> {
+  if size.width == 0 || size.height == 0 {
+    return Ok(RgbaImage {
+      size,
+      pixels: Vec::new(),
+    });
+  }
+
+  let window_dc = unsafe { GetDC(hwnd) };
+  if window_dc.is_invalid() {
+    return Err(WindowError::Error(
+      "failed to get device context for capturing".to_owned(),
+    ));
+  }
+  let memory_dc = unsafe { CreateCompatibleDC(window_dc) };
+  let result = capture_into(hwnd, window_dc, memory_dc, size);
+  unsafe {
+    DeleteDC(memory_dc);
+    ReleaseDC(hwnd, window_dc);
+  }
+  result
+}
+
+fn capture_into(
+  hwnd: HWND,
+  window_dc: HDC,
+  memory_dc: HDC,
+  size: PhysicalSize,
+) -> Result<RgbaImage, WindowError> {
+  let info = BITMAPINFO {
+    bmiHeader: BITMAPINFOHEADER {
+      biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+      biWidth: size.width as i32,
+      // negative for rows from the top
+      biHeight: -(size.height as i32),
+      biPlanes: 1,
+      biBitCount: 32,
+      biCompression: Gdi::BI_RGB.0,
+      ..Default::default()
+    },
+    ..Default::default()
+  };
+  let mut bits = std::ptr::null_mut();
+  let bitmap = unsafe {
+    CreateDIBSection(memory_dc, &info, Gdi::DIB_RGB_COLORS, &mut bits, None, 0)
+  }?;
+  let previous = unsafe { SelectObject(memory_dc, bitmap) };
+
+  // rendering the full content also captures what DirectX and OpenGL draw
+  let flags =
+    PRINT_WINDOW_FLAGS(PW_CLIENTONLY.0 | WindowsAndMessaging::PW_RENDERFULLCONTENT);
+  let printed = unsafe { PrintWindow(hwnd, memory_dc, flags) }.as_bool();
+  let result = match printed {
+    true => Ok(()),
+    // copies from the screen instead, including any windows covering it
+    false => unsafe {
+      BitBlt(
+        memory_dc,
+        0,
+        0,
+        size.width as i32,
+        size.height as i32,
+        window_dc,
+        0,
+        0,
+        Gdi::SRCCOPY,
+      )
+    },
+  };
+
+  let pixel_count = size.width as usize * size.height as usize;
+  let bgra = unsafe { std::slice::from_raw_parts(bits.cast::<u8>(), pixel_count * 4) };
+  // GDI leaves alpha undefined
+  let pixels = bgra
+    .chunks_exact(4)
+    .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], 255])
+    .collect();
+
+  unsafe {
+    SelectObject(memory_dc, previous);
+    DeleteObject(bitmap);
+  }
+  result?;
+  Ok(RgbaImage { size, pixels })
+}
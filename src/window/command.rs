@@ -1,44 +1,184 @@
+use std::{
+  sync::Arc,
+  time::{Duration, Instant},
+};
+
 use cursor_icon::CursorIcon;
 use windows::{
   core::HSTRING,
   Win32::{
     Foundation::{HWND, LPARAM, WPARAM},
-    UI::WindowsAndMessaging::{self, PostMessageW, SendMessageW},
+    UI::WindowsAndMessaging::{self, SendMessageW},
   },
 };
 
-use super::data::{CursorMode, Fullscreen, Position, Size, Visibility};
+#[cfg(feature = "audio")]
+use super::audio::AudioEvent;
+use super::{
+  data::{CursorMode, Fullscreen, Position, Size, UserAttentionType, Visibility},
+  icon::{CustomCursor, Icon},
+  message::{CommandKind, Message, ScrollDelta},
+  power::KeepAwake,
+  queue,
+  snap::SnapZone,
+  subclass::{Subclass, SubclassId},
+  translator::CustomMessage,
+};
+use crate::error::WindowError;
 
 #[repr(u32)]
 #[derive(Debug, Clone, PartialEq)]
 pub enum Command {
   Exit,
   Destroy,
+  Quit,
   Redraw,
   SetVisibility(Visibility),
   SetDecorations(Visibility),
-  SetWindowText(HSTRING),
+  SetResizable(bool),
+  SetEnabled(bool),
+  SetOwner(HWND),
+  SetSkipTaskbar(bool),
+  UpdateWindowText,
+  SetIcon(Option<Icon>),
+  AppendSystemMenuItem(u16, HSTRING),
+  AppendSystemMenuSeparator,
+  SetSystemMenuItemChecked(u16, bool),
+  SetSystemMenuItemEnabled(u16, bool),
+  RemoveSystemMenuItem(u16),
+  Snap(SnapZone),
+  DragWindow,
+  StealForeground,
+  Focus,
+  /// Move the window in the z-order below the window given, or to the top or
+  /// bottom with `HWND_TOP` and `HWND_BOTTOM`.
+  SetZOrder(HWND),
+  RequestUserAttention(Option<UserAttentionType>),
+  SetKeepAwake(KeepAwake),
+  HideToTray,
+  RestoreFromTray,
   SetSize(Size),
   SetPosition(Position),
+  SetBounds(Position, Size),
   SetFullscreen(Option<Fullscreen>),
   SetCursorIcon(CursorIcon),
+  SetCustomCursor(Option<CustomCursor>),
   SetCursorMode(CursorMode),
   SetCursorVisibility(Visibility),
+  SetWakeTimer(Instant),
+  SetIdleThreshold(Option<Duration>),
+  SetTimer {
+    id: u32,
+    interval: Duration,
+    repeating: bool,
+  },
+  CancelTimer(u32),
+  User(CustomMessage),
+  Scroll(ScrollDelta),
+  Inject(Box<Message>),
+  AddSubclass(PendingSubclass),
+  RemoveSubclass(SubclassId),
+  #[cfg(feature = "audio")]
+  SetAudioNotifications(bool),
+  #[cfg(feature = "audio")]
+  Audio(AudioEvent),
 }
 
 impl Command {
   pub const MESSAGE_ID: u32 = WindowsAndMessaging::WM_USER + 69;
 
-  pub fn post(self, hwnd: HWND) {
-    let command = Box::leak(Box::new(self));
-    let addr = command as *mut Command as usize;
-    unsafe {
-      if let Err(e) = PostMessageW(hwnd, Self::MESSAGE_ID, WPARAM(addr), LPARAM(0)) {
-        tracing::error!("{e}");
+  /// Kind reported once the command has been applied, for commands whose
+  /// effects aren't visible until then.
+  pub(crate) fn completion_kinds(&self) -> &'static [CommandKind] {
+    match self {
+      Command::SetVisibility(_) => &[CommandKind::Visibility],
+      Command::SetDecorations(_) => &[CommandKind::Decorations],
+      Command::SetSize(_) => &[CommandKind::Size],
+      Command::SetPosition(_) => &[CommandKind::Position],
+      Command::SetBounds(..) => &[CommandKind::Position, CommandKind::Size],
+      Command::SetFullscreen(_) => &[CommandKind::Fullscreen],
+      Command::Snap(_) => &[CommandKind::Snap],
+      _ => &[],
+    }
+  }
+
+  /// Whether the command may be skipped to make room for newer ones.
+  pub(crate) fn is_droppable(&self) -> bool {
+    // skipping subclass changes would leave them installed or leaked, and
+    // skipping a text update would leave later ones waiting on it forever
+    !matches!(
+      self,
+      Command::Exit
+        | Command::Destroy
+        | Command::Quit
+        | Command::AddSubclass(_)
+        | Command::RemoveSubclass(_)
+        | Command::UpdateWindowText
+    )
+  }
+
+  /// Whether running this command right after `earlier` makes running
+  /// `earlier` pointless, as both set the same state.
+  pub(crate) fn replaces(&self, earlier: &Command) -> bool {
+    let sets_state = matches!(
+      self,
+      Command::Redraw
+        | Command::SetVisibility(_)
+        | Command::SetDecorations(_)
+        | Command::SetResizable(_)
+        | Command::SetEnabled(_)
+        | Command::SetOwner(_)
+        | Command::SetSkipTaskbar(_)
+        | Command::SetKeepAwake(_)
+        | Command::UpdateWindowText
+        | Command::SetIcon(_)
+        | Command::SetSize(_)
+        | Command::SetPosition(_)
+        | Command::SetBounds(..)
+        | Command::SetFullscreen(_)
+        | Command::SetCursorIcon(_)
+        | Command::SetCustomCursor(_)
+        | Command::SetCursorMode(_)
+        | Command::SetCursorVisibility(_)
+        | Command::SetWakeTimer(_)
+        | Command::SetIdleThreshold(_)
+    );
+    sets_state && std::mem::discriminant(self) == std::mem::discriminant(earlier)
+  }
+
+  /// Fold `later`, run right after this command, into it when a single
+  /// command has the same effect as both. Returns `later` otherwise.
+  pub(crate) fn absorb(&mut self, later: Command) -> Option<Command> {
+    if later.replaces(self) {
+      *self = later;
+      return None;
+    }
+
+    // moving and resizing together takes a single SetWindowPos, so the window
+    // is repainted and sent WM_WINDOWPOSCHANGED once instead of twice
+    let bounds = match (&*self, &later) {
+      (Command::SetPosition(position), Command::SetSize(size))
+      | (Command::SetSize(size), Command::SetPosition(position))
+      | (Command::SetBounds(_, size), Command::SetPosition(position))
+      | (Command::SetBounds(position, _), Command::SetSize(size)) => {
+        Command::SetBounds(*position, *size)
       }
+      _ => return Some(later),
+    };
+    *self = bounds;
+    None
+  }
+
+  pub fn post(self, hwnd: HWND) {
+    if let Err(e) = self.try_post(hwnd) {
+      tracing::error!("{e}");
     }
   }
 
+  pub(crate) fn try_post(self, hwnd: HWND) -> Result<(), WindowError> {
+    queue::push(hwnd, self)
+  }
+
   pub(crate) fn send(self, hwnd: HWND) {
     let command = Box::leak(Box::new(self));
     let addr = command as *mut Command as usize;
@@ -47,3 +187,22 @@ impl Command {
     }
   }
 }
+
+/// Subclass waiting to be installed on the window thread.
+#[derive(Clone)]
+pub struct PendingSubclass {
+  pub id: SubclassId,
+  pub subclass: Arc<dyn Subclass>,
+}
+
+impl PartialEq for PendingSubclass {
+  fn eq(&self, other: &Self) -> bool {
+    self.id == other.id
+  }
+}
+
+impl std::fmt::Debug for PendingSubclass {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_tuple("PendingSubclass").field(&self.id).finish()
+  }
+}
@@ -0,0 +1,86 @@
+use windows::{
+  core::{IUnknown, Interface},
+  Win32::Graphics::DirectComposition::{
+    DCompositionCreateDevice2,
+    IDCompositionDesktopDevice,
+    IDCompositionTarget,
+    IDCompositionVisual2,
+  },
+};
+
+use super::Window;
+use crate::error::WindowError;
+
+/// DirectComposition device and target bound to a window, for hosting
+/// swapchains created with `IDXGIFactory2::CreateSwapChainForComposition`.
+/// Unlike swapchains created for the window handle, these can be transparent
+/// and are presented without flicker while resizing.
+///
+/// Pair with [`WindowBuilder::with_no_redirection_bitmap`] so Windows doesn't
+/// also keep a GDI surface for the window.
+///
+/// [`WindowBuilder::with_no_redirection_bitmap`]: crate::WindowBuilder::with_no_redirection_bitmap
+pub struct Composition {
+  device: IDCompositionDesktopDevice,
+  target: IDCompositionTarget,
+  root: IDCompositionVisual2,
+}
+
+impl Composition {
+  /// Create a device without a rendering device, and a target showing a root
+  /// visual in `window` above any child windows.
+  pub fn new(window: &Window) -> Result<Self, WindowError> {
+    Self::create(window, None)
+  }
+
+  /// Create the device for use with `rendering_device`, a Direct3D or
+  /// Direct2D device, letting it create composition surfaces.
+  pub fn with_rendering_device(
+    window: &Window,
+    rendering_device: &IUnknown,
+  ) -> Result<Self, WindowError> {
+    Self::create(window, Some(rendering_device))
+  }
+
+  fn create(
+    window: &Window,
+    rendering_device: Option<&IUnknown>,
+  ) -> Result<Self, WindowError> {
+    let device: IDCompositionDesktopDevice =
+      unsafe { DCompositionCreateDevice2(rendering_device) }?;
+    let target = unsafe { device.CreateTargetForHwnd(window.0.hwnd, true) }?;
+    let root = unsafe { device.CreateVisual() }?;
+    unsafe { target.SetRoot(&root) }?;
+    Ok(Self {
+      device,
+      target,
+      root,
+    })
+  }
+
+  pub fn device(&self) -> &IDCompositionDesktopDevice {
+    &self.device
+  }
+
+  pub fn target(&self) -> &IDCompositionTarget {
+    &self.target
+  }
+
+  /// Visual shown in the window. Add child visuals to it to compose several
+  /// pieces of content.
+  pub fn root(&self) -> &IDCompositionVisual2 {
+    &self.root
+  }
+
+  /// Show `content`, such as a swapchain created for composition, as the root
+  /// visual's content. Takes effect on the next [`Composition::commit`].
+  pub fn set_content(&self, content: &impl Interface) -> Result<(), WindowError> {
+    let content: IUnknown = content.cast()?;
+    Ok(unsafe { self.root.SetContent(&content) }?)
+  }
+
+  /// Apply the changes made since the last commit.
+  pub fn commit(&self) -> Result<(), WindowError> {
+    Ok(unsafe { self.device.Commit() }?)
+  }
+}
@@ -1,6 +1,7 @@
 use cursor_icon::CursorIcon;
+use windows::Win32::UI::WindowsAndMessaging;
 
-use crate::{CursorMode, PhysicalPosition, Visibility};
+use crate::{CursorMode, CustomCursor, PhysicalPosition, Visibility};
 
 #[derive(Debug, Clone)]
 pub struct Cursor {
@@ -9,4 +10,35 @@ pub struct Cursor {
   pub inside_window: bool,
   pub last_position: PhysicalPosition,
   pub selected_icon: CursorIcon,
+  /// Cursor shown in the client area instead of `selected_icon`.
+  pub custom: Option<CustomCursor>,
+  /// Custom cursor the window thread last applied, which keeps its handles
+  /// alive until it's replaced there.
+  pub(crate) shown_custom: Option<CustomCursor>,
+  /// Icon last reported with `Message::CursorIconChanged`, `None` while the
+  /// cursor is outside the window.
+  pub effective_icon: Option<CursorIcon>,
+  /// Whether the cursor is currently confined to the window.
+  pub grabbed: bool,
+  /// Whether the confinement was released while still in
+  /// [`CursorMode::Confined`], reported with `Message::CursorModeLost`.
+  pub grab_lost: bool,
+}
+
+/// Icon Windows shows for the given hit-test area, falling back to the icon
+/// selected by the app for the client area.
+pub(crate) fn hit_test_icon(hit_test: u32, selected: CursorIcon) -> CursorIcon {
+  match hit_test {
+    WindowsAndMessaging::HTCLIENT => selected,
+    WindowsAndMessaging::HTLEFT | WindowsAndMessaging::HTRIGHT => CursorIcon::EwResize,
+    WindowsAndMessaging::HTTOP | WindowsAndMessaging::HTBOTTOM => CursorIcon::NsResize,
+    WindowsAndMessaging::HTTOPLEFT | WindowsAndMessaging::HTBOTTOMRIGHT => {
+      CursorIcon::NwseResize
+    }
+    WindowsAndMessaging::HTTOPRIGHT | WindowsAndMessaging::HTBOTTOMLEFT => {
+      CursorIcon::NeswResize
+    }
+    WindowsAndMessaging::HTHELP => CursorIcon::Help,
+    _ => CursorIcon::Default,
+  }
 }
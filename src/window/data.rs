@@ -1,22 +1,49 @@
 use std::{
+  cell::RefCell,
+  collections::{HashSet, VecDeque},
   ops::{Div, Mul},
-  sync::{Arc, Condvar, Mutex, MutexGuard},
+  sync::{
+    atomic::{AtomicBool, Ordering},
+    Arc,
+    Condvar,
+    Mutex,
+    MutexGuard,
+  },
+  task::Waker,
   thread::JoinHandle,
+  time::{Duration, Instant},
 };
 
+use cursor_icon::CursorIcon;
 use windows::{
   core::PCWSTR,
   Win32::{
-    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, WPARAM},
+    Foundation::{
+      COLORREF,
+      HANDLE,
+      HINSTANCE,
+      HWND,
+      LPARAM,
+      LRESULT,
+      POINT,
+      RECT,
+      WPARAM,
+    },
     Graphics::Gdi::{
       self,
+      BeginPaint,
       ClientToScreen,
+      EndPaint,
       GetMonitorInfoW,
       InvalidateRgn,
       MonitorFromWindow,
       RedrawWindow,
+      ScreenToClient,
+      HDC,
       MONITORINFO,
+      PAINTSTRUCT,
     },
+    System::Threading::GetCurrentThreadId,
     UI::{
       self,
       Controls,
@@ -25,34 +52,83 @@ use windows::{
         HRAWINPUT,
         RID_DEVICE_INFO_TYPE,
       },
+      TextServices::HKL,
       WindowsAndMessaging::{
         self,
         DefWindowProcW,
         GetClientRect,
+        GetMessageTime,
+        GetWindowLongW,
         GetWindowRect,
+        GetWindowThreadProcessId,
+        IsWindow,
+        KillTimer,
         LoadCursorW,
         SetCursor,
+        SetForegroundWindow,
+        SetTimer,
+        SetWindowLongPtrW,
         SetWindowLongW,
         SetWindowPos,
         SetWindowTextW,
         ShowWindow,
-        UnregisterClassW,
+        HCURSOR,
+        SHOW_WINDOW_CMD,
         WINDOWPOS,
       },
     },
   },
 };
 
+#[cfg(feature = "accessibility")]
+use super::accessibility::{self, AccessibilityHandler, AccessibleInfo};
+#[cfg(feature = "audio")]
+use super::audio::{self, AudioEvent, AudioFlow};
+#[cfg(feature = "gamepad")]
+use super::input::gamepad::{self, GamepadPoller};
 use super::{
+  broadcast::Broadcaster,
   command::Command,
-  cursor::Cursor,
+  cursor::{self, Cursor},
+  drag::{self, TabStrip},
   frame::Style,
-  input::mouse::mouse_button_states,
-  message::{get_cursor_move_kind, CursorMoveKind, Focus},
-  stage::Stage,
+  gesture::{self, GestureTracker},
+  hit_test::HitTester,
+  icon::{self, Icon},
+  input::{
+    joystick::{self, JoystickTracker},
+    layout::KeyboardLayout,
+    mouse::{mouse_button_states, ClickTracker},
+    pointer::PointerDevices,
+  },
+  journal::Journal,
+  jump_list,
+  menu::{self, SystemCommand},
+  message::{
+    get_cursor_move_kind,
+    is_whole_notch,
+    CursorMoveKind,
+    Focus,
+    LoopMessage,
+    ScrollDelta,
+  },
+  power::{self, KeepAwake},
+  procedure,
+  queue,
+  recording::Recorder,
+  registry::WindowClass,
+  resize::ResizeSignal,
+  snap,
+  stage::{AtomicStage, Stage},
+  stream,
+  subclass,
+  thread::WindowThread,
+  timer::UserTimers,
+  translator::{FilterAction, MessageFilter, MessageReplier, MessageTranslator},
+  tray::{self, add_tray_icon, remove_tray_icon, TrayBehavior},
 };
 use crate::{
-  error::WindowError,
+  error::{SharedError, WindowError},
   utilities::{
     self,
     dpi_to_scale_factor,
@@ -66,7 +142,10 @@ use crate::{
     signed_lo_word,
     to_windows_cursor,
   },
-  window::Input,
+  window::{
+    input::state::{ButtonState, KeyRepeat, KeyState, Modifiers},
+    Input,
+  },
   Key,
   Message,
   MouseButton,
@@ -74,36 +153,124 @@ use crate::{
   RawKeyState,
 };
 
+/// `WM_TIMER` event id of a redraw deferred to the next compositor refresh.
+const REDRAW_TIMER_ID: usize = 0x7264;
+
+/// Hand-off of a single message from the window thread to the main thread.
+///
+/// Holds at most one message. The window thread doesn't queue further
+/// messages behind it, but waits in [`SyncData::wait_on_frame`] until the main
+/// thread has taken the message and handled it, so that the window can't
+/// change under the app while it reacts, such as in the middle of a resize.
+/// This backpressure is also what keeps the main thread from falling behind.
+/// Whether a message is waiting is tracked separately with an atomic, so
+/// checking doesn't contend with the other thread.
+#[derive(Default)]
+pub struct Mailbox {
+  slot: Mutex<Option<Message>>,
+  filled: Condvar,
+  occupied: AtomicBool,
+}
+
+impl Mailbox {
+  pub fn is_occupied(&self) -> bool {
+    self.occupied.load(Ordering::Acquire)
+  }
+
+  pub fn put(&self, message: Message) {
+    let mut slot = self.slot.lock().unwrap();
+    *slot = Some(message);
+    self.occupied.store(true, Ordering::Release);
+    self.filled.notify_all();
+  }
+
+  /// Take the waiting message, if any.
+  pub fn take(&self) -> Option<Message> {
+    if !self.is_occupied() {
+      return None;
+    }
+    let mut slot = self.slot.lock().unwrap();
+    let message = slot.take();
+    self.occupied.store(false, Ordering::Release);
+    message
+  }
+
+  /// Take the waiting message, waiting up to `timeout` (or indefinitely if
+  /// `None`) for one to arrive.
+  pub fn wait_take(&self, timeout: Option<Duration>) -> Option<Message> {
+    let slot = self.slot.lock().unwrap();
+    let mut slot = match timeout {
+      Some(timeout) => {
+        self
+          .filled
+          .wait_timeout_while(slot, timeout, |slot| slot.is_none())
+          .unwrap()
+          .0
+      }
+      None => self.filled.wait_while(slot, |slot| slot.is_none()).unwrap(),
+    };
+    let message = slot.take();
+    self.occupied.store(false, Ordering::Release);
+    message
+  }
+}
+
 #[derive(Clone)]
 pub struct SyncData {
-  pub new_message: Arc<(Mutex<bool>, Condvar)>,
+  pub mailbox: Arc<Mailbox>,
   pub next_frame: Arc<(Mutex<bool>, Condvar)>,
+  /// Mirrors the flag in `next_frame`, so releasing a window thread which is
+  /// already released skips the lock and condvar. Under [`Flow::Poll`] this is
+  /// the case on every iteration without a message.
+  pub frame_released: Arc<AtomicBool>,
   pub skip_wait: Arc<Mutex<bool>>,
+  /// Woken along with the mailbox for [`Window::next_message`].
+  pub waker: Arc<Mutex<Option<Waker>>>,
 }
 
 impl SyncData {
-  pub fn signal_new_message(&self) {
-    let (lock, cvar) = self.new_message.as_ref();
-    let mut new = lock.lock().unwrap();
-    if !*new {
-      *new = true;
-      cvar.notify_all();
+  /// Leave a message for the main thread and wake it.
+  pub fn deliver(&self, message: Message) {
+    self.mailbox.put(message);
+    if let Some(waker) = self.waker.lock().unwrap().take() {
+      waker.wake();
     }
   }
 
+  /// Stop the window thread from waiting on the main thread, such as while the
+  /// main thread is stuck in a modal loop which needs the window thread to
+  /// answer. Messages sent meanwhile replace each other. Returns what to pass
+  /// to [`SyncData::resume_lockstep`].
+  pub fn suspend_lockstep(&self) -> bool {
+    let previous = std::mem::replace(&mut *self.skip_wait.lock().unwrap(), true);
+    self.signal_next_frame();
+    previous
+  }
+
+  pub fn resume_lockstep(&self, previous: bool) {
+    *self.skip_wait.lock().unwrap() = previous;
+  }
+
   pub fn wait_on_frame(&self) {
     let (lock, cvar) = self.next_frame.as_ref();
     let mut next = cvar
       .wait_while(lock.lock().unwrap(), |next| !*next)
       .unwrap();
     *next = *self.skip_wait.lock().unwrap();
+    self.frame_released.store(*next, Ordering::Release);
   }
 
   pub fn signal_next_frame(&self) {
+    // a stale `true` only delays the release to the next iteration, as the
+    // window thread consumes it right after handing over a message
+    if self.frame_released.load(Ordering::Acquire) {
+      return;
+    }
     let (lock, cvar) = self.next_frame.as_ref();
     let mut next = lock.lock().unwrap();
     if !*next {
       *next = true;
+      self.frame_released.store(true, Ordering::Release);
       cvar.notify_all();
     }
   }
@@ -112,32 +279,65 @@ impl SyncData {
 pub struct Internal {
   pub hinstance: HINSTANCE,
   pub hwnd: HWND,
-  pub class_atom: u16,
-  pub message: Arc<Mutex<Option<Message>>>,
   pub sync: SyncData,
+  // touched by both threads on every message, so kept out of `data` to avoid waiting
+  // on whichever thread holds it
+  pub(crate) stage: AtomicStage,
+  pub(crate) requested_redraw: AtomicBool,
+  pub(crate) flow: Mutex<Flow>,
+  pub(crate) input: Mutex<Input>,
+  /// Errors from the main thread, yielded by the iterator before its next
+  /// message. The window thread sends its own errors right away instead.
+  pub(crate) pending_errors: Mutex<VecDeque<SharedError>>,
   pub thread: Mutex<Option<JoinHandle<Result<(), WindowError>>>>,
   pub data: Mutex<Data>,
+  pub(crate) broadcaster: Broadcaster,
+  pub(crate) before_destroy: Mutex<Vec<Box<dyn FnOnce() + Send>>>,
+  /// Released after the window is destroyed, unregistering the class if this
+  /// was the last window using it. Held by the thread instead for windows on a
+  /// [`WindowThread`].
+  pub(crate) _class: Option<WindowClass>,
+  /// Keeps a shared window thread alive for as long as its windows.
+  pub(crate) _shared_thread: Option<WindowThread>,
 }
 
 /// Window is destroyed on drop.
 impl Drop for Internal {
   fn drop(&mut self) {
+    self.destroy();
+  }
+}
+
+impl Internal {
+  /// Destroy the window and join its thread, if not already done. Runs on drop,
+  /// or earlier when the console shuts the app down, see
+  /// [`shutdown_on_console_exit`](crate::shutdown_on_console_exit).
+  pub(crate) fn destroy(&self) {
     let title = self.data_lock().title.clone();
 
-    if self.data_lock().stage == Stage::Destroyed {
+    // checked and set together, the console handler thread may race the drop
+    if self.stage.swap(Stage::Destroyed) == Stage::Destroyed {
       return;
-    } else {
-      self.data_lock().stage = Stage::Destroyed;
     }
 
+    let callbacks = std::mem::take(&mut *self.before_destroy.lock().unwrap());
+    if !callbacks.is_empty() {
+      tracing::trace!("[`{}`]: running before_destroy callbacks", title);
+      for callback in callbacks {
+        callback();
+      }
+    }
+
+    if self.data_lock().in_tray {
+      remove_tray_icon(self.hwnd);
+    }
+    self.end_modal();
+
     tracing::trace!("[`{}`]: destroying window", title);
 
     Command::Destroy.post(self.hwnd);
     self.join_thread();
-
-    tracing::trace!("[`{}`]: unregistering window class", title);
-    unsafe { UnregisterClassW(PCWSTR(self.class_atom as *const u16), self.hinstance) }
-      .unwrap();
+    self.broadcaster.disconnect();
 
     tracing::trace!("[`{}`]: destroyed window", title);
   }
@@ -146,20 +346,115 @@ impl Drop for Internal {
 pub struct Data {
   pub title: String,
   pub subtitle: String,
+  /// Title and subtitle already encoded as UTF-16 for `SetWindowTextW`, each
+  /// re-encoded into the same buffer when it changes.
+  pub(crate) title_wide: Vec<u16>,
+  pub(crate) subtitle_wide: Vec<u16>,
+  /// Whether a `Command::UpdateWindowText` is waiting on the window thread.
+  /// Further title changes only update the encoded text until it is handled.
+  pub window_text_pending: bool,
   pub theme: Theme,
-  pub flow: Flow,
+  pub backdrop: Backdrop,
+  pub caption_color: Option<Color>,
+  pub caption_text_color: Option<Color>,
+  pub border_color: Option<Color>,
+  pub icon: Option<Icon>,
+  /// Icon the window was last given, only replaced on the window thread so
+  /// its handles aren't destroyed while still in use.
+  pub(crate) shown_icon: Option<Icon>,
+  /// Device context from `BeginPaint` while `Message::Paint` is handled.
+  pub(crate) paint_dc: Option<HDC>,
+  pub app_id: Option<String>,
+  pub keep_awake: KeepAwake,
+  pub high_contrast: bool,
+  pub animations: bool,
+  pub transparency: bool,
+  pub accent_color: Color,
+  /// Lines scrolled per wheel notch, cached as wheel messages arrive in bursts.
+  pub wheel_scroll_lines: f32,
+  /// Characters scrolled per horizontal wheel notch.
+  pub wheel_scroll_chars: f32,
+  /// Raw input devices which reported fractions of a notch, whose wheel
+  /// messages are reported in pixels even when a whole notch was scrolled.
+  pub(crate) smooth_wheel_devices: HashSet<isize>,
+  /// Device behind the wheel message about to arrive, from raw input.
+  pub(crate) wheel_device: Option<isize>,
+  pub tray_behavior: TrayBehavior,
+  pub in_tray: bool,
+  /// Owner disabled while this window is shown with `Window::show_modal`.
+  pub(crate) modal_owner: Option<HWND>,
+  /// Whether double clicks are reported as such, see
+  /// `WindowBuilder::with_double_clicks`.
+  pub double_clicks: bool,
+  pub(crate) clicks: ClickTracker,
+  pub ime_composing: bool,
+  /// Keys, buttons, and modifiers as the window thread last saw them, for
+  /// filtering key repeats and noticing modifier changes. The `Input` read by
+  /// the app follows the messages the iterator yields instead.
+  pub(crate) window_input: Input,
+  /// High surrogate of a character outside the BMP, waiting for the
+  /// `WM_CHAR` carrying its low surrogate.
+  pub(crate) pending_surrogate: Option<u16>,
+  pub key_repeat: KeyRepeat,
+  /// `Some` while gesture recognition is enabled.
+  pub(crate) gesture_tracker: Option<GestureTracker>,
+  pub(crate) tab_strip: Option<TabStrip>,
+  pub(crate) joysticks: Option<JoystickTracker>,
+  pub(crate) pointer_devices: PointerDevices,
+  #[cfg(feature = "gamepad")]
+  pub(crate) gamepad_poller: GamepadPoller,
+  pub keyboard_layout: KeyboardLayout,
+  pub message_translator: Option<Arc<dyn MessageTranslator>>,
+  pub message_filter: Option<Arc<dyn MessageFilter>>,
+  pub message_replier: Option<Arc<dyn MessageReplier>>,
+  pub hit_tester: Option<Arc<dyn HitTester>>,
+  #[cfg(feature = "accessibility")]
+  pub(crate) accessible: Arc<Mutex<AccessibleInfo>>,
+  #[cfg(feature = "accessibility")]
+  pub accessibility_handler: Option<Arc<dyn AccessibilityHandler>>,
+  /// `Some` while journaling is enabled.
+  pub(crate) journal: Option<Journal>,
+  /// `Some` while recording.
+  pub(crate) recorder: Option<Recorder>,
+  pub background_interval: Option<Duration>,
+  pub visible_after_first_draw: bool,
+  /// How to show the window the first time it's shown, if not normally.
+  pub(crate) initial_show: Option<SHOW_WINDOW_CMD>,
   pub close_on_x: bool,
 
-  pub stage: Stage,
   pub style: Style,
-  pub input: Input,
   pub cursor: Cursor,
 
   pub last_windowed_position: Position,
   pub last_windowed_size: Size,
   pub scale_factor: f64,
 
-  pub requested_redraw: bool,
+  /// Whether the message being handled on the main thread reports a resize or
+  /// move. Setters which would cause another one are deferred until the next
+  /// iteration, see `Window::post_or_defer`.
+  pub handling_resize: bool,
+  pub handling_move: bool,
+  pub deferred_commands: Vec<Command>,
+  /// Whether `Window::pump_messages` released the window thread and is still
+  /// waiting for its next message.
+  pub(crate) pump_started: bool,
+  /// Deadline of the timer waking an async wait under [`Flow::WaitUntil`].
+  pub(crate) wake_deadline: Option<Instant>,
+  pub idle_threshold: Option<Duration>,
+  /// Whether `Message::UserIdle` was sent without a `Message::UserActive`
+  /// since.
+  pub user_idle: bool,
+  pub(crate) timers: UserTimers,
+  pub frame_pacing: bool,
+  /// Compositor refresh during which the last `Message::Paint` was sent, see
+  /// `utilities::composition_refresh_count`.
+  pub(crate) last_paint_refresh: Option<u64>,
+  /// Whether a redraw is waiting on `REDRAW_TIMER_ID` for the next refresh.
+  pub(crate) redraw_deferred: bool,
+  /// Code sent with `LoopMessage::Exit`.
+  pub exit_code: i32,
+  /// Whether `Message::Suspended` was sent without a `Message::Resumed` since.
+  pub suspended: bool,
 }
 
 impl Internal {
@@ -172,19 +467,52 @@ impl Internal {
   }
 
   pub fn send_message_to_main(&self, message: Message) {
-    let should_wait = self.message.lock().unwrap().is_some();
-    if should_wait {
+    if self.sync.mailbox.is_occupied() {
       self.sync.wait_on_frame();
     }
 
-    self.message.lock().unwrap().replace(message);
-    self.sync.signal_new_message();
+    self.sync.deliver(message);
 
     // TODO: try inverting these locks so that they don't lock unless the main thread tells them to lock.
 
     self.sync.wait_on_frame();
   }
 
+  /// Cache the new `value` of a system preference, reporting it with
+  /// `message` if it changed.
+  fn update_preference<T: PartialEq + Copy>(
+    &self,
+    field: impl FnOnce(&mut Data) -> &mut T,
+    value: T,
+    message: fn(T) -> Message,
+  ) {
+    let previous = std::mem::replace(field(&mut self.data.lock().unwrap()), value);
+    if previous != value {
+      self.send_message_to_main(message(value));
+    }
+  }
+
+  /// Log `error` and report it to the app with [`Message::Error`].
+  pub(crate) fn report_error(&self, error: impl Into<WindowError>) {
+    let error = SharedError::from(error.into());
+    tracing::error!("{error}");
+
+    let on_window_thread =
+      unsafe { GetWindowThreadProcessId(self.hwnd, None) == GetCurrentThreadId() };
+    if on_window_thread && matches!(self.stage.load(), Stage::Looping | Stage::Closing) {
+      self.send_message_to_main(Message::Error(error));
+    } else {
+      self.pending_errors.lock().unwrap().push_back(error);
+    }
+  }
+
+  /// Post a command to the window, reporting it if it couldn't be.
+  pub(crate) fn post(&self, command: Command) {
+    if let Err(e) = command.try_post(self.hwnd) {
+      self.report_error(e);
+    }
+  }
+
   pub(crate) fn join_thread(&self) {
     let thread = self.thread.lock().unwrap().take();
     if let Some(thread) = thread {
@@ -195,10 +523,7 @@ impl Internal {
   }
 
   pub(crate) fn is_closing(&self) -> bool {
-    matches!(
-      self.data.lock().unwrap().stage,
-      Stage::Closing | Stage::ExitLoop | Stage::Destroyed
-    )
+    matches!(self.stage.load(), Stage::Closing | Stage::ExitLoop | Stage::Destroyed)
   }
 
   // pub(crate) fn exit_loop(&self) {
@@ -217,7 +542,7 @@ impl Internal {
       let style = &self.data_lock().style;
       style.focused && style.active
     };
-    if is_focused {
+    let grabbed = if is_focused {
       let is_confined = matches!(self.data_lock().cursor.mode, CursorMode::Confined);
       let is_hidden = matches!(self.data_lock().cursor.visibility, Visibility::Hidden);
       let cursor_clip = match is_confined {
@@ -255,7 +580,14 @@ impl Internal {
       if active_cursor_clip != cursor_clip.map(rect_to_tuple) {
         utilities::set_cursor_clip(cursor_clip.as_ref());
       }
-    }
+      cursor_clip.is_some()
+    } else {
+      // the cursor is shared, so release it for other windows until focus returns
+      if self.data_lock().cursor.grabbed {
+        utilities::set_cursor_clip(None);
+      }
+      false
+    };
 
     let cursor_visibility = self.data_lock().cursor.visibility;
     let cursor_in_client = self.data_lock().cursor.inside_window;
@@ -265,9 +597,34 @@ impl Internal {
       utilities::set_cursor_visibility(Visibility::Shown);
     }
 
+    self.update_cursor_grab(grabbed);
     Ok(())
   }
 
+  /// Report the cursor being released or confined again while the cursor mode
+  /// stays [`CursorMode::Confined`].
+  fn update_cursor_grab(&self, grabbed: bool) {
+    let message = {
+      let mut data = self.data_lock();
+      let cursor = &mut data.cursor;
+      let was_grabbed = std::mem::replace(&mut cursor.grabbed, grabbed);
+      if cursor.mode != CursorMode::Confined {
+        cursor.grab_lost = false;
+        None
+      } else if was_grabbed && !grabbed {
+        cursor.grab_lost = true;
+        Some(Message::CursorModeLost)
+      } else if grabbed && std::mem::take(&mut cursor.grab_lost) {
+        Some(Message::CursorModeRestored)
+      } else {
+        None
+      }
+    };
+    if let Some(message) = message {
+      self.send_message_to_main(message);
+    }
+  }
+
   pub(crate) fn update_last_windowed_pos_size(&self, hwnd: HWND) {
     let mut window_rect = RECT::default();
     let _ = unsafe { GetWindowRect(hwnd, &mut window_rect) };
@@ -283,6 +640,231 @@ impl Internal {
     self.data.lock().unwrap().last_windowed_position = position.into();
   }
 
+  /// Restart the journal's debounce timer, if journaling is enabled.
+  fn schedule_journal(&self, hwnd: HWND) {
+    if let Some(journal) = &self.data.lock().unwrap().journal {
+      journal.schedule(hwnd);
+    }
+  }
+
+  /// Refresh the modifier keys, sending [`Message::ModifiersChanged`] if they
+  /// changed, and return the current snapshot.
+  fn update_modifiers(&self) -> Modifiers {
+    let (changed, modifiers) = {
+      let mut data = self.data.lock().unwrap();
+      let changed = data.window_input.update_modifiers_state();
+      (changed, data.window_input.modifiers())
+    };
+    if let Some(modifiers) = changed {
+      self.send_message_to_main(Message::ModifiersChanged(modifiers));
+    }
+    modifiers
+  }
+
+  /// Move the window until the left button is released, then report the
+  /// release which the move loop swallowed.
+  fn drag_window(&self, hwnd: HWND) {
+    drag::drag_window(hwnd);
+
+    {
+      let mut data = self.data.lock().unwrap();
+      if !data.window_input.mouse(MouseButton::Left).is_pressed() {
+        return;
+      }
+      data
+        .window_input
+        .update_mouse_button_state(MouseButton::Left, ButtonState::Released);
+    }
+    let modifiers = self.update_modifiers();
+    let (position, click_count) = {
+      let data = self.data.lock().unwrap();
+      (data.cursor.last_position, data.clicks.release(MouseButton::Left))
+    };
+    self.send_message_to_main(Message::MouseButton {
+      button: MouseButton::Left,
+      state: ButtonState::Released,
+      position,
+      is_double_click: false,
+      click_count,
+      modifiers,
+    });
+  }
+
+  /// Move and/or resize the window with a single `SetWindowPos`.
+  fn set_bounds(&self, hwnd: HWND, position: Option<Position>, size: Option<Size>) {
+    let scale_factor = self.data.lock().unwrap().scale_factor;
+    let mut flags = WindowsAndMessaging::SWP_NOZORDER
+      | WindowsAndMessaging::SWP_NOREPOSITION
+      | WindowsAndMessaging::SWP_NOACTIVATE;
+    let position = match position {
+      Some(position) => position.as_physical(scale_factor),
+      None => {
+        flags |= WindowsAndMessaging::SWP_NOMOVE;
+        PhysicalPosition::default()
+      }
+    };
+    let size = match size {
+      Some(size) => size.as_physical(scale_factor),
+      None => {
+        flags |= WindowsAndMessaging::SWP_NOSIZE;
+        PhysicalSize::default()
+      }
+    };
+    unsafe {
+      SetWindowPos(
+        hwnd,
+        None,
+        position.x,
+        position.y,
+        size.width as i32,
+        size.height as i32,
+        flags,
+      )
+      .expect("Failed to set window bounds");
+    }
+    unsafe { InvalidateRgn(hwnd, None, false) };
+  }
+
+  /// Give input back to the owner disabled by `Window::show_modal`, and the
+  /// foreground with it, as Windows would activate another app otherwise.
+  pub(crate) fn end_modal(&self) {
+    let Some(owner) = self.data.lock().unwrap().modal_owner.take() else {
+      return;
+    };
+    if unsafe { IsWindow(owner) }.as_bool() {
+      Command::SetEnabled(true).post(owner);
+      Command::StealForeground.post(owner);
+    }
+  }
+
+  /// Redraw the window, waiting for the next compositor refresh with a timer
+  /// if it was already painted during this one.
+  fn redraw(&self, hwnd: HWND) {
+    {
+      let mut data = self.data.lock().unwrap();
+      if data.redraw_deferred {
+        return;
+      }
+      let painted_this_refresh = data.frame_pacing
+        && data.last_paint_refresh.is_some()
+        && utilities::composition_refresh_count() == data.last_paint_refresh;
+      // checked again once the timer fires, rather than blocking the thread on
+      // `DwmFlush` while messages wait
+      if painted_this_refresh && unsafe { SetTimer(hwnd, REDRAW_TIMER_ID, 1, None) } != 0
+      {
+        data.redraw_deferred = true;
+        return;
+      }
+    }
+    unsafe { RedrawWindow(hwnd, None, None, Gdi::RDW_INTERNALPAINT) };
+  }
+
+  fn send_paint(&self) {
+    self.requested_redraw.store(false, Ordering::Release);
+    {
+      let mut data = self.data.lock().unwrap();
+      if data.frame_pacing {
+        data.last_paint_refresh = utilities::composition_refresh_count();
+      }
+    }
+    self.send_message_to_main(Message::Paint);
+  }
+
+  /// Cursor to show in the client area, the custom cursor if there is one.
+  fn client_cursor(&self, hwnd: HWND) -> HCURSOR {
+    let (selected_icon, custom) = {
+      let cursor = &self.data.lock().unwrap().cursor;
+      (cursor.selected_icon, cursor.shown_custom.clone())
+    };
+    match custom {
+      Some(custom) => custom.hcursor(hwnd),
+      None => {
+        unsafe { LoadCursorW(HINSTANCE::default(), to_windows_cursor(selected_icon)) }
+          .unwrap()
+      }
+    }
+  }
+
+  fn update_cursor_icon(&self, icon: Option<CursorIcon>) {
+    let changed = {
+      let cursor = &mut self.data.lock().unwrap().cursor;
+      let changed = cursor.effective_icon != icon;
+      cursor.effective_icon = icon;
+      changed
+    };
+    if let (true, Some(icon)) = (changed, icon) {
+      self.send_message_to_main(Message::CursorIconChanged(icon));
+    }
+  }
+
+  pub(crate) fn hide_to_tray(&self, hwnd: HWND) {
+    let (icon, title) = {
+      let data = self.data.lock().unwrap();
+      if data.in_tray {
+        return;
+      }
+      (data.icon.clone(), data.title.clone())
+    };
+
+    if !add_tray_icon(hwnd, icon.as_ref(), &title) {
+      self.report_error(WindowError::Error(format!(
+        "[`{title}`]: failed to add tray icon"
+      )));
+      return;
+    }
+    unsafe { ShowWindow(hwnd, WindowsAndMessaging::SW_HIDE) };
+
+    let mut data = self.data.lock().unwrap();
+    data.in_tray = true;
+    data.style.visibility = Visibility::Hidden;
+  }
+
+  pub(crate) fn restore_from_tray(&self, hwnd: HWND) {
+    if !self.data.lock().unwrap().in_tray {
+      return;
+    }
+
+    remove_tray_icon(hwnd);
+    unsafe { ShowWindow(hwnd, WindowsAndMessaging::SW_RESTORE) };
+    utilities::steal_foreground(hwnd);
+
+    let mut data = self.data.lock().unwrap();
+    data.in_tray = false;
+    data.style.visibility = Visibility::Shown;
+    data.style.minimized = false;
+  }
+
+  /// Run the window's [`MessageFilter`], unless the message is one of the
+  /// crate's own.
+  fn filter_message(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> FilterAction {
+    if procedure::is_internal_message(msg) {
+      return FilterAction::Pass;
+    }
+    let filter = self.data.lock().unwrap().message_filter.clone();
+    filter.map_or(FilterAction::Pass, |filter| filter.filter(hwnd, msg, wparam, lparam))
+  }
+
+  /// Answer a message the crate doesn't handle with the window's
+  /// [`MessageReplier`], or `DefWindowProc` without one.
+  fn reply_unhandled(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> LRESULT {
+    let replier = self.data.lock().unwrap().message_replier.clone();
+    replier
+      .and_then(|replier| replier.reply(hwnd, msg, wparam, lparam))
+      .unwrap_or_else(|| unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) })
+  }
+
   pub(crate) fn on_message(
     &self,
     hwnd: HWND,
@@ -290,22 +872,60 @@ impl Internal {
     wparam: WPARAM,
     lparam: LPARAM,
   ) -> LRESULT {
+    let (msg, wparam, lparam) = match self.filter_message(hwnd, msg, wparam, lparam) {
+      FilterAction::Pass => (msg, wparam, lparam),
+      FilterAction::Replace {
+        msg,
+        wparam,
+        lparam,
+      } => (msg, wparam, lparam),
+      FilterAction::Consume(result) => return result,
+    };
+
+    if msg != Command::MESSAGE_ID {
+      let translator = self.data.lock().unwrap().message_translator.clone();
+      if let Some(message) =
+        translator.and_then(|translator| translator.translate(hwnd, msg, wparam, lparam))
+      {
+        self.send_message_to_main(message);
+        return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
+      }
+    }
+
     match msg {
       Command::MESSAGE_ID => {
         let command = unsafe { Box::from_raw(wparam.0 as *mut Command) };
         // tracing::debug!("{command:?}");
+        let completion_kinds = command.completion_kinds();
+        if let Some((dropped, refused)) = queue::take_report(hwnd) {
+          self.send_message_to_main(Message::Loop(LoopMessage::QueueFull {
+            dropped,
+            refused,
+          }));
+        }
         match *command {
           Command::Exit => (),
           Command::Destroy => (),
-          Command::Redraw => unsafe {
-            RedrawWindow(hwnd, None, None, Gdi::RDW_INTERNALPAINT);
-          },
-          Command::SetVisibility(visibility) => unsafe {
-            ShowWindow(hwnd, match visibility {
+          Command::Quit => {
+            self.send_message_to_main(Message::Loop(LoopMessage::QuitRequested))
+          }
+          Command::Redraw => {
+            self.redraw(hwnd);
+          }
+          Command::SetVisibility(visibility) => {
+            let command = match visibility {
               Visibility::Hidden => WindowsAndMessaging::SW_HIDE,
-              Visibility::Shown => WindowsAndMessaging::SW_SHOW,
-            });
-          },
+              Visibility::Shown => {
+                let mut data = self.data.lock().unwrap();
+                let show = match data.style.no_activate {
+                  true => WindowsAndMessaging::SW_SHOWNA,
+                  false => WindowsAndMessaging::SW_SHOW,
+                };
+                data.initial_show.take().unwrap_or(show)
+              }
+            };
+            unsafe { ShowWindow(hwnd, command) };
+          }
           Command::SetDecorations(decorations) => {
             let style = self.data.lock().unwrap().style.clone();
             match decorations {
@@ -359,47 +979,165 @@ impl Internal {
               .expect("Failed to set window size");
             }
           }
-          Command::SetWindowText(text) => unsafe {
-            SetWindowTextW(hwnd, &text).unwrap();
-          },
-          Command::SetSize(size) => {
-            let physical_size = size.as_physical(self.data.lock().unwrap().scale_factor);
+          Command::SetResizable(resizeable) => {
+            // borderless fullscreen picks the style up once it's left
+            if self.data.lock().unwrap().style.fullscreen.is_none() {
+              let resize_style = WindowsAndMessaging::WS_SIZEBOX
+                | WindowsAndMessaging::WS_MAXIMIZEBOX
+                | WindowsAndMessaging::WS_MINIMIZEBOX;
+              let style = unsafe { GetWindowLongW(hwnd, WindowsAndMessaging::GWL_STYLE) };
+              let style = match resizeable {
+                true => style | resize_style.0 as i32,
+                false => style & !(resize_style.0 as i32),
+              };
+              unsafe { SetWindowLongW(hwnd, WindowsAndMessaging::GWL_STYLE, style) };
+              if let Err(e) = unsafe {
+                SetWindowPos(
+                  hwnd,
+                  None,
+                  0,
+                  0,
+                  0,
+                  0,
+                  WindowsAndMessaging::SWP_NOZORDER
+                    | WindowsAndMessaging::SWP_NOMOVE
+                    | WindowsAndMessaging::SWP_NOSIZE
+                    | WindowsAndMessaging::SWP_NOACTIVATE
+                    | WindowsAndMessaging::SWP_FRAMECHANGED,
+                )
+              } {
+                self.report_error(e);
+              }
+            }
+          }
+          Command::SetEnabled(enabled) => {
+            unsafe { KeyboardAndMouse::EnableWindow(hwnd, enabled) };
+          }
+          Command::SetOwner(owner) => {
+            // despite the name, GWLP_HWNDPARENT sets the owner of top-level windows
+            unsafe {
+              SetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_HWNDPARENT, owner.0)
+            };
+          }
+          Command::SetSkipTaskbar(skip) => {
+            let (style, visible) = {
+              let style = &self.data.lock().unwrap().style;
+              (get_window_ex_style(style), style.visibility == Visibility::Shown)
+            };
             unsafe {
+              SetWindowLongW(hwnd, WindowsAndMessaging::GWL_EXSTYLE, style.0 as i32)
+            };
+            if let Err(e) = unsafe {
               SetWindowPos(
                 hwnd,
                 None,
                 0,
                 0,
-                physical_size.width as i32,
-                physical_size.height as i32,
+                0,
+                0,
                 WindowsAndMessaging::SWP_NOZORDER
                   | WindowsAndMessaging::SWP_NOMOVE
-                  | WindowsAndMessaging::SWP_NOREPOSITION
-                  | WindowsAndMessaging::SWP_NOACTIVATE,
+                  | WindowsAndMessaging::SWP_NOSIZE
+                  | WindowsAndMessaging::SWP_NOACTIVATE
+                  | WindowsAndMessaging::SWP_FRAMECHANGED,
               )
-              .expect("Failed to set window size");
+            } {
+              self.report_error(e);
+            }
+            if visible {
+              if let Err(e) = utilities::set_taskbar_button(hwnd, !skip) {
+                self.report_error(e);
+              }
             }
-            unsafe { InvalidateRgn(hwnd, None, false) };
           }
-          Command::SetPosition(position) => {
-            let physical_position =
-              position.as_physical(self.data.lock().unwrap().scale_factor);
-            unsafe {
+          Command::UpdateWindowText => {
+            thread_local! {
+              // reused so frequent title updates don't allocate
+              static WINDOW_TEXT: RefCell<Vec<u16>> = const { RefCell::new(Vec::new()) };
+            }
+            let result = WINDOW_TEXT.with(|text| {
+              let mut text = text.borrow_mut();
+              {
+                let mut data = self.data.lock().unwrap();
+                data.window_text_pending = false;
+                text.clear();
+                text.extend_from_slice(&data.title_wide);
+                text.extend_from_slice(&data.subtitle_wide);
+                text.push(0);
+              }
+              unsafe { SetWindowTextW(hwnd, PCWSTR(text.as_ptr())) }
+            });
+            if let Err(e) = result {
+              self.report_error(e);
+            }
+            self.schedule_journal(hwnd);
+          }
+          Command::Snap(zone) => {
+            if self.data.lock().unwrap().style.fullscreen.is_none() {
+              snap::snap(hwnd, zone);
+            }
+          }
+          Command::DragWindow => self.drag_window(hwnd),
+          Command::HideToTray => self.hide_to_tray(hwnd),
+          Command::RestoreFromTray => self.restore_from_tray(hwnd),
+          Command::StealForeground => {
+            utilities::steal_foreground(hwnd);
+          }
+          Command::Focus => {
+            if self.data.lock().unwrap().style.child {
+              unsafe { KeyboardAndMouse::SetFocus(hwnd) };
+            } else {
+              // refused unless the app is allowed to take the foreground, in which
+              // case Windows flashes the taskbar button instead
+              unsafe { SetForegroundWindow(hwnd) };
+            }
+          }
+          Command::SetZOrder(insert_after) => {
+            if let Err(e) = unsafe {
               SetWindowPos(
                 hwnd,
-                None,
-                physical_position.x,
-                physical_position.y,
+                insert_after,
                 0,
                 0,
-                WindowsAndMessaging::SWP_NOZORDER
+                0,
+                0,
+                WindowsAndMessaging::SWP_NOMOVE
                   | WindowsAndMessaging::SWP_NOSIZE
-                  | WindowsAndMessaging::SWP_NOREPOSITION
                   | WindowsAndMessaging::SWP_NOACTIVATE,
               )
-              .expect("Failed to set window position");
+            } {
+              self.report_error(e);
             }
-            unsafe { InvalidateRgn(hwnd, None, false) };
+          }
+          Command::RequestUserAttention(attention) => {
+            utilities::request_user_attention(hwnd, attention)
+          }
+          // the window thread's execution state ends along with the thread
+          Command::SetKeepAwake(keep_awake) => power::set_keep_awake(keep_awake),
+          Command::AppendSystemMenuItem(id, label) => {
+            menu::append_system_menu_item(hwnd, id, Some(&label))
+          }
+          Command::AppendSystemMenuSeparator => {
+            menu::append_system_menu_item(hwnd, 0, None)
+          }
+          Command::SetSystemMenuItemChecked(id, checked) => {
+            menu::set_system_menu_item_checked(hwnd, id, checked)
+          }
+          Command::SetSystemMenuItemEnabled(id, enabled) => {
+            menu::set_system_menu_item_enabled(hwnd, id, enabled)
+          }
+          Command::RemoveSystemMenuItem(id) => menu::remove_system_menu_item(hwnd, id),
+          Command::SetIcon(icon) => {
+            icon::apply_window_icon(hwnd, icon.as_ref());
+            // the previous icon is no longer shown, so it's safe to destroy here
+            let previous =
+              std::mem::replace(&mut self.data.lock().unwrap().shown_icon, icon);
+            drop(previous);
+          }
+          Command::SetSize(size) => self.set_bounds(hwnd, None, Some(size)),
+          Command::SetPosition(position) => self.set_bounds(hwnd, Some(position), None),
+          Command::SetBounds(position, size) => {
+            self.set_bounds(hwnd, Some(position), Some(size))
           }
           Command::SetFullscreen(fullscreen) => {
             // update style
@@ -438,6 +1176,7 @@ impl Internal {
                       info.rcMonitor.bottom - info.rcMonitor.top,
                       WindowsAndMessaging::SWP_ASYNCWINDOWPOS
                         | WindowsAndMessaging::SWP_NOZORDER
+                        | WindowsAndMessaging::SWP_NOACTIVATE
                         | WindowsAndMessaging::SWP_FRAMECHANGED,
                     )
                     .expect("Failed to set window to fullscreen");
@@ -469,6 +1208,7 @@ impl Internal {
                     size.height as i32,
                     WindowsAndMessaging::SWP_ASYNCWINDOWPOS
                       | WindowsAndMessaging::SWP_NOZORDER
+                      | WindowsAndMessaging::SWP_NOACTIVATE
                       | WindowsAndMessaging::SWP_FRAMECHANGED,
                   )
                   .expect("Failed to set window to windowed");
@@ -479,10 +1219,22 @@ impl Internal {
           }
           Command::SetCursorIcon(icon) => {
             self.data.lock().unwrap().cursor.selected_icon = icon;
-            let cursor_icon = to_windows_cursor(icon);
-            let hcursor =
-              unsafe { LoadCursorW(HINSTANCE::default(), cursor_icon) }.unwrap();
-            unsafe { SetCursor(hcursor) };
+            unsafe { SetCursor(self.client_cursor(hwnd)) };
+            if self.data.lock().unwrap().cursor.inside_window {
+              self.update_cursor_icon(Some(icon));
+            }
+          }
+          Command::SetCustomCursor(cursor) => {
+            let (inside_window, previous) = {
+              let mut data = self.data.lock().unwrap();
+              let previous = std::mem::replace(&mut data.cursor.shown_custom, cursor);
+              (data.cursor.inside_window, previous)
+            };
+            if inside_window {
+              unsafe { SetCursor(self.client_cursor(hwnd)) };
+            }
+            // destroyed only once the new cursor replaced it
+            drop(previous);
           }
           Command::SetCursorMode(mode) => {
             // match mode {
@@ -499,29 +1251,115 @@ impl Internal {
 
             self.data.lock().unwrap().cursor.mode = mode;
             if let Err(e) = self.refresh_os_cursor() {
-              tracing::error!("{e}");
+              self.report_error(e);
             };
           }
           Command::SetCursorVisibility(visibility) => {
             self.data.lock().unwrap().cursor.visibility = visibility;
             if let Err(e) = self.refresh_os_cursor() {
-              tracing::error!("{e}");
+              self.report_error(e);
             };
           }
+          Command::SetIdleThreshold(threshold) => {
+            {
+              let mut data = self.data.lock().unwrap();
+              data.idle_threshold = threshold;
+              data.user_idle = false;
+            }
+            match threshold {
+              Some(threshold) => utilities::start_idle_timer(hwnd, threshold),
+              None => {
+                let _ = unsafe { KillTimer(hwnd, utilities::IDLE_TIMER_ID) };
+              }
+            }
+          }
+          Command::SetTimer {
+            id,
+            interval,
+            repeating,
+          } => {
+            let mut data = self.data.lock().unwrap();
+            data.timers.set(hwnd, id, interval, repeating);
+          }
+          Command::CancelTimer(id) => self.data.lock().unwrap().timers.cancel(hwnd, id),
+          Command::User(message) => self.send_message_to_main(Message::User(message)),
+          Command::Scroll(delta) => {
+            let modifiers = self.update_modifiers();
+            let position = self.data.lock().unwrap().cursor.last_position;
+            self.send_message_to_main(Message::MouseWheel {
+              delta,
+              position,
+              modifiers,
+            });
+          }
+          Command::Inject(message) => self.send_message_to_main(*message),
+          Command::AddSubclass(pending) => subclass::install(hwnd, pending),
+          Command::RemoveSubclass(id) => subclass::remove(hwnd, id),
+          Command::SetWakeTimer(deadline) => {
+            let timeout = deadline.saturating_duration_since(Instant::now());
+            // round up so the task never wakes before the deadline
+            let elapse = timeout
+              .as_micros()
+              .div_ceil(1000)
+              .clamp(1, u32::MAX as u128);
+            if unsafe { SetTimer(hwnd, stream::WAKE_TIMER_ID, elapse as u32, None) } == 0
+            {
+              self.report_error(windows::core::Error::from_win32());
+            }
+          }
+          #[cfg(feature = "audio")]
+          Command::SetAudioNotifications(enabled) => match enabled {
+            true => {
+              if let Err(e) = audio::listen(hwnd) {
+                self.report_error(e);
+              }
+            }
+            false => audio::stop(hwnd),
+          },
+          #[cfg(feature = "audio")]
+          Command::Audio(event) => {
+            if let AudioEvent::DefaultDeviceChanged {
+              flow: AudioFlow::Render,
+              ..
+            } = event
+            {
+              audio::refresh_session(hwnd);
+            }
+            self.send_message_to_main(Message::Audio(event));
+          }
+        }
+
+        for &kind in completion_kinds {
+          self.send_message_to_main(Message::CommandCompleted(kind));
         }
 
         LRESULT(0)
       }
+      WindowsAndMessaging::WM_NCHITTEST => {
+        let hit_tester = self.data.lock().unwrap().hit_tester.clone();
+        let Some(hit_tester) = hit_tester else {
+          return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
+        };
+        // screen coordinates, like wheel messages
+        let mut position = POINT {
+          x: signed_lo_word(lparam.0 as i32) as i32,
+          y: signed_hi_word(lparam.0 as i32) as i32,
+        };
+        unsafe { ScreenToClient(hwnd, &mut position) };
+        match hit_tester.hit_test(PhysicalPosition::new(position.x, position.y)) {
+          Some(hit_test) => hit_test.to_lresult(),
+          None => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+        }
+      }
       WindowsAndMessaging::WM_SETCURSOR => {
-        let in_client_area =
-          lo_word(lparam.0 as u32) as u32 == WindowsAndMessaging::HTCLIENT;
+        let hit_test = lo_word(lparam.0 as u32) as u32;
+        let in_client_area = hit_test == WindowsAndMessaging::HTCLIENT;
+
+        let selected_icon = self.data.lock().unwrap().cursor.selected_icon;
+        self.update_cursor_icon(Some(cursor::hit_test_icon(hit_test, selected_icon)));
 
         if in_client_area {
-          let icon = self.data.lock().unwrap().cursor.selected_icon;
-          let cursor_icon = to_windows_cursor(icon);
-          let hcursor =
-            unsafe { LoadCursorW(HINSTANCE::default(), cursor_icon) }.unwrap();
-          unsafe { SetCursor(hcursor) };
+          unsafe { SetCursor(self.client_cursor(hwnd)) };
         }
 
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
@@ -531,17 +1369,29 @@ impl Internal {
       //   return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
       // }
       WindowsAndMessaging::WM_CLOSE => {
+        if self.data.lock().unwrap().tray_behavior.on_close() {
+          self.hide_to_tray(hwnd);
+          return LRESULT(0);
+        }
         self.send_message_to_main(Message::CloseRequested);
         LRESULT(0)
       }
       WindowsAndMessaging::WM_PAINT => {
-        self.data.lock().unwrap().requested_redraw = false;
-        self.send_message_to_main(Message::Paint);
-        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+        // the app paints into this while handling `Message::Paint`
+        let mut paint = PAINTSTRUCT::default();
+        let hdc = unsafe { BeginPaint(hwnd, &mut paint) };
+        self.data.lock().unwrap().paint_dc = (!hdc.is_invalid()).then_some(hdc);
+        self.send_paint();
+        self.data.lock().unwrap().paint_dc = None;
+        let _ = unsafe { EndPaint(hwnd, &paint) };
+        LRESULT(0)
       }
       WindowsAndMessaging::WM_SIZE => {
-        self.data.lock().unwrap().style.maximized =
-          is_flag_set(wparam.0 as u32, WindowsAndMessaging::SIZE_MAXIMIZED);
+        {
+          let style = &mut self.data.lock().unwrap().style;
+          style.maximized = wparam.0 as u32 == WindowsAndMessaging::SIZE_MAXIMIZED;
+          style.minimized = wparam.0 as u32 == WindowsAndMessaging::SIZE_MINIMIZED;
+        }
 
         // info!("RESIZED: {_size:?}");
         let is_windowed = self.data.lock().unwrap().style.fullscreen.is_none();
@@ -549,11 +1399,27 @@ impl Internal {
         if is_windowed {
           self.update_last_windowed_pos_size(hwnd);
         }
+        self.schedule_journal(hwnd);
+        // keep the confinement on the client area through moves, resizes, and
+        // fullscreen transitions
+        if let Err(e) = self.refresh_os_cursor() {
+          self.report_error(e);
+        };
 
         let width = lo_word(lparam.0 as u32) as u32;
         let height = hi_word(lparam.0 as u32) as u32;
 
         self.send_message_to_main(Message::Resized(PhysicalSize::new(width, height)));
+
+        // Windows shows the new size as soon as this returns, stretching the last
+        // frame until the next one is presented. Holding off until the app is
+        // done resizing lets it present at the new size first.
+        let minimized = wparam.0 as u32 == WindowsAndMessaging::SIZE_MINIMIZED;
+        if !minimized && width != 0 && height != 0 {
+          let signal = ResizeSignal::default();
+          self.send_message_to_main(Message::Resizing(signal.clone()));
+          signal.wait();
+        }
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
       WindowsAndMessaging::WM_MOVE => {
@@ -590,19 +1456,47 @@ impl Internal {
 
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
+      WindowsAndMessaging::WM_ACTIVATE | WindowsAndMessaging::WM_DISPLAYCHANGE => {
+        // Windows may reset the cursor clip behind the window's back on either
+        if let Err(e) = self.refresh_os_cursor() {
+          self.report_error(e);
+        };
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_MOUSEACTIVATE if self.data.lock().unwrap().style.child => {
+        // child windows only take keyboard focus when asked to
+        unsafe { KeyboardAndMouse::SetFocus(hwnd) };
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
       WindowsAndMessaging::WM_SETFOCUS => {
         self.data.lock().unwrap().style.focused = true;
         if let Err(e) = self.refresh_os_cursor() {
-          tracing::error!("{e}");
+          self.report_error(e);
         };
+        #[cfg(feature = "accessibility")]
+        {
+          let accessible = self.data.lock().unwrap().accessible.clone();
+          accessibility::raise_focus_changed(hwnd, &accessible);
+        }
         self.send_message_to_main(Message::Focus(Focus::Gained));
 
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
+      #[cfg(feature = "accessibility")]
+      WindowsAndMessaging::WM_GETOBJECT => {
+        let (handler, accessible) = {
+          let data = self.data.lock().unwrap();
+          (data.accessibility_handler.clone(), data.accessible.clone())
+        };
+        handler
+          .and_then(|handler| handler.get_object(hwnd, wparam, lparam))
+          .or_else(|| accessibility::get_object(hwnd, wparam, lparam, &accessible))
+          .unwrap_or_else(|| unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) })
+      }
       WindowsAndMessaging::WM_KILLFOCUS => {
         self.data.lock().unwrap().style.focused = false;
         if let Err(e) = self.refresh_os_cursor() {
-          tracing::error!("{e}");
+          self.report_error(e);
         };
         self.send_message_to_main(Message::Focus(Focus::Lost));
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
@@ -612,19 +1506,46 @@ impl Internal {
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
       WindowsAndMessaging::WM_SYSCOMMAND => {
-        match wparam.0 as u32 {
-          WindowsAndMessaging::SC_MINIMIZE => {
+        let command = SystemCommand::from_wparam(wparam.0);
+
+        let to_tray = self.data.lock().unwrap().tray_behavior.on_minimize();
+        if to_tray && command == SystemCommand::Minimize {
+          self.hide_to_tray(hwnd);
+          self.send_message_to_main(Message::SystemCommand(command));
+          return LRESULT(0);
+        }
+
+        match command {
+          SystemCommand::Minimize => {
             self.data.lock().unwrap().style.minimized = true;
           }
-          WindowsAndMessaging::SC_RESTORE => {
+          SystemCommand::Restore => {
             self.data.lock().unwrap().style.minimized = false;
           }
           _ => {}
         }
 
-        self.send_message_to_main(Message::SystemCommand);
+        self.send_message_to_main(Message::SystemCommand(command));
+        // custom items have no default behavior
+        if let SystemCommand::Custom(_) = command {
+          return LRESULT(0);
+        }
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
+      tray::TRAY_MESSAGE_ID => {
+        let Some((button, is_double_click)) = tray::tray_click(lparam) else {
+          return LRESULT(0);
+        };
+
+        if button == MouseButton::Left {
+          self.restore_from_tray(hwnd);
+        }
+        self.send_message_to_main(Message::TrayIcon {
+          button,
+          is_double_click,
+        });
+        LRESULT(0)
+      }
       WindowsAndMessaging::WM_DPICHANGED => {
         let dpi = lo_word(wparam.0 as u32) as u32;
         let suggested_rect = unsafe { *(lparam.0 as *const RECT) };
@@ -641,10 +1562,66 @@ impl Internal {
         }
         .unwrap();
         let scale_factor = dpi_to_scale_factor(dpi);
-        self.data.lock().unwrap().scale_factor = scale_factor;
+        let (icon, custom_cursor_shown) = {
+          let mut data = self.data.lock().unwrap();
+          data.scale_factor = scale_factor;
+          let cursor = &data.cursor;
+          (data.shown_icon.clone(), cursor.inside_window && cursor.shown_custom.is_some())
+        };
+        // pick the images fitting the new DPI
+        if icon.is_some() {
+          icon::apply_window_icon(hwnd, icon.as_ref());
+        }
+        if custom_cursor_shown {
+          unsafe { SetCursor(self.client_cursor(hwnd)) };
+        }
         self.send_message_to_main(Message::ScaleFactorChanged(scale_factor));
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
+      WindowsAndMessaging::WM_INPUT if joystick::is_hid_input(HRAWINPUT(lparam.0)) => {
+        let events = {
+          let mut data = self.data.lock().unwrap();
+          data
+            .joysticks
+            .as_mut()
+            .map(|joysticks| joysticks.on_input(HRAWINPUT(lparam.0)))
+            .unwrap_or_default()
+        };
+        for event in events {
+          self.send_message_to_main(Message::Joystick(event));
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_INPUT_DEVICE_CHANGE => {
+        let event = {
+          let mut data = self.data.lock().unwrap();
+          data.joysticks.as_mut().and_then(|joysticks| {
+            joysticks.on_device_change(wparam.0 as u32, HANDLE(lparam.0))
+          })
+        };
+        if let Some(event) = event {
+          self.send_message_to_main(Message::Joystick(event));
+        }
+        LRESULT(0)
+      }
+      WindowsAndMessaging::WM_POINTERDOWN
+      | WindowsAndMessaging::WM_POINTERUPDATE
+      | WindowsAndMessaging::WM_POINTERUP => {
+        let pointer_id = lo_word(wparam.0 as u32) as u32;
+        let event = {
+          let mut data = self.data.lock().unwrap();
+          data.pointer_devices.read(hwnd, msg, pointer_id)
+        };
+        if let Some(event) = event {
+          self.send_message_to_main(Message::Pointer(event));
+        }
+        // still promoted to mouse messages by the default handling
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_POINTERDEVICECHANGE => {
+        self.data.lock().unwrap().pointer_devices.clear();
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
       WindowsAndMessaging::WM_INPUT => {
         let Some(data) = read_raw_input(HRAWINPUT(lparam.0)) else {
           return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
@@ -673,6 +1650,21 @@ impl Internal {
               }
             }
 
+            // raw input arrives ahead of the wheel message it belongs to
+            let wheel_flags = (WindowsAndMessaging::RI_MOUSE_WHEEL
+              | WindowsAndMessaging::RI_MOUSE_HWHEEL)
+              as u16;
+            if button_flags & wheel_flags != 0 {
+              let wheel_delta =
+                unsafe { mouse_data.Anonymous.Anonymous.usButtonData } as i16;
+              let device = data.header.hDevice.0;
+              let mut data = self.data.lock().unwrap();
+              if !is_whole_notch(wheel_delta) {
+                data.smooth_wheel_devices.insert(device);
+              }
+              data.wheel_device = Some(device);
+            }
+
             for (id, state) in mouse_button_states(button_flags).iter().enumerate() {
               if let Some(state) = *state {
                 let button = MouseButton::from_state(id);
@@ -709,37 +1701,238 @@ impl Internal {
         };
         LRESULT(0)
       }
+      WindowsAndMessaging::WM_GESTURE => {
+        let gesture = {
+          let mut data = self.data.lock().unwrap();
+          data
+            .gesture_tracker
+            .as_mut()
+            .and_then(|tracker| gesture::decode_gesture(hwnd, lparam, tracker))
+        };
+        match gesture {
+          Some(gesture) => {
+            self.send_message_to_main(Message::Gesture(gesture));
+            LRESULT(0)
+          }
+          None => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+        }
+      }
+      #[cfg(feature = "gamepad")]
+      WindowsAndMessaging::WM_TIMER if wparam.0 == gamepad::POLL_TIMER_ID => {
+        let events = {
+          let mut data = self.data.lock().unwrap();
+          // skipping polls while unfocused reports everything that changed
+          // in the meantime once focus returns
+          match data.style.focused {
+            true => data.gamepad_poller.poll(),
+            false => Vec::new(),
+          }
+        };
+        for event in events {
+          self.send_message_to_main(Message::Gamepad(event));
+        }
+        LRESULT(0)
+      }
+      WindowsAndMessaging::WM_TIMER if wparam.0 == utilities::IDLE_TIMER_ID => {
+        let idle_time = utilities::idle_time();
+        let message = {
+          let mut data = self.data.lock().unwrap();
+          let is_idle = data
+            .idle_threshold
+            .is_some_and(|threshold| idle_time >= threshold);
+          match (data.user_idle, is_idle) {
+            (false, true) => Some(Message::UserIdle(idle_time)),
+            (true, false) => Some(Message::UserActive),
+            _ => None,
+          }
+          .inspect(|_| data.user_idle = is_idle)
+        };
+        if let Some(message) = message {
+          self.send_message_to_main(message);
+        }
+        LRESULT(0)
+      }
+      WindowsAndMessaging::WM_TIMER if wparam.0 == REDRAW_TIMER_ID => {
+        let _ = unsafe { KillTimer(hwnd, REDRAW_TIMER_ID) };
+        self.data.lock().unwrap().redraw_deferred = false;
+        self.redraw(hwnd);
+        LRESULT(0)
+      }
+      WindowsAndMessaging::WM_TIMER if wparam.0 == stream::WAKE_TIMER_ID => {
+        let _ = unsafe { KillTimer(hwnd, stream::WAKE_TIMER_ID) };
+        if let Some(waker) = self.sync.waker.lock().unwrap().take() {
+          waker.wake();
+        }
+        LRESULT(0)
+      }
+      WindowsAndMessaging::WM_TIMER => {
+        let id = self.data.lock().unwrap().timers.fire(hwnd, wparam.0);
+        match id {
+          Some(id) => {
+            self.send_message_to_main(Message::Timer(id));
+            LRESULT(0)
+          }
+          None => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+        }
+      }
+      WindowsAndMessaging::WM_SETTINGCHANGE => {
+        let setting = wparam.0 as u32;
+        if setting == WindowsAndMessaging::SPI_SETHIGHCONTRAST.0 {
+          self.update_preference(
+            |data| &mut data.high_contrast,
+            utilities::is_high_contrast(),
+            Message::HighContrastChanged,
+          );
+        }
+        if setting == WindowsAndMessaging::SPI_SETCLIENTAREAANIMATION.0 {
+          self.update_preference(
+            |data| &mut data.animations,
+            utilities::animations_enabled(),
+            Message::AnimationsChanged,
+          );
+        }
+        if setting == WindowsAndMessaging::SPI_SETWHEELSCROLLLINES.0 {
+          self.data.lock().unwrap().wheel_scroll_lines = utilities::wheel_scroll_lines();
+        }
+        if setting == WindowsAndMessaging::SPI_SETWHEELSCROLLCHARS.0 {
+          self.data.lock().unwrap().wheel_scroll_chars = utilities::wheel_scroll_chars();
+        }
+        // personalization settings only name the area which changed
+        let area = (lparam.0 != 0)
+          .then(|| unsafe { PCWSTR(lparam.0 as *const u16).to_string() }.ok())
+          .flatten();
+        if area.as_deref() == Some("ImmersiveColorSet") {
+          self.update_preference(
+            |data| &mut data.transparency,
+            utilities::transparency_enabled(),
+            Message::TransparencyChanged,
+          );
+          self.update_preference(
+            |data| &mut data.accent_color,
+            utilities::accent_color(),
+            Message::AccentColorChanged,
+          );
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_DWMCOLORIZATIONCOLORCHANGED => {
+        self.update_preference(
+          |data| &mut data.accent_color,
+          utilities::accent_color(),
+          Message::AccentColorChanged,
+        );
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_POWERBROADCAST => {
+        match wparam.0 as u32 {
+          WindowsAndMessaging::PBT_APMSUSPEND => {
+            self.data.lock().unwrap().suspended = true;
+            self.send_message_to_main(Message::Suspended);
+          }
+          // sent on every wake, followed by PBT_APMRESUMESUSPEND if the user woke it
+          WindowsAndMessaging::PBT_APMRESUMEAUTOMATIC
+          | WindowsAndMessaging::PBT_APMRESUMESUSPEND => {
+            let was_suspended = std::mem::take(&mut self.data.lock().unwrap().suspended);
+            if was_suspended {
+              self.send_message_to_main(Message::Resumed);
+            }
+          }
+          WindowsAndMessaging::PBT_APMPOWERSTATUSCHANGE => {
+            self.send_message_to_main(Message::PowerStatusChanged(power::power_status()))
+          }
+          _ => (),
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_WTSSESSION_CHANGE => {
+        match wparam.0 as u32 {
+          WindowsAndMessaging::WTS_SESSION_LOCK => {
+            self.send_message_to_main(Message::SessionLocked)
+          }
+          WindowsAndMessaging::WTS_SESSION_UNLOCK => {
+            self.send_message_to_main(Message::SessionUnlocked)
+          }
+          _ => (),
+        }
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_IME_STARTCOMPOSITION => {
+        self.data.lock().unwrap().ime_composing = true;
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_INPUTLANGCHANGE => {
+        let layout = KeyboardLayout::from_hkl(HKL(lparam.0));
+        self.data.lock().unwrap().keyboard_layout = layout;
+        self.send_message_to_main(Message::KeyboardLayoutChanged(layout));
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_IME_ENDCOMPOSITION => {
+        self.data.lock().unwrap().ime_composing = false;
+        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+      }
+      WindowsAndMessaging::WM_COPYDATA => match jump_list::read_forwarded_task(lparam) {
+        Some(task) => {
+          self.send_message_to_main(Message::JumpTask(task));
+          LRESULT(1)
+        }
+        None => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+      },
       WindowsAndMessaging::WM_CHAR => {
-        let text = char::from_u32(wparam.0 as u32)
-          .unwrap_or_default()
-          .to_string();
-        self.send_message_to_main(Message::Text(text));
+        let unit = wparam.0 as u16;
+        // characters outside the BMP arrive as two messages, one per surrogate
+        let character = {
+          let mut data = self.data.lock().unwrap();
+          let units = match (data.pending_surrogate.take(), unit) {
+            (_, 0xD800..=0xDBFF) => {
+              data.pending_surrogate = Some(unit);
+              vec![]
+            }
+            (Some(high), 0xDC00..=0xDFFF) => vec![high, unit],
+            (..) => vec![unit],
+          };
+          // unpaired halves are dropped
+          char::decode_utf16(units).next().and_then(Result::ok)
+        };
+        if let Some(text) = character.map(String::from) {
+          self.send_message_to_main(Message::Text(text));
+        }
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
       WindowsAndMessaging::WM_KEYDOWN
       | WindowsAndMessaging::WM_SYSKEYDOWN
       | WindowsAndMessaging::WM_KEYUP
       | WindowsAndMessaging::WM_SYSKEYUP => {
-        let (changed, shift, ctrl, alt, win) =
-          self.data.lock().unwrap().input.update_modifiers_state();
-        if changed {
-          self.send_message_to_main(Message::ModifiersChanged {
-            shift,
-            ctrl,
-            alt,
-            win,
-          });
+        let modifiers = self.update_modifiers();
+        // VK_PROCESSKEY is reported for keystrokes consumed by the IME, which
+        // can arrive before WM_IME_STARTCOMPOSITION
+        let during_composition = self.data.lock().unwrap().ime_composing
+          || wparam.0 as u16 == KeyboardAndMouse::VK_PROCESSKEY.0;
+        let message =
+          Message::new_keyboard_message(lparam, during_composition, modifiers);
+        let mut suppressed = false;
+        if let Message::Key {
+          key,
+          state,
+          during_composition: false,
+          ..
+        } = &message
+        {
+          let mut data = self.data.lock().unwrap();
+          let key_repeat = data.key_repeat;
+          let input = &mut data.window_input;
+          let previous = input.key(*key);
+          suppressed = match (key_repeat, state) {
+            (KeyRepeat::Disabled, KeyState::Held(_)) => true,
+            (KeyRepeat::Collapsed, KeyState::Held(_)) => {
+              matches!(previous, KeyState::Held(_))
+            }
+            _ => false,
+          };
+          input.update_key_state(*key, *state);
         }
-        let message = Message::new_keyboard_message(lparam);
-        if let Message::Key { key, state, .. } = &message {
-          self
-            .data
-            .lock()
-            .unwrap()
-            .input
-            .update_key_state(*key, *state);
+        if !suppressed {
+          self.send_message_to_main(message);
         }
-        self.send_message_to_main(message);
         // messages.push();
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
@@ -748,6 +1941,21 @@ impl Internal {
         let y = signed_hi_word(lparam.0 as i32) as i32;
         let position = PhysicalPosition::new(x, y);
 
+        let start_drag = {
+          let mut data = self.data.lock().unwrap();
+          match data.tab_strip.as_mut() {
+            Some(tab_strip) if tab_strip.exceeds_drag_threshold(position) => {
+              tab_strip.pressed_at = None;
+              true
+            }
+            _ => false,
+          }
+        };
+        if start_drag {
+          self.drag_window(hwnd);
+          return LRESULT(0);
+        }
+
         let kind = get_cursor_move_kind(
           hwnd,
           self.data.lock().unwrap().cursor.inside_window,
@@ -760,7 +1968,7 @@ impl Internal {
             CursorMoveKind::Entered => {
               self.data.lock().unwrap().cursor.inside_window = true;
               if let Err(e) = self.refresh_os_cursor() {
-                tracing::error!("{e}");
+                self.report_error(e);
               };
 
               unsafe {
@@ -777,8 +1985,9 @@ impl Internal {
             }
             CursorMoveKind::Left => {
               self.data.lock().unwrap().cursor.inside_window = false;
+              self.update_cursor_icon(None);
               if let Err(e) = self.refresh_os_cursor() {
-                tracing::error!("{e}");
+                self.report_error(e);
               };
 
               true
@@ -793,7 +2002,7 @@ impl Internal {
           self.send_message_to_main(Message::CursorMove { position, kind });
           self.data.lock().unwrap().cursor.last_position = position;
           if let Err(e) = self.refresh_os_cursor() {
-            tracing::error!("{e}");
+            self.report_error(e);
           };
         }
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
@@ -801,7 +2010,7 @@ impl Internal {
       Controls::WM_MOUSELEAVE => {
         self.data.lock().unwrap().cursor.inside_window = false;
         if let Err(e) = self.refresh_os_cursor() {
-          tracing::error!("{e}");
+          self.report_error(e);
         };
         let position = self.data.lock().unwrap().cursor.last_position;
         self.send_message_to_main(Message::CursorMove {
@@ -810,21 +2019,41 @@ impl Internal {
         });
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
-      WindowsAndMessaging::WM_MOUSEWHEEL => {
-        let delta = signed_hi_word(wparam.0 as i32) as f32
-          / WindowsAndMessaging::WHEEL_DELTA as f32;
-        self.send_message_to_main(Message::MouseWheel {
-          delta_x: 0.0,
-          delta_y: delta,
-        });
-        unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
-      }
-      WindowsAndMessaging::WM_MOUSEHWHEEL => {
-        let delta = signed_hi_word(wparam.0 as i32) as f32
-          / WindowsAndMessaging::WHEEL_DELTA as f32;
+      WindowsAndMessaging::WM_MOUSEWHEEL | WindowsAndMessaging::WM_MOUSEHWHEEL => {
+        let horizontal = msg == WindowsAndMessaging::WM_MOUSEHWHEEL;
+        let (scale_factor, lines_per_notch, smooth) = {
+          let mut data = self.data.lock().unwrap();
+          let lines_per_notch = match horizontal {
+            true => data.wheel_scroll_chars,
+            false => data.wheel_scroll_lines,
+          };
+          // touchpads don't always send raw wheel input, their fractional
+          // deltas tell them apart regardless
+          let smooth = data
+            .wheel_device
+            .take()
+            .is_some_and(|device| data.smooth_wheel_devices.contains(&device));
+          (data.scale_factor, lines_per_notch, smooth)
+        };
+        let delta = ScrollDelta::from_wheel(
+          signed_hi_word(wparam.0 as i32),
+          horizontal,
+          scale_factor,
+          lines_per_notch,
+          smooth,
+        );
+        // unlike other mouse messages, wheel messages carry screen coordinates
+        let mut position = POINT {
+          x: signed_lo_word(lparam.0 as i32) as i32,
+          y: signed_hi_word(lparam.0 as i32) as i32,
+        };
+        unsafe { ScreenToClient(hwnd, &mut position) };
+        let position = PhysicalPosition::new(position.x, position.y);
+        let modifiers = self.update_modifiers();
         self.send_message_to_main(Message::MouseWheel {
-          delta_x: delta,
-          delta_y: 0.0,
+          delta,
+          position,
+          modifiers,
         });
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
@@ -833,24 +2062,58 @@ impl Internal {
           .contains(&msg) =>
       {
         // mouse move / wheels will match earlier
-        let message = Message::new_mouse_button_message(msg, wparam, lparam);
-        if let Message::MouseButton { button, state, .. } = &message {
-          self
-            .data
-            .lock()
-            .unwrap()
-            .input
-            .update_mouse_button_state(*button, *state);
+        let modifiers = self.update_modifiers();
+        let double_clicks = self.data.lock().unwrap().double_clicks;
+        let msg = match msg {
+          // the second press of a double click as if the class didn't ask for them
+          WindowsAndMessaging::WM_LBUTTONDBLCLK if !double_clicks => {
+            WindowsAndMessaging::WM_LBUTTONDOWN
+          }
+          WindowsAndMessaging::WM_MBUTTONDBLCLK if !double_clicks => {
+            WindowsAndMessaging::WM_MBUTTONDOWN
+          }
+          WindowsAndMessaging::WM_RBUTTONDBLCLK if !double_clicks => {
+            WindowsAndMessaging::WM_RBUTTONDOWN
+          }
+          WindowsAndMessaging::WM_XBUTTONDBLCLK if !double_clicks => {
+            WindowsAndMessaging::WM_XBUTTONDOWN
+          }
+          msg => msg,
+        };
+        let mut message =
+          Message::new_mouse_button_message(msg, wparam, lparam, modifiers);
+        if let Message::MouseButton {
+          button,
+          state,
+          position,
+          click_count,
+          ..
+        } = &mut message
+        {
+          let mut data = self.data.lock().unwrap();
+          data.window_input.update_mouse_button_state(*button, *state);
+          *click_count = match state {
+            ButtonState::Pressed => data
+              .clicks
+              .press(*button, *position, unsafe { GetMessageTime() }),
+            _ => data.clicks.release(*button),
+          };
+          if let (MouseButton::Left, Some(tab_strip)) = (button, data.tab_strip.as_mut())
+          {
+            tab_strip.pressed_at =
+              (state.is_pressed() && tab_strip.contains(*position)).then_some(*position);
+          }
         }
         self.send_message_to_main(message);
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
-      _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+      _ => self.reply_unhandled(hwnd, msg, wparam, lparam),
     }
   }
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Position {
   Logical(LogicalPosition),
   Physical(PhysicalPosition),
@@ -913,6 +2176,7 @@ impl From<[i32; 2]> for Position {
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LogicalPosition {
   pub x: f64,
   pub y: f64,
@@ -993,6 +2257,7 @@ impl From<[f64; 2]> for LogicalPosition {
 }
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PhysicalPosition {
   pub x: i32,
   pub y: i32,
@@ -1085,6 +2350,7 @@ impl From<[i32; 2]> for PhysicalPosition {
 }
 
 #[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Size {
   Logical(LogicalSize),
   Physical(PhysicalSize),
@@ -1123,6 +2389,7 @@ impl From<PhysicalSize> for Size {
 }
 
 #[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct LogicalSize {
   pub width: f64,
   pub height: f64,
@@ -1216,6 +2483,7 @@ impl From<[f64; 2]> for LogicalSize {
 }
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct PhysicalSize {
   pub width: u32,
   pub height: u32,
@@ -1303,13 +2571,61 @@ impl From<[u32; 2]> for PhysicalSize {
   }
 }
 
+/// An opaque RGB color.
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Color {
+  pub r: u8,
+  pub g: u8,
+  pub b: u8,
+}
+
+impl Color {
+  pub const BLACK: Self = Self::rgb(0, 0, 0);
+  pub const WHITE: Self = Self::rgb(255, 255, 255);
+
+  pub const fn rgb(r: u8, g: u8, b: u8) -> Self {
+    Self { r, g, b }
+  }
+
+  pub(crate) fn to_colorref(self) -> COLORREF {
+    COLORREF(self.r as u32 | (self.g as u32) << 8 | (self.b as u32) << 16)
+  }
+}
+
+impl From<(u8, u8, u8)> for Color {
+  fn from(value: (u8, u8, u8)) -> Self {
+    Self::rgb(value.0, value.1, value.2)
+  }
+}
+
+impl From<[u8; 3]> for Color {
+  fn from(value: [u8; 3]) -> Self {
+    Self::rgb(value[0], value[1], value[2])
+  }
+}
+
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Fullscreen {
   // Exclusive, // todo
   Borderless,
 }
 
+/// How urgently [`Window::request_user_attention`] asks for the user's
+/// attention.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum UserAttentionType {
+  /// Flash the taskbar button a few times, leaving it highlighted until the
+  /// window is activated.
+  Informational,
+  /// Flash the titlebar and taskbar button until the window is activated.
+  Critical,
+}
+
 #[derive(Default, Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CursorMode {
   #[default]
   Normal,
@@ -1318,6 +2634,7 @@ pub enum CursorMode {
 
 /// The wait behaviour of the window.
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Flow {
   /// Window will block if there are no new messages.
   #[default]
@@ -1326,9 +2643,18 @@ pub enum Flow {
   /// [`LoopMessage::Empty`](`crate::LoopMessage::Empty`) when there are no
   /// new messages and will not block.
   Poll,
+  /// Window will block if there are no new messages, but only until the given
+  /// time, then send an artificial
+  /// [`LoopMessage::Empty`](`crate::LoopMessage::Empty`). Use this to wake up
+  /// for animations or timeouts. Once the time has passed, this behaves like
+  /// [`Flow::Poll`] until the flow is changed with
+  /// [`Window::set_flow`](crate::Window::set_flow).
+  #[cfg_attr(feature = "serde", serde(skip))]
+  WaitUntil(Instant),
 }
 
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Visibility {
   #[default]
   Shown,
@@ -1336,9 +2662,30 @@ pub enum Visibility {
 }
 
 #[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Theme {
   #[default]
   Auto,
   Dark,
   Light,
 }
+
+/// The system-drawn material behind the window. Requires Windows 11 (build
+/// 22621) for [`Backdrop::Mica`], [`Backdrop::MicaAlt`], and
+/// [`Backdrop::Acrylic`]; older versions fall back to a blurred background.
+///
+/// The client area must be drawn with a transparent clear color for the
+/// backdrop to show through.
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Backdrop {
+  #[default]
+  None,
+  /// Long-lived windows, tinted by the desktop wallpaper.
+  Mica,
+  /// Variant of [`Backdrop::Mica`] with a stronger tint, intended for tabbed
+  /// windows.
+  MicaAlt,
+  /// Translucent, blurred material for transient windows.
+  Acrylic,
+}
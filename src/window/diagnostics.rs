@@ -0,0 +1,134 @@
+use windows::Win32::{
+  Foundation::HWND,
+  UI::{
+    HiDpi::{
+      self,
+      AreDpiAwarenessContextsEqual,
+      GetAwarenessFromDpiAwarenessContext,
+      GetWindowDpiAwarenessContext,
+    },
+    WindowsAndMessaging::{self, GetWindowLongW},
+  },
+};
+
+use super::{
+  data::{
+    Backdrop,
+    CursorMode,
+    Flow,
+    Fullscreen,
+    PhysicalPosition,
+    PhysicalSize,
+    Theme,
+    Visibility,
+  },
+  stage::Stage,
+};
+use crate::utilities::Monitor;
+
+/// DPI awareness of the window, as reported by Windows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum DpiAwareness {
+  Unaware,
+  System,
+  PerMonitor,
+  PerMonitorV2,
+  Unknown,
+}
+
+impl DpiAwareness {
+  pub(crate) fn of_window(hwnd: HWND) -> Self {
+    let context = unsafe { GetWindowDpiAwarenessContext(hwnd) };
+    if unsafe {
+      AreDpiAwarenessContextsEqual(
+        context,
+        HiDpi::DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2,
+      )
+    }
+    .as_bool()
+    {
+      return Self::PerMonitorV2;
+    }
+
+    match unsafe { GetAwarenessFromDpiAwarenessContext(context) } {
+      HiDpi::DPI_AWARENESS_UNAWARE => Self::Unaware,
+      HiDpi::DPI_AWARENESS_SYSTEM_AWARE => Self::System,
+      HiDpi::DPI_AWARENESS_PER_MONITOR_AWARE => Self::PerMonitor,
+      _ => Self::Unknown,
+    }
+  }
+}
+
+/// Snapshot of a single monitor at the time a [`DiagnosticsReport`] was taken.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct MonitorReport {
+  pub name: String,
+  pub position: PhysicalPosition,
+  pub size: PhysicalSize,
+  pub scale_factor: f64,
+  pub is_primary: bool,
+}
+
+impl From<&Monitor> for MonitorReport {
+  fn from(monitor: &Monitor) -> Self {
+    Self {
+      name: monitor.name(),
+      position: monitor.position(),
+      size: monitor.size(),
+      scale_factor: monitor.scale_factor(),
+      is_primary: monitor.is_primary(),
+    }
+  }
+}
+
+/// Snapshot of the window and its environment, created by
+/// [`Window::diagnostics_report`](crate::Window::diagnostics_report).
+///
+/// Attach one of these when filing issues about platform-specific behavior.
+/// With the `serde` feature enabled, the report can be serialized to JSON (or
+/// any other format) with the serializer of your choice.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct DiagnosticsReport {
+  pub crate_version: &'static str,
+  /// Build number of Windows 10/11, if it could be determined.
+  pub os_build: Option<u32>,
+  pub dpi_awareness: DpiAwareness,
+  pub scale_factor: f64,
+  pub monitors: Vec<MonitorReport>,
+  pub current_monitor: MonitorReport,
+
+  /// Raw `WINDOW_STYLE` flags.
+  pub style: u32,
+  /// Raw `WINDOW_EX_STYLE` flags.
+  pub ex_style: u32,
+  pub visibility: Visibility,
+  pub decorations: Visibility,
+  pub fullscreen: Option<Fullscreen>,
+  pub resizeable: bool,
+  pub minimized: bool,
+  pub maximized: bool,
+  pub focused: bool,
+  pub theme: Theme,
+  pub backdrop: Backdrop,
+  pub cursor_mode: CursorMode,
+
+  pub outer_position: PhysicalPosition,
+  pub outer_size: PhysicalSize,
+  pub inner_size: PhysicalSize,
+
+  pub stage: Stage,
+  pub flow: Flow,
+  /// Whether a message is waiting to be taken by the iterator.
+  pub pending_message: bool,
+  pub requested_redraw: bool,
+  pub subscribers: usize,
+}
+
+pub(crate) fn window_styles(hwnd: HWND) -> (u32, u32) {
+  let style = unsafe { GetWindowLongW(hwnd, WindowsAndMessaging::GWL_STYLE) } as u32;
+  let ex_style = unsafe { GetWindowLongW(hwnd, WindowsAndMessaging::GWL_EXSTYLE) } as u32;
+  (style, ex_style)
+}
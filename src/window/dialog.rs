@@ -0,0 +1,84 @@
+use windows::{
+  core::HSTRING,
+  Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{self, MessageBoxW, MESSAGEBOX_RESULT, MESSAGEBOX_STYLE},
+  },
+};
+
+/// Buttons and icon of a message box shown with
+/// [`Window::message_box`](crate::Window::message_box).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageBoxKind {
+  /// Ok button with an information icon.
+  Info,
+  /// Ok button with a warning icon.
+  Warning,
+  /// Ok button with an error icon.
+  Error,
+  /// Ok and cancel buttons with a question icon.
+  Confirm,
+  /// Yes and no buttons with a question icon.
+  YesNo,
+  /// Yes, no, and cancel buttons with a question icon, such as for saving
+  /// before closing.
+  YesNoCancel,
+}
+
+impl MessageBoxKind {
+  fn style(self) -> MESSAGEBOX_STYLE {
+    match self {
+      Self::Info => WindowsAndMessaging::MB_OK | WindowsAndMessaging::MB_ICONINFORMATION,
+      Self::Warning => WindowsAndMessaging::MB_OK | WindowsAndMessaging::MB_ICONWARNING,
+      Self::Error => WindowsAndMessaging::MB_OK | WindowsAndMessaging::MB_ICONERROR,
+      Self::Confirm => {
+        WindowsAndMessaging::MB_OKCANCEL | WindowsAndMessaging::MB_ICONQUESTION
+      }
+      Self::YesNo => WindowsAndMessaging::MB_YESNO | WindowsAndMessaging::MB_ICONQUESTION,
+      Self::YesNoCancel => {
+        WindowsAndMessaging::MB_YESNOCANCEL | WindowsAndMessaging::MB_ICONQUESTION
+      }
+    }
+  }
+}
+
+/// Button the user picked to close a message box. Closing it with escape or
+/// the close button counts as [`MessageBoxResult::Cancel`] for boxes with a
+/// cancel button, and [`MessageBoxResult::Ok`] for boxes with only an ok
+/// button.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum MessageBoxResult {
+  Ok,
+  Cancel,
+  Yes,
+  No,
+}
+
+/// Show a message box owned by `owner`, returning once it's closed.
+pub(crate) fn message_box(
+  owner: HWND,
+  title: &str,
+  text: &str,
+  kind: MessageBoxKind,
+) -> MessageBoxResult {
+  let result = unsafe {
+    MessageBoxW(
+      owner,
+      &HSTRING::from(text),
+      &HSTRING::from(title),
+      kind.style() | WindowsAndMessaging::MB_SETFOREGROUND,
+    )
+  };
+  match result {
+    WindowsAndMessaging::IDYES => MessageBoxResult::Yes,
+    WindowsAndMessaging::IDNO => MessageBoxResult::No,
+    WindowsAndMessaging::IDCANCEL => MessageBoxResult::Cancel,
+    MESSAGEBOX_RESULT(0) => {
+      tracing::error!("{}", windows::core::Error::from_win32());
+      MessageBoxResult::Cancel
+    }
+    _ => MessageBoxResult::Ok,
+  }
+}
@@ -0,0 +1,71 @@
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, POINT, WPARAM},
+  UI::{
+    Input::KeyboardAndMouse::ReleaseCapture,
+    WindowsAndMessaging::{self, GetCursorPos, GetSystemMetrics, SendMessageW},
+  },
+};
+
+use super::data::{PhysicalPosition, PhysicalSize};
+
+/// Area of the client area declared with
+/// [`Window::set_tab_strip`](crate::Window::set_tab_strip), and where the left
+/// button was pressed inside it, if it's still held.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct TabStrip {
+  pub position: PhysicalPosition,
+  pub size: PhysicalSize,
+  pub pressed_at: Option<PhysicalPosition>,
+}
+
+impl TabStrip {
+  pub fn new(position: PhysicalPosition, size: PhysicalSize) -> Self {
+    Self {
+      position,
+      size,
+      pressed_at: None,
+    }
+  }
+
+  pub fn contains(&self, point: PhysicalPosition) -> bool {
+    point.x >= self.position.x
+      && point.y >= self.position.y
+      && point.x < self.position.x + self.size.width as i32
+      && point.y < self.position.y + self.size.height as i32
+  }
+
+  /// Whether the cursor moved far enough from where the button was pressed to
+  /// count as a drag, using the system drag threshold.
+  pub fn exceeds_drag_threshold(&self, point: PhysicalPosition) -> bool {
+    let Some(origin) = self.pressed_at else {
+      return false;
+    };
+    let threshold_x = unsafe { GetSystemMetrics(WindowsAndMessaging::SM_CXDRAG) };
+    let threshold_y = unsafe { GetSystemMetrics(WindowsAndMessaging::SM_CYDRAG) };
+    (point.x - origin.x).abs() > threshold_x || (point.y - origin.y).abs() > threshold_y
+  }
+}
+
+/// Start moving the window as if its titlebar was dragged. Returns once the
+/// user releases the mouse button. Must be called on the window's thread.
+pub(crate) fn drag_window(hwnd: HWND) {
+  let mut cursor = POINT::default();
+  if let Err(e) = unsafe { GetCursorPos(&mut cursor) } {
+    tracing::error!("{e}");
+    return;
+  }
+  if let Err(e) = unsafe { ReleaseCapture() } {
+    tracing::error!("{e}");
+  }
+
+  // screen coordinates packed like a POINTS
+  let lparam = (cursor.x as u16 as u32) | ((cursor.y as u16 as u32) << 16);
+  unsafe {
+    SendMessageW(
+      hwnd,
+      WindowsAndMessaging::WM_NCLBUTTONDOWN,
+      WPARAM(WindowsAndMessaging::HTCAPTION as usize),
+      LPARAM(lparam as isize),
+    )
+  };
+}
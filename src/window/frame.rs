@@ -10,4 +10,13 @@ pub struct Style {
   pub maximized: bool,
   pub focused: bool,
   pub active: bool,
+  /// Created without a redirection bitmap, fixed for the window's lifetime.
+  pub no_redirection_bitmap: bool,
+  /// Created inside another window's client area, fixed for the window's
+  /// lifetime.
+  pub child: bool,
+  /// Left out of the taskbar and Alt+Tab.
+  pub skip_taskbar: bool,
+  /// Never activated by clicks or by being shown.
+  pub no_activate: bool,
 }
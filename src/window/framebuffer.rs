@@ -0,0 +1,261 @@
+use windows::Win32::{
+  Foundation::{COLORREF, HWND, POINT, SIZE},
+  Graphics::Gdi::{
+    self,
+    CreateCompatibleDC,
+    CreateDIBSection,
+    DeleteDC,
+    DeleteObject,
+    GetDC,
+    ReleaseDC,
+    SelectObject,
+    StretchDIBits,
+    BITMAPINFO,
+    BITMAPINFOHEADER,
+    BLENDFUNCTION,
+    HDC,
+  },
+  UI::WindowsAndMessaging::{self, GetWindowLongW, SetWindowLongW, UpdateLayeredWindow},
+};
+
+use super::{data::PhysicalSize, paint::WindowDc, Window};
+use crate::error::WindowError;
+
+/// How a [`Framebuffer`] is shown in its window.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PresentMode {
+  /// Copy the pixels to the client area with GDI, ignoring alpha.
+  #[default]
+  Gdi,
+  /// Show the pixels as the whole window, frame included, blending with what
+  /// is behind it by their alpha. Meant for undecorated windows, such as
+  /// overlays or splash screens. The window becomes layered, so it no longer
+  /// receives `Paint` messages.
+  Layered,
+}
+
+/// RGBA pixel buffer presented to a window with GDI, for tools and emulators
+/// which draw in software and don't need a GPU surface.
+///
+/// The buffer follows the window's size. Once the window has been resized,
+/// the next call to [`Framebuffer::pixels_mut`] resizes the buffer to match,
+/// clearing it to transparent black. Holds a clone of the window, keeping it
+/// open.
+///
+/// # Example
+///
+/// ```no_run
+/// # use witer::prelude::*;
+/// # let window = Window::builder().build()?;
+/// let mut framebuffer = Framebuffer::new(&window);
+/// for message in &window {
+///   if let Message::Paint = message {
+///     for pixel in framebuffer.pixels_mut().chunks_exact_mut(4) {
+///       pixel.copy_from_slice(&[30, 30, 30, 255]);
+///     }
+///     framebuffer.present()?;
+///   }
+/// }
+/// # Ok::<(), witer::error::WindowError>(())
+/// ```
+pub struct Framebuffer {
+  window: Window,
+  mode: PresentMode,
+  size: PhysicalSize,
+  pixels: Vec<u8>,
+  /// Pixels converted to the BGRA Windows expects, reused between frames.
+  bgra: Vec<u8>,
+}
+
+impl Framebuffer {
+  /// Create a framebuffer presented to the client area of `window`.
+  pub fn new(window: &Window) -> Self {
+    Self::with_present_mode(window, PresentMode::Gdi)
+  }
+
+  pub fn with_present_mode(window: &Window, mode: PresentMode) -> Self {
+    let mut framebuffer = Self {
+      window: window.clone(),
+      mode,
+      size: PhysicalSize::default(),
+      pixels: Vec::new(),
+      bgra: Vec::new(),
+    };
+    framebuffer.fit_window();
+    framebuffer
+  }
+
+  pub fn present_mode(&self) -> PresentMode {
+    self.mode
+  }
+
+  /// Size of the buffer, which is the window's size as of the last call to
+  /// [`Framebuffer::pixels_mut`].
+  pub fn size(&self) -> PhysicalSize {
+    self.size
+  }
+
+  /// Tightly packed, non-premultiplied RGBA8 pixels, row by row from the top.
+  pub fn pixels(&self) -> &[u8] {
+    &self.pixels
+  }
+
+  /// Pixels to draw the next frame into, resized to the window first if its
+  /// size changed. See [`Framebuffer::pixels`].
+  pub fn pixels_mut(&mut self) -> &mut [u8] {
+    self.fit_window();
+    &mut self.pixels
+  }
+
+  /// Show the pixels in the window. If the window was resized since the
+  /// pixels were drawn, they are stretched to fit.
+  pub fn present(&mut self) -> Result<(), WindowError> {
+    if self.size.width == 0 || self.size.height == 0 {
+      return Ok(());
+    }
+
+    let premultiply = self.mode == PresentMode::Layered;
+    self.bgra.clear();
+    self
+      .bgra
+      .extend(self.pixels.chunks_exact(4).flat_map(|pixel| {
+        let [r, g, b, a] = [pixel[0], pixel[1], pixel[2], pixel[3]];
+        match premultiply {
+          true => {
+            let scale = |channel: u8| ((channel as u16 * a as u16 + 127) / 255) as u8;
+            [scale(b), scale(g), scale(r), a]
+          }
+          false => [b, g, r, a],
+        }
+      }));
+
+    let info = BITMAPINFO {
+      bmiHeader: BITMAPINFOHEADER {
+        biSize: std::mem::size_of::<BITMAPINFOHEADER>() as u32,
+        biWidth: self.size.width as i32,
+        // negative for rows from the top
+        biHeight: -(self.size.height as i32),
+        biPlanes: 1,
+        biBitCount: 32,
+        biCompression: Gdi::BI_RGB.0,
+        ..Default::default()
+      },
+      ..Default::default()
+    };
+    match self.mode {
+      PresentMode::Gdi => self.present_gdi(&info),
+      PresentMode::Layered => self.present_layered(&info),
+    }
+  }
+
+  fn present_gdi(&self, info: &BITMAPINFO) -> Result<(), WindowError> {
+    let hwnd = self.window.0.hwnd;
+    let target = self.window.inner_size();
+    let paint_dc = self.window.0.data.lock().unwrap().paint_dc;
+    let Some(hdc) = WindowDc::get(hwnd, paint_dc) else {
+      return Err(WindowError::Error(
+        "failed to get device context for presenting".to_owned(),
+      ));
+    };
+    let lines = unsafe {
+      StretchDIBits(
+        hdc.hdc(),
+        0,
+        0,
+        target.width as i32,
+        target.height as i32,
+        0,
+        0,
+        self.size.width as i32,
+        self.size.height as i32,
+        Some(self.bgra.as_ptr().cast()),
+        info,
+        Gdi::DIB_RGB_COLORS,
+        Gdi::SRCCOPY,
+      )
+    };
+    drop(hdc);
+    match lines {
+      0 => Err(WindowError::Win32Error(windows::core::Error::from_win32())),
+      _ => Ok(()),
+    }
+  }
+
+  fn present_layered(&self, info: &BITMAPINFO) -> Result<(), WindowError> {
+    let hwnd = self.window.0.hwnd;
+    // changing decorations or fullscreen rewrites the extended style
+    let ex_style = unsafe { GetWindowLongW(hwnd, WindowsAndMessaging::GWL_EXSTYLE) };
+    let layered = WindowsAndMessaging::WS_EX_LAYERED.0 as i32;
+    if ex_style & layered == 0 {
+      unsafe {
+        SetWindowLongW(hwnd, WindowsAndMessaging::GWL_EXSTYLE, ex_style | layered)
+      };
+    }
+
+    let screen_dc = unsafe { GetDC(None) };
+    let memory_dc = unsafe { CreateCompatibleDC(screen_dc) };
+    let result = Self::update_layered(hwnd, screen_dc, memory_dc, info, &self.bgra);
+    unsafe {
+      DeleteDC(memory_dc);
+      ReleaseDC(None, screen_dc);
+    }
+    result
+  }
+
+  fn update_layered(
+    hwnd: HWND,
+    screen_dc: HDC,
+    memory_dc: HDC,
+    info: &BITMAPINFO,
+    bgra: &[u8],
+  ) -> Result<(), WindowError> {
+    let mut bits = std::ptr::null_mut();
+    let bitmap = unsafe {
+      CreateDIBSection(memory_dc, info, Gdi::DIB_RGB_COLORS, &mut bits, None, 0)
+    }?;
+    unsafe { std::ptr::copy_nonoverlapping(bgra.as_ptr(), bits.cast(), bgra.len()) };
+    let previous = unsafe { SelectObject(memory_dc, bitmap) };
+
+    let size = SIZE {
+      cx: info.bmiHeader.biWidth,
+      cy: -info.bmiHeader.biHeight,
+    };
+    let blend = BLENDFUNCTION {
+      BlendOp: Gdi::AC_SRC_OVER as u8,
+      BlendFlags: 0,
+      SourceConstantAlpha: 255,
+      AlphaFormat: Gdi::AC_SRC_ALPHA as u8,
+    };
+    let result = unsafe {
+      UpdateLayeredWindow(
+        hwnd,
+        screen_dc,
+        None,
+        Some(&size),
+        memory_dc,
+        Some(&POINT::default()),
+        COLORREF(0),
+        Some(&blend),
+        WindowsAndMessaging::ULW_ALPHA,
+      )
+    };
+
+    unsafe {
+      SelectObject(memory_dc, previous);
+      DeleteObject(bitmap);
+    }
+    Ok(result?)
+  }
+
+  fn fit_window(&mut self) {
+    let size = match self.mode {
+      PresentMode::Gdi => self.window.inner_size(),
+      PresentMode::Layered => self.window.outer_size(),
+    };
+    if size != self.size {
+      self.size = size;
+      self.pixels = vec![0; size.width as usize * size.height as usize * 4];
+    }
+  }
+}
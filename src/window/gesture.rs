@@ -0,0 +1,168 @@
+use std::f64::consts::PI;
+
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, POINT},
+  Graphics::Gdi::ScreenToClient,
+  UI::{
+    Input::Touch::{
+      self,
+      CloseGestureInfoHandle,
+      GetGestureInfo,
+      SetGestureConfig,
+      GESTURECONFIG,
+      GESTURECONFIG_ID,
+      GESTUREINFO,
+      HGESTUREINFO,
+    },
+    WindowsAndMessaging,
+  },
+};
+
+use super::data::PhysicalPosition;
+use crate::utilities::is_flag_set;
+
+/// Touch gesture recognized by Windows. Sent with [`Message::Gesture`] when
+/// enabled with
+/// [`WindowBuilder::with_gestures`](crate::WindowBuilder::with_gestures).
+///
+/// [`Message::Gesture`]: crate::Message::Gesture
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Gesture {
+  pub kind: GestureKind,
+  pub phase: GesturePhase,
+  /// Center of the gesture in client coordinates.
+  pub position: PhysicalPosition,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GestureKind {
+  /// Two fingers moving apart or together. `scale` is relative to the
+  /// previous pinch message, so multiply it into the current zoom level.
+  Pinch { scale: f64 },
+  /// Fingers dragged across the window. `delta` is the movement since the
+  /// previous pan message.
+  Pan { delta: PhysicalPosition },
+  /// Two fingers turning around their center. `angle` is the change in
+  /// radians since the previous rotate message, positive counter-clockwise.
+  Rotate { angle: f64 },
+  /// Two fingers tapping at once.
+  TwoFingerTap,
+  /// One finger held down while another taps.
+  PressAndTap,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GesturePhase {
+  Started,
+  Updated,
+  Ended,
+}
+
+/// Previous values of the ongoing gesture, used to turn the totals Windows
+/// reports into per-message deltas.
+#[derive(Debug, Default, Clone, Copy)]
+pub(crate) struct GestureTracker {
+  distance: u32,
+  location: POINT,
+  angle: f64,
+}
+
+/// Opt in to every gesture, including rotation which is off by default.
+pub(crate) fn enable_gestures(hwnd: HWND) {
+  const GC_ALLGESTURES: u32 = 1;
+  let config = [GESTURECONFIG {
+    dwID: GESTURECONFIG_ID(0),
+    dwWant: GC_ALLGESTURES,
+    dwBlock: 0,
+  }];
+  if let Err(e) = unsafe {
+    SetGestureConfig(hwnd, 0, &config, std::mem::size_of::<GESTURECONFIG>() as u32)
+  } {
+    tracing::error!("{e}");
+  }
+}
+
+/// Decode a `WM_GESTURE` message. The gesture info handle is closed once a
+/// gesture has been decoded; otherwise it must be passed on to
+/// `DefWindowProcW`.
+pub(crate) fn decode_gesture(
+  hwnd: HWND,
+  lparam: LPARAM,
+  tracker: &mut GestureTracker,
+) -> Option<Gesture> {
+  let handle = HGESTUREINFO(lparam.0);
+  let mut info = GESTUREINFO {
+    cbSize: std::mem::size_of::<GESTUREINFO>() as u32,
+    ..Default::default()
+  };
+  if let Err(e) = unsafe { GetGestureInfo(handle, &mut info) } {
+    tracing::error!("{e}");
+    return None;
+  }
+
+  let phase = if is_flag_set(info.dwFlags, WindowsAndMessaging::GF_BEGIN) {
+    GesturePhase::Started
+  } else if is_flag_set(info.dwFlags, WindowsAndMessaging::GF_END) {
+    GesturePhase::Ended
+  } else {
+    GesturePhase::Updated
+  };
+  let location = POINT {
+    x: info.ptsLocation.x as i32,
+    y: info.ptsLocation.y as i32,
+  };
+  let arguments = info.ullArguments as u32;
+
+  let kind = match GESTURECONFIG_ID(info.dwID) {
+    Touch::GID_ZOOM => {
+      let previous = std::mem::replace(&mut tracker.distance, arguments);
+      let scale = match phase {
+        GesturePhase::Started => 1.0,
+        _ if previous == 0 => 1.0,
+        _ => arguments as f64 / previous as f64,
+      };
+      GestureKind::Pinch { scale }
+    }
+    Touch::GID_PAN => {
+      let previous = std::mem::replace(&mut tracker.location, location);
+      let delta = match phase {
+        GesturePhase::Started => PhysicalPosition::default(),
+        _ => PhysicalPosition::new(location.x - previous.x, location.y - previous.y),
+      };
+      GestureKind::Pan { delta }
+    }
+    Touch::GID_ROTATE => {
+      // GID_ROTATE_ANGLE_FROM_ARGUMENT, the total rotation since the gesture began
+      let total = (arguments as f64 / 65535.0) * 4.0 * PI - 2.0 * PI;
+      let angle = match phase {
+        GesturePhase::Started => 0.0,
+        _ => total - tracker.angle,
+      };
+      tracker.angle = if phase == GesturePhase::Started {
+        0.0
+      } else {
+        total
+      };
+      GestureKind::Rotate { angle }
+    }
+    Touch::GID_TWOFINGERTAP => GestureKind::TwoFingerTap,
+    Touch::GID_PRESSANDTAP => GestureKind::PressAndTap,
+    // GID_BEGIN and GID_END must go to DefWindowProcW
+    _ => return None,
+  };
+
+  if let Err(e) = unsafe { CloseGestureInfoHandle(handle) } {
+    tracing::error!("{e}");
+  }
+
+  let mut client = location;
+  unsafe { ScreenToClient(hwnd, &mut client) };
+  Some(Gesture {
+    kind,
+    phase,
+    position: PhysicalPosition::new(client.x, client.y),
+  })
+}
@@ -0,0 +1,475 @@
+use std::{
+  cell::Cell,
+  ffi::{c_void, CString},
+  marker::PhantomData,
+  sync::OnceLock,
+};
+
+use windows::{
+  core::{w, PCSTR},
+  Win32::{
+    Foundation::{BOOL, HWND},
+    Graphics::{
+      Gdi::{GetDC, ReleaseDC, HDC},
+      OpenGL::{
+        self,
+        wglCreateContext,
+        wglDeleteContext,
+        wglGetCurrentContext,
+        wglGetProcAddress,
+        wglMakeCurrent,
+        ChoosePixelFormat,
+        DescribePixelFormat,
+        GetPixelFormat,
+        SetPixelFormat,
+        SwapBuffers,
+        HGLRC,
+        PIXELFORMATDESCRIPTOR,
+      },
+    },
+    System::LibraryLoader::{GetModuleHandleW, GetProcAddress},
+    UI::WindowsAndMessaging::{self, CreateWindowExW, DestroyWindow},
+  },
+};
+
+use super::Window;
+use crate::error::WindowError;
+
+/*
+  Modern contexts are created through WGL extensions, whose functions can only be
+  loaded while some context is current. They are loaded once from a throwaway context
+  on a hidden window, as a window's pixel format can only be set once and the real
+  window's format should be chosen with the extensions too.
+*/
+
+const WGL_DRAW_TO_WINDOW_ARB: i32 = 0x2001;
+const WGL_ACCELERATION_ARB: i32 = 0x2003;
+const WGL_SUPPORT_OPENGL_ARB: i32 = 0x2010;
+const WGL_DOUBLE_BUFFER_ARB: i32 = 0x2011;
+const WGL_PIXEL_TYPE_ARB: i32 = 0x2013;
+const WGL_COLOR_BITS_ARB: i32 = 0x2014;
+const WGL_ALPHA_BITS_ARB: i32 = 0x201B;
+const WGL_DEPTH_BITS_ARB: i32 = 0x2022;
+const WGL_STENCIL_BITS_ARB: i32 = 0x2023;
+const WGL_FULL_ACCELERATION_ARB: i32 = 0x2027;
+const WGL_TYPE_RGBA_ARB: i32 = 0x202B;
+const WGL_SAMPLE_BUFFERS_ARB: i32 = 0x2041;
+const WGL_SAMPLES_ARB: i32 = 0x2042;
+const WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB: i32 = 0x20A9;
+
+const WGL_CONTEXT_MAJOR_VERSION_ARB: i32 = 0x2091;
+const WGL_CONTEXT_MINOR_VERSION_ARB: i32 = 0x2092;
+const WGL_CONTEXT_FLAGS_ARB: i32 = 0x2094;
+const WGL_CONTEXT_PROFILE_MASK_ARB: i32 = 0x9126;
+const WGL_CONTEXT_DEBUG_BIT_ARB: i32 = 0x1;
+const WGL_CONTEXT_CORE_PROFILE_BIT_ARB: i32 = 0x1;
+const WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB: i32 = 0x2;
+
+type ChoosePixelFormatArb =
+  unsafe extern "system" fn(HDC, *const i32, *const f32, u32, *mut i32, *mut u32) -> BOOL;
+type CreateContextAttribsArb = unsafe extern "system" fn(HDC, HGLRC, *const i32) -> HGLRC;
+type SwapIntervalExt = unsafe extern "system" fn(i32) -> BOOL;
+type GetSwapIntervalExt = unsafe extern "system" fn() -> i32;
+
+/// Which OpenGL profile to request, see [`GlConfig::profile`].
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GlProfile {
+  /// Only the functionality of the requested version, without the deprecated
+  /// fixed-function pipeline.
+  #[default]
+  Core,
+  /// Everything up to the requested version, including deprecated functions.
+  Compatibility,
+}
+
+/// Pixel format and context attributes for [`GlContext::new`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct GlConfig {
+  /// OpenGL version to request as `(major, minor)`.
+  pub version: (u8, u8),
+  pub profile: GlProfile,
+  /// Request a debug context, for use with `glDebugMessageCallback`.
+  pub debug: bool,
+  pub alpha_bits: u8,
+  pub depth_bits: u8,
+  pub stencil_bits: u8,
+  /// Samples per pixel for multisampling, or `0` to disable it.
+  pub samples: u8,
+  /// Request a framebuffer which can convert to sRGB on write.
+  pub srgb: bool,
+  pub double_buffer: bool,
+}
+
+impl Default for GlConfig {
+  fn default() -> Self {
+    Self {
+      version: (3, 3),
+      profile: GlProfile::Core,
+      debug: false,
+      alpha_bits: 8,
+      depth_bits: 24,
+      stencil_bits: 8,
+      samples: 0,
+      srgb: false,
+      double_buffer: true,
+    }
+  }
+}
+
+/// WGL extension functions, loaded once. Any of them may be missing on old
+/// drivers or the software renderer.
+#[derive(Default)]
+struct Extensions {
+  choose_pixel_format: Option<ChoosePixelFormatArb>,
+  create_context_attribs: Option<CreateContextAttribsArb>,
+  swap_interval: Option<SwapIntervalExt>,
+  get_swap_interval: Option<GetSwapIntervalExt>,
+}
+
+fn extensions() -> &'static Extensions {
+  static EXTENSIONS: OnceLock<Extensions> = OnceLock::new();
+  EXTENSIONS.get_or_init(|| match load_extensions() {
+    Ok(extensions) => extensions,
+    Err(e) => {
+      tracing::error!("failed to load WGL extensions: {e}");
+      Extensions::default()
+    }
+  })
+}
+
+fn load_extensions() -> Result<Extensions, WindowError> {
+  let hinstance = unsafe { GetModuleHandleW(None) }?;
+  let hwnd = unsafe {
+    CreateWindowExW(
+      Default::default(),
+      w!("STATIC"),
+      w!(""),
+      WindowsAndMessaging::WS_POPUP,
+      0,
+      0,
+      1,
+      1,
+      None,
+      None,
+      hinstance,
+      None,
+    )
+  };
+  if hwnd.0 == 0 {
+    return Err(WindowError::Win32Error(windows::core::Error::from_win32()));
+  }
+
+  let hdc = unsafe { GetDC(hwnd) };
+  let result = (|| -> Result<Extensions, WindowError> {
+    set_legacy_pixel_format(hdc, &GlConfig::default())?;
+    let hglrc = unsafe { wglCreateContext(hdc) }?;
+    let extensions = unsafe { wglMakeCurrent(hdc, hglrc) }.map(|_| unsafe {
+      Extensions {
+        choose_pixel_format: load(c"wglChoosePixelFormatARB"),
+        create_context_attribs: load(c"wglCreateContextAttribsARB"),
+        swap_interval: load(c"wglSwapIntervalEXT"),
+        get_swap_interval: load(c"wglGetSwapIntervalEXT"),
+      }
+    });
+    unsafe {
+      let _ = wglMakeCurrent(HDC::default(), HGLRC::default());
+      let _ = wglDeleteContext(hglrc);
+    }
+    Ok(extensions?)
+  })();
+  unsafe {
+    ReleaseDC(hwnd, hdc);
+    let _ = DestroyWindow(hwnd);
+  }
+  result
+}
+
+/// Load an extension function of type `F` from the current context.
+///
+/// # Safety
+///
+/// `F` must be the function pointer type of the extension named `name`.
+unsafe fn load<F>(name: &std::ffi::CStr) -> Option<F> {
+  let address = unsafe { wglGetProcAddress(PCSTR(name.as_ptr().cast())) }? as usize;
+  // some drivers return small values instead of null for missing functions
+  if matches!(address, 1 | 2 | 3 | usize::MAX) {
+    return None;
+  }
+  Some(unsafe { std::mem::transmute_copy(&address) })
+}
+
+fn legacy_descriptor(config: &GlConfig) -> PIXELFORMATDESCRIPTOR {
+  let mut flags = OpenGL::PFD_DRAW_TO_WINDOW | OpenGL::PFD_SUPPORT_OPENGL;
+  if config.double_buffer {
+    flags |= OpenGL::PFD_DOUBLEBUFFER;
+  }
+  PIXELFORMATDESCRIPTOR {
+    nSize: std::mem::size_of::<PIXELFORMATDESCRIPTOR>() as u16,
+    nVersion: 1,
+    dwFlags: flags,
+    iPixelType: OpenGL::PFD_TYPE_RGBA,
+    cColorBits: 24,
+    cAlphaBits: config.alpha_bits,
+    cDepthBits: config.depth_bits,
+    cStencilBits: config.stencil_bits,
+    iLayerType: OpenGL::PFD_MAIN_PLANE.0 as u8,
+    ..Default::default()
+  }
+}
+
+fn set_legacy_pixel_format(hdc: HDC, config: &GlConfig) -> Result<(), WindowError> {
+  let descriptor = legacy_descriptor(config);
+  let format = unsafe { ChoosePixelFormat(hdc, &descriptor) };
+  if format == 0 {
+    return Err(WindowError::Win32Error(windows::core::Error::from_win32()));
+  }
+  Ok(unsafe { SetPixelFormat(hdc, format, &descriptor) }?)
+}
+
+fn set_pixel_format(hdc: HDC, config: &GlConfig) -> Result<(), WindowError> {
+  let Some(choose_pixel_format) = extensions().choose_pixel_format else {
+    if config.samples > 0 || config.srgb {
+      return Err(WindowError::Error(
+        "multisampling and sRGB need WGL_ARB_pixel_format".to_owned(),
+      ));
+    }
+    return set_legacy_pixel_format(hdc, config);
+  };
+
+  let mut attributes = vec![
+    WGL_DRAW_TO_WINDOW_ARB,
+    1,
+    WGL_SUPPORT_OPENGL_ARB,
+    1,
+    WGL_DOUBLE_BUFFER_ARB,
+    config.double_buffer as i32,
+    WGL_ACCELERATION_ARB,
+    WGL_FULL_ACCELERATION_ARB,
+    WGL_PIXEL_TYPE_ARB,
+    WGL_TYPE_RGBA_ARB,
+    WGL_COLOR_BITS_ARB,
+    24,
+    WGL_ALPHA_BITS_ARB,
+    config.alpha_bits as i32,
+    WGL_DEPTH_BITS_ARB,
+    config.depth_bits as i32,
+    WGL_STENCIL_BITS_ARB,
+    config.stencil_bits as i32,
+  ];
+  if config.samples > 0 {
+    attributes.extend([
+      WGL_SAMPLE_BUFFERS_ARB,
+      1,
+      WGL_SAMPLES_ARB,
+      config.samples as i32,
+    ]);
+  }
+  if config.srgb {
+    attributes.extend([WGL_FRAMEBUFFER_SRGB_CAPABLE_ARB, 1]);
+  }
+  attributes.push(0);
+
+  let mut format = 0;
+  let mut count = 0;
+  let found = unsafe {
+    choose_pixel_format(
+      hdc,
+      attributes.as_ptr(),
+      std::ptr::null(),
+      1,
+      &mut format,
+      &mut count,
+    )
+  };
+  if !found.as_bool() || count == 0 {
+    return Err(WindowError::Error(format!("no pixel format matches {config:?}")));
+  }
+
+  let mut descriptor = PIXELFORMATDESCRIPTOR::default();
+  unsafe {
+    DescribePixelFormat(
+      hdc,
+      format,
+      std::mem::size_of::<PIXELFORMATDESCRIPTOR>() as u32,
+      Some(&mut descriptor),
+    )
+  };
+  Ok(unsafe { SetPixelFormat(hdc, format, &descriptor) }?)
+}
+
+/// An OpenGL context drawing to a window, created without needing `glutin`.
+///
+/// A context is current on at most one thread at a time, made so with
+/// [`GlContext::make_current`]. It may be moved to another thread, such as a
+/// render thread, but not shared between threads. It must be dropped before
+/// the window it draws to.
+#[derive(Debug)]
+pub struct GlContext {
+  hwnd: HWND,
+  hdc: HDC,
+  hglrc: HGLRC,
+  _not_sync: PhantomData<Cell<()>>,
+}
+
+impl GlContext {
+  /// Create a context drawing to `window`. Sets the window's pixel format to
+  /// match `config`, or reuses the one set by an earlier context, as it can
+  /// only be set once per window.
+  pub fn new(window: &Window, config: &GlConfig) -> Result<Self, WindowError> {
+    Self::create(window, config, None)
+  }
+
+  /// Create a context like [`GlContext::new`], sharing textures, buffers, and
+  /// other objects with `share`.
+  pub fn new_shared(
+    window: &Window,
+    config: &GlConfig,
+    share: &GlContext,
+  ) -> Result<Self, WindowError> {
+    Self::create(window, config, Some(share))
+  }
+
+  fn create(
+    window: &Window,
+    config: &GlConfig,
+    share: Option<&GlContext>,
+  ) -> Result<Self, WindowError> {
+    let hwnd = window.0.hwnd;
+    // the window class has its own DC, so this stays valid for the window
+    let hdc = unsafe { GetDC(hwnd) };
+    let mut context = Self {
+      hwnd,
+      hdc,
+      hglrc: HGLRC::default(),
+      _not_sync: PhantomData,
+    };
+
+    if unsafe { GetPixelFormat(hdc) } == 0 {
+      set_pixel_format(hdc, config)?;
+    }
+
+    let share = share.map(|share| share.hglrc).unwrap_or_default();
+    context.hglrc = match extensions().create_context_attribs {
+      Some(create_context_attribs) => {
+        let profile = match config.profile {
+          GlProfile::Core => WGL_CONTEXT_CORE_PROFILE_BIT_ARB,
+          GlProfile::Compatibility => WGL_CONTEXT_COMPATIBILITY_PROFILE_BIT_ARB,
+        };
+        let flags = match config.debug {
+          true => WGL_CONTEXT_DEBUG_BIT_ARB,
+          false => 0,
+        };
+        let attributes = [
+          WGL_CONTEXT_MAJOR_VERSION_ARB,
+          config.version.0 as i32,
+          WGL_CONTEXT_MINOR_VERSION_ARB,
+          config.version.1 as i32,
+          WGL_CONTEXT_PROFILE_MASK_ARB,
+          profile,
+          WGL_CONTEXT_FLAGS_ARB,
+          flags,
+          0,
+        ];
+        let hglrc = unsafe { create_context_attribs(hdc, share, attributes.as_ptr()) };
+        if hglrc.is_invalid() {
+          return Err(WindowError::Win32Error(windows::core::Error::from_win32()));
+        }
+        hglrc
+      }
+      None if config.version > (2, 1) => {
+        return Err(WindowError::Error(format!(
+          "OpenGL {}.{} needs WGL_ARB_create_context",
+          config.version.0, config.version.1
+        )));
+      }
+      None => {
+        let hglrc = unsafe { wglCreateContext(hdc) }?;
+        if !share.is_invalid() {
+          unsafe { OpenGL::wglShareLists(share, hglrc) }?;
+        }
+        hglrc
+      }
+    };
+
+    Ok(context)
+  }
+
+  /// Make the context current on the calling thread, so OpenGL calls from it
+  /// draw to the window.
+  pub fn make_current(&self) -> Result<(), WindowError> {
+    Ok(unsafe { wglMakeCurrent(self.hdc, self.hglrc) }?)
+  }
+
+  /// Release the context from the calling thread, so another thread can make
+  /// it current.
+  pub fn make_not_current(&self) -> Result<(), WindowError> {
+    if !self.is_current() {
+      return Ok(());
+    }
+    Ok(unsafe { wglMakeCurrent(HDC::default(), HGLRC::default()) }?)
+  }
+
+  /// Whether the context is current on the calling thread.
+  pub fn is_current(&self) -> bool {
+    unsafe { wglGetCurrentContext() == self.hglrc }
+  }
+
+  /// Present the frame drawn since the last swap.
+  pub fn swap_buffers(&self) -> Result<(), WindowError> {
+    Ok(unsafe { SwapBuffers(self.hdc) }?)
+  }
+
+  /// Set how many vertical blanks [`GlContext::swap_buffers`] waits for, `0`
+  /// to disable vsync, or `-1` for adaptive vsync where supported. Applies to
+  /// the context current on the calling thread.
+  pub fn set_swap_interval(&self, interval: i32) -> Result<(), WindowError> {
+    let Some(swap_interval) = extensions().swap_interval else {
+      return Err(WindowError::Error("WGL_EXT_swap_control is unsupported".to_owned()));
+    };
+    match unsafe { swap_interval(interval) }.as_bool() {
+      true => Ok(()),
+      false => Err(WindowError::Win32Error(windows::core::Error::from_win32())),
+    }
+  }
+
+  /// Swap interval of the context current on the calling thread, or `None` if
+  /// unsupported.
+  pub fn swap_interval(&self) -> Option<i32> {
+    extensions()
+      .get_swap_interval
+      .map(|get_swap_interval| unsafe { get_swap_interval() })
+  }
+
+  /// Address of the OpenGL function `name`, for loaders such as `gl` or
+  /// `glow`, or null if it doesn't exist. The context must be current.
+  pub fn get_proc_address(&self, name: &str) -> *const c_void {
+    let Ok(name) = CString::new(name) else {
+      return std::ptr::null();
+    };
+    let name = PCSTR(name.as_ptr().cast());
+    // OpenGL 1.1 functions are only exported by opengl32.dll itself
+    let address = match unsafe { wglGetProcAddress(name) } {
+      Some(address) if !matches!(address as usize, 1 | 2 | 3 | usize::MAX) => {
+        Some(address)
+      }
+      _ => unsafe { GetModuleHandleW(w!("opengl32.dll")) }
+        .ok()
+        .and_then(|module| unsafe { GetProcAddress(module, name) }),
+    };
+    address.map_or(std::ptr::null(), |address| address as *const c_void)
+  }
+}
+
+impl Drop for GlContext {
+  fn drop(&mut self) {
+    if self.is_current() {
+      let _ = unsafe { wglMakeCurrent(HDC::default(), HGLRC::default()) };
+    }
+    if !self.hglrc.is_invalid() {
+      let _ = unsafe { wglDeleteContext(self.hglrc) };
+    }
+    unsafe { ReleaseDC(self.hwnd, self.hdc) };
+  }
+}
@@ -0,0 +1,578 @@
+use std::{
+  collections::VecDeque,
+  sync::{Arc, Mutex},
+};
+
+use super::{
+  data::{PhysicalPosition, PhysicalSize, Size},
+  input::{
+    key::{Key, PhysicalKey},
+    mouse::MouseButton,
+    state::{ButtonState, KeyState, Modifiers},
+    Input,
+  },
+  message::{Focus, LoopMessage, Message, RawInputMessage},
+  MessageIterator,
+  Window,
+};
+
+/*
+  A headless window never creates a Win32 window or thread, so it works without a desktop
+  session. Injected messages are queued, and each one is applied to the window's state as
+  the iterator yields it, the same way a real window applies input: keys and buttons
+  update the input state, resizes update the size, and so on.
+
+  Once the injected messages run out, the iterator yields `LoopMessage::Empty` to end the
+  frame and then stops, so a test can inject, iterate, check, and repeat. The input of
+  the last frame stays readable after the loop stops.
+*/
+
+/// Stand-in for a [`Window`] driven by injected messages rather than
+/// Windows, for testing input handling in CI. Write the code under test
+/// against [`AnyWindow`] to run it with either.
+///
+/// Iterating yields the injected messages in order, followed by
+/// [`LoopMessage::Empty`], after which it stops until more are injected.
+/// Getters mirror those of [`Window`](crate::Window) and reflect the messages
+/// yielded so far. Closing the window ends iteration for good, after
+/// [`LoopMessage::Exit`].
+///
+/// # Example
+///
+/// ```no_run
+/// # use witer::prelude::*;
+/// let window = HeadlessWindow::new();
+/// window.inject_key(Key::Space, KeyState::Pressed);
+/// for message in &window {
+///   // the app's handler
+/// }
+/// assert!(window.just_pressed(Key::Space));
+/// ```
+#[derive(Debug, Clone)]
+pub struct HeadlessWindow(Arc<HeadlessInner>);
+
+#[derive(Debug)]
+struct HeadlessInner {
+  data: Mutex<HeadlessData>,
+  input: Mutex<Input>,
+}
+
+#[derive(Debug)]
+struct HeadlessData {
+  title: String,
+  size: PhysicalSize,
+  scale_factor: f64,
+  focused: bool,
+  close_on_x: bool,
+  closing: bool,
+  exited: bool,
+  messages: VecDeque<Message>,
+  /// Whether `LoopMessage::Empty` was yielded since the last message.
+  frame_ended: bool,
+}
+
+impl HeadlessWindow {
+  /// Create a focused 800x500 window with a scale factor of 1.
+  pub fn new() -> Self {
+    Self(Arc::new(HeadlessInner {
+      data: Mutex::new(HeadlessData {
+        title: String::new(),
+        size: PhysicalSize::new(800, 500),
+        scale_factor: 1.0,
+        focused: true,
+        close_on_x: true,
+        closing: false,
+        exited: false,
+        messages: VecDeque::new(),
+        frame_ended: false,
+      }),
+      input: Mutex::new(Input::new()),
+    }))
+  }
+
+  pub fn with_title(self, title: impl Into<String>) -> Self {
+    self.0.data.lock().unwrap().title = title.into();
+    self
+  }
+
+  pub fn with_size(self, size: impl Into<Size>) -> Self {
+    {
+      let mut data = self.0.data.lock().unwrap();
+      data.size = size.into().as_physical(data.scale_factor);
+    }
+    self
+  }
+
+  pub fn with_scale_factor(self, scale_factor: f64) -> Self {
+    self.0.data.lock().unwrap().scale_factor = scale_factor;
+    self
+  }
+
+  /// See [`WindowBuilder::with_close_on_x`](crate::WindowBuilder::with_close_on_x).
+  pub fn with_close_on_x(self, close_on_x: bool) -> Self {
+    self.0.data.lock().unwrap().close_on_x = close_on_x;
+    self
+  }
+
+  /// Queue a message to be yielded by the iterator, applying it to the
+  /// window's state once it is.
+  pub fn inject(&self, message: Message) {
+    self.0.data.lock().unwrap().messages.push_back(message);
+  }
+
+  /// Inject a [`Message::Key`] for `key`, with the modifiers it leaves held
+  /// once the messages injected before it are yielded.
+  pub fn inject_key(&self, key: Key, state: KeyState) {
+    let modifiers = self.modifiers_after(Some((key, state)));
+    self.inject(Message::Key {
+      key,
+      physical_key: PhysicalKey::Code(key),
+      state,
+      scan_code: 0,
+      is_extended_key: false,
+      during_composition: false,
+      modifiers,
+    });
+  }
+
+  /// Inject a [`Message::Text`], as typed after the keys producing it.
+  pub fn inject_text(&self, text: impl Into<String>) {
+    self.inject(Message::Text(text.into()));
+  }
+
+  /// Inject a single click's [`Message::MouseButton`], with the modifiers
+  /// held once the messages injected before it are yielded.
+  pub fn inject_mouse_button(
+    &self,
+    button: MouseButton,
+    state: ButtonState,
+    position: PhysicalPosition,
+  ) {
+    let modifiers = self.modifiers_after(None);
+    self.inject(Message::MouseButton {
+      button,
+      state,
+      position,
+      is_double_click: false,
+      click_count: 1,
+      modifiers,
+    });
+  }
+
+  /// Inject raw mouse motion, as read by [`HeadlessWindow::mouse_delta`].
+  pub fn inject_mouse_motion(&self, delta_x: f32, delta_y: f32) {
+    self.inject(Message::RawInput(RawInputMessage::MouseMove { delta_x, delta_y }));
+  }
+
+  pub fn iter(&self) -> HeadlessIterator<'_> {
+    HeadlessIterator { window: self }
+  }
+
+  pub fn title(&self) -> String {
+    self.0.data.lock().unwrap().title.clone()
+  }
+
+  pub fn set_title(&self, title: impl AsRef<str>) {
+    self.0.data.lock().unwrap().title = title.as_ref().to_owned();
+  }
+
+  pub fn inner_size(&self) -> PhysicalSize {
+    self.0.data.lock().unwrap().size
+  }
+
+  /// Same as [`HeadlessWindow::inner_size`], as there is no frame.
+  pub fn outer_size(&self) -> PhysicalSize {
+    self.inner_size()
+  }
+
+  /// Resize the window, reported with [`Message::Resized`] like a real one.
+  pub fn set_inner_size(&self, size: impl Into<Size>) {
+    let size = size.into().as_physical(self.scale_factor());
+    self.inject(Message::Resized(size));
+  }
+
+  pub fn scale_factor(&self) -> f64 {
+    self.0.data.lock().unwrap().scale_factor
+  }
+
+  pub fn is_focused(&self) -> bool {
+    self.0.data.lock().unwrap().focused
+  }
+
+  /// Queue a [`Message::Paint`].
+  pub fn request_redraw(&self) {
+    self.inject(Message::Paint);
+  }
+
+  pub fn is_closing(&self) -> bool {
+    self.0.data.lock().unwrap().closing
+  }
+
+  /// Request the window be closed, ending iteration with
+  /// [`LoopMessage::Exit`] once the messages before it are yielded.
+  pub fn close(&self) {
+    self.exit_with_code(0);
+  }
+
+  pub fn exit_with_code(&self, code: i32) {
+    let mut data = self.0.data.lock().unwrap();
+    if data.closing {
+      return;
+    }
+    data.closing = true;
+    data
+      .messages
+      .push_back(Message::Loop(LoopMessage::Exit(code)));
+  }
+
+  pub fn key(&self, keycode: Key) -> KeyState {
+    self.0.input.lock().unwrap().key(keycode)
+  }
+
+  /// See [`Input::just_pressed`](crate::Input::just_pressed).
+  pub fn just_pressed(&self, keycode: Key) -> bool {
+    self.0.input.lock().unwrap().just_pressed(keycode)
+  }
+
+  /// See [`Input::just_released`](crate::Input::just_released).
+  pub fn just_released(&self, keycode: Key) -> bool {
+    self.0.input.lock().unwrap().just_released(keycode)
+  }
+
+  /// See [`Input::take_text`](crate::Input::take_text).
+  pub fn take_text(&self) -> String {
+    self.0.input.lock().unwrap().take_text()
+  }
+
+  pub fn mouse(&self, button: MouseButton) -> ButtonState {
+    self.0.input.lock().unwrap().mouse(button)
+  }
+
+  /// See [`Input::mouse_delta`](crate::Input::mouse_delta).
+  pub fn mouse_delta(&self) -> (f32, f32) {
+    self.0.input.lock().unwrap().mouse_delta()
+  }
+
+  /// See [`Input::scroll_delta`](crate::Input::scroll_delta).
+  pub fn scroll_delta(&self) -> (f32, f32) {
+    self.0.input.lock().unwrap().scroll_delta()
+  }
+
+  pub fn modifiers(&self) -> Modifiers {
+    self.0.input.lock().unwrap().modifiers()
+  }
+
+  pub fn shift(&self) -> ButtonState {
+    self.0.input.lock().unwrap().shift()
+  }
+
+  pub fn ctrl(&self) -> ButtonState {
+    self.0.input.lock().unwrap().ctrl()
+  }
+
+  pub fn alt(&self) -> ButtonState {
+    self.0.input.lock().unwrap().alt()
+  }
+
+  pub fn win(&self) -> ButtonState {
+    self.0.input.lock().unwrap().win()
+  }
+
+  /// Modifiers held once the messages injected so far are yielded, followed
+  /// by `change`, if any.
+  fn modifiers_after(&self, change: Option<(Key, KeyState)>) -> Modifiers {
+    let data = self.0.data.lock().unwrap();
+    let input = self.0.input.lock().unwrap();
+    let queued = data.messages.iter().filter_map(|message| match message {
+      Message::Key {
+        key,
+        state,
+        during_composition: false,
+        ..
+      } => Some((*key, *state)),
+      _ => None,
+    });
+    // the last change to a key decides whether it's held
+    let changes: Vec<_> = queued.chain(change).collect();
+    let held = |left: Key, right: Key| {
+      let pressed = |k: Key| match changes.iter().rev().find(|(key, _)| *key == k) {
+        Some((_, state)) => state.is_pressed(),
+        None => input.key(k).is_pressed(),
+      };
+      match pressed(left) || pressed(right) {
+        true => ButtonState::Pressed,
+        false => ButtonState::Released,
+      }
+    };
+    Modifiers {
+      shift: held(Key::LeftShift, Key::RightShift),
+      ctrl: held(Key::LeftControl, Key::RightControl),
+      alt: held(Key::LeftAlt, Key::RightAlt),
+      win: held(Key::LeftSuper, Key::RightSuper),
+    }
+  }
+
+  fn next_message(&self) -> Option<Message> {
+    let message = {
+      let mut data = self.0.data.lock().unwrap();
+      if data.exited {
+        return None;
+      }
+      match data.messages.pop_front() {
+        Some(message) => {
+          data.frame_ended = false;
+          message
+        }
+        None if !data.frame_ended => {
+          data.frame_ended = true;
+          Message::Loop(LoopMessage::Empty)
+        }
+        None => return None,
+      }
+    };
+
+    self.0.input.lock().unwrap().start_frame();
+    self.apply(&message);
+    if matches!(message, Message::Loop(LoopMessage::Empty) | Message::Paint) {
+      self.0.input.lock().unwrap().end_frame();
+    }
+    Some(message)
+  }
+
+  /// Update the window's state for a message about to be yielded.
+  fn apply(&self, message: &Message) {
+    let mut data = self.0.data.lock().unwrap();
+    self
+      .0
+      .input
+      .lock()
+      .unwrap()
+      .apply_message(message, data.scale_factor);
+    match message {
+      Message::Resized(size) => data.size = *size,
+      Message::ScaleFactorChanged(scale_factor) => data.scale_factor = *scale_factor,
+      Message::Focus(focus) => data.focused = *focus == Focus::Gained,
+      Message::CloseRequested if data.close_on_x && !data.closing => {
+        data.closing = true;
+        data.messages.push_back(Message::Loop(LoopMessage::Exit(0)));
+      }
+      Message::Loop(LoopMessage::Exit(_)) => {
+        data.closing = true;
+        data.exited = true;
+      }
+      _ => (),
+    }
+  }
+}
+
+impl Default for HeadlessWindow {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+pub struct HeadlessIterator<'a> {
+  window: &'a HeadlessWindow,
+}
+
+impl<'a> Iterator for HeadlessIterator<'a> {
+  type Item = Message;
+
+  fn next(&mut self) -> Option<Self::Item> {
+    self.window.next_message()
+  }
+}
+
+impl<'a> IntoIterator for &'a HeadlessWindow {
+  type IntoIter = HeadlessIterator<'a>;
+  type Item = Message;
+
+  fn into_iter(self) -> Self::IntoIter {
+    self.iter()
+  }
+}
+
+/// Methods shared by [`Window`] and [`HeadlessWindow`], so input handling can
+/// be written once and tested headless.
+///
+/// # Example
+///
+/// ```no_run
+/// # use witer::prelude::*;
+/// fn handle(window: &impl AnyWindow) {
+///   for message in window.messages() {
+///     if window.just_pressed(Key::Escape) {
+///       window.close();
+///     }
+///   }
+/// }
+/// ```
+pub trait AnyWindow {
+  type Messages<'a>: Iterator<Item = Message>
+  where
+    Self: 'a;
+
+  /// Iterate over the window's messages, like iterating over `&window`.
+  fn messages(&self) -> Self::Messages<'_>;
+  fn inject(&self, message: Message);
+  fn title(&self) -> String;
+  fn set_title(&self, title: impl AsRef<str>);
+  fn inner_size(&self) -> PhysicalSize;
+  fn outer_size(&self) -> PhysicalSize;
+  fn set_inner_size(&self, size: impl Into<Size>);
+  fn scale_factor(&self) -> f64;
+  fn is_focused(&self) -> bool;
+  fn request_redraw(&self);
+  fn is_closing(&self) -> bool;
+  fn close(&self);
+  fn exit_with_code(&self, code: i32);
+  fn key(&self, keycode: Key) -> KeyState;
+  fn just_pressed(&self, keycode: Key) -> bool;
+  fn just_released(&self, keycode: Key) -> bool;
+  fn take_text(&self) -> String;
+  fn mouse(&self, button: MouseButton) -> ButtonState;
+  fn mouse_delta(&self) -> (f32, f32);
+  fn scroll_delta(&self) -> (f32, f32);
+  fn modifiers(&self) -> Modifiers;
+  fn shift(&self) -> ButtonState;
+  fn ctrl(&self) -> ButtonState;
+  fn alt(&self) -> ButtonState;
+  fn win(&self) -> ButtonState;
+}
+
+macro_rules! impl_any_window {
+  ($window:ty, $messages:ident) => {
+    impl AnyWindow for $window {
+      type Messages<'a> = $messages<'a>;
+
+      fn messages(&self) -> Self::Messages<'_> {
+        <$window>::iter(self)
+      }
+
+      fn inject(&self, message: Message) {
+        <$window>::inject(self, message)
+      }
+
+      fn title(&self) -> String {
+        <$window>::title(self)
+      }
+
+      fn set_title(&self, title: impl AsRef<str>) {
+        <$window>::set_title(self, title)
+      }
+
+      fn inner_size(&self) -> PhysicalSize {
+        <$window>::inner_size(self)
+      }
+
+      fn outer_size(&self) -> PhysicalSize {
+        <$window>::outer_size(self)
+      }
+
+      fn set_inner_size(&self, size: impl Into<Size>) {
+        <$window>::set_inner_size(self, size)
+      }
+
+      fn scale_factor(&self) -> f64 {
+        <$window>::scale_factor(self)
+      }
+
+      fn is_focused(&self) -> bool {
+        <$window>::is_focused(self)
+      }
+
+      fn request_redraw(&self) {
+        <$window>::request_redraw(self)
+      }
+
+      fn is_closing(&self) -> bool {
+        <$window>::is_closing(self)
+      }
+
+      fn close(&self) {
+        <$window>::close(self)
+      }
+
+      fn exit_with_code(&self, code: i32) {
+        <$window>::exit_with_code(self, code)
+      }
+
+      fn key(&self, keycode: Key) -> KeyState {
+        <$window>::key(self, keycode)
+      }
+
+      fn just_pressed(&self, keycode: Key) -> bool {
+        <$window>::just_pressed(self, keycode)
+      }
+
+      fn just_released(&self, keycode: Key) -> bool {
+        <$window>::just_released(self, keycode)
+      }
+
+      fn take_text(&self) -> String {
+        <$window>::take_text(self)
+      }
+
+      fn mouse(&self, button: MouseButton) -> ButtonState {
+        <$window>::mouse(self, button)
+      }
+
+      fn mouse_delta(&self) -> (f32, f32) {
+        <$window>::mouse_delta(self)
+      }
+
+      fn scroll_delta(&self) -> (f32, f32) {
+        <$window>::scroll_delta(self)
+      }
+
+      fn modifiers(&self) -> Modifiers {
+        <$window>::modifiers(self)
+      }
+
+      fn shift(&self) -> ButtonState {
+        <$window>::shift(self)
+      }
+
+      fn ctrl(&self) -> ButtonState {
+        <$window>::ctrl(self)
+      }
+
+      fn alt(&self) -> ButtonState {
+        <$window>::alt(self)
+      }
+
+      fn win(&self) -> ButtonState {
+        <$window>::win(self)
+      }
+    }
+  };
+}
+
+impl_any_window!(Window, MessageIterator);
+impl_any_window!(HeadlessWindow, HeadlessIterator);
+
+#[cfg(test)]
+mod tests {
+  use super::*;
+
+  #[test]
+  fn shift_injected_before_iterating_applies_to_later_keys() {
+    let window = HeadlessWindow::new();
+    window.inject_key(Key::LeftShift, KeyState::Pressed);
+    window.inject_key(Key::A, KeyState::Pressed);
+
+    let modifiers: Vec<_> = window
+      .messages()
+      .filter_map(|message| match message {
+        Message::Key { key, modifiers, .. } => Some((key, modifiers.shift)),
+        _ => None,
+      })
+      .collect();
+    assert_eq!(modifiers, [
+      (Key::LeftShift, ButtonState::Pressed),
+      (Key::A, ButtonState::Pressed)
+    ]);
+    assert!(window.shift().is_pressed());
+    assert!(window.just_pressed(Key::A));
+  }
+}
@@ -0,0 +1,87 @@
+use windows::Win32::{Foundation::LRESULT, UI::WindowsAndMessaging};
+
+use super::data::PhysicalPosition;
+
+/// Part of the window a point belongs to, answering `WM_NCHITTEST`. Windows
+/// moves, resizes, and shows the resize cursors according to it.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HitTest {
+  /// Mouse input goes to the app as usual.
+  Client,
+  /// Dragging moves the window and double-clicking maximizes it, like a
+  /// titlebar.
+  Caption,
+  Left,
+  Right,
+  Top,
+  Bottom,
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  BottomRight,
+  MinimizeButton,
+  /// Hovering it shows the snap layouts flyout on Windows 11.
+  MaximizeButton,
+  CloseButton,
+  /// Mouse input goes to the window underneath, if it belongs to the same
+  /// thread.
+  Transparent,
+  /// Neither the app nor Windows handles mouse input there.
+  Nowhere,
+}
+
+impl HitTest {
+  pub(crate) fn to_lresult(self) -> LRESULT {
+    let hit_test = match self {
+      HitTest::Client => WindowsAndMessaging::HTCLIENT,
+      HitTest::Caption => WindowsAndMessaging::HTCAPTION,
+      HitTest::Left => WindowsAndMessaging::HTLEFT,
+      HitTest::Right => WindowsAndMessaging::HTRIGHT,
+      HitTest::Top => WindowsAndMessaging::HTTOP,
+      HitTest::Bottom => WindowsAndMessaging::HTBOTTOM,
+      HitTest::TopLeft => WindowsAndMessaging::HTTOPLEFT,
+      HitTest::TopRight => WindowsAndMessaging::HTTOPRIGHT,
+      HitTest::BottomLeft => WindowsAndMessaging::HTBOTTOMLEFT,
+      HitTest::BottomRight => WindowsAndMessaging::HTBOTTOMRIGHT,
+      HitTest::MinimizeButton => WindowsAndMessaging::HTMINBUTTON,
+      HitTest::MaximizeButton => WindowsAndMessaging::HTMAXBUTTON,
+      HitTest::CloseButton => WindowsAndMessaging::HTCLOSE,
+      HitTest::Transparent => {
+        return LRESULT(WindowsAndMessaging::HTTRANSPARENT as isize);
+      }
+      HitTest::Nowhere => WindowsAndMessaging::HTNOWHERE,
+    };
+    LRESULT(hit_test as isize)
+  }
+}
+
+/// Decides which part of the window each point belongs to, such as for a
+/// custom titlebar with its own buttons, or resize borders drawn by the app.
+/// Set with [`Window::set_hit_tester`](crate::Window::set_hit_tester).
+///
+/// Points are given in physical pixels relative to the client area, and may
+/// lie outside of it on the window's frame. Returning `None` leaves the point
+/// to Windows. Hit testers are called on the window thread, often, and must
+/// not block on the window's iterator.
+///
+/// Closures with the same signature as [`HitTester::hit_test`] implement this
+/// trait.
+pub trait HitTester: Send + Sync {
+  fn hit_test(&self, position: PhysicalPosition) -> Option<HitTest>;
+}
+
+impl<F> HitTester for F
+where
+  F: Fn(PhysicalPosition) -> Option<HitTest> + Send + Sync,
+{
+  fn hit_test(&self, position: PhysicalPosition) -> Option<HitTest> {
+    self(position)
+  }
+}
+
+impl std::fmt::Debug for dyn HitTester {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("HitTester")
+  }
+}
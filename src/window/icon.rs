@@ -0,0 +1,296 @@
+use std::{
+  collections::HashMap,
+  sync::{Arc, Mutex},
+};
+
+use windows::Win32::{
+  Foundation::{BOOL, HANDLE, HWND, LPARAM, WPARAM},
+  Graphics::Gdi::{CreateBitmap, DeleteObject},
+  UI::{
+    HiDpi::GetSystemMetricsForDpi,
+    WindowsAndMessaging::{
+      self,
+      CopyImage,
+      CreateIconIndirect,
+      SendMessageW,
+      HCURSOR,
+      HICON,
+      ICONINFO,
+    },
+  },
+};
+
+use super::{
+  data::{PhysicalPosition, PhysicalSize},
+  registry::{OwnedResource, Resource},
+};
+use crate::{error::WindowError, utilities::hwnd_dpi};
+
+/*
+  Icons and cursors may be created from several images of different sizes. Whenever one
+  is shown, the image closest in size to what Windows asks for at the window's DPI is
+  picked, preferring to scale down over scaling up. If none match exactly, a copy scaled
+  to the right size is made once and cached, as cursors in particular are otherwise
+  drawn at their original size no matter the DPI.
+*/
+
+/// An icon created from RGBA pixels. Cloning is cheap, and the underlying
+/// `HICON`s are destroyed once the last clone (including any held by windows
+/// using it) is dropped.
+#[derive(Debug, Clone)]
+pub struct Icon {
+  images: Arc<ImageSet>,
+}
+
+impl Icon {
+  /// Create an icon from tightly packed, non-premultiplied RGBA8 pixels.
+  pub fn from_rgba(rgba: &[u8], width: u32, height: u32) -> Result<Self, WindowError> {
+    Self::from_rgba_images([(rgba, width, height)])
+  }
+
+  /// Create an icon from several images of the same picture at different
+  /// sizes, each as tightly packed, non-premultiplied RGBA8 pixels with its
+  /// width and height. The best fitting image is shown for the window's DPI,
+  /// and picked again when it changes.
+  pub fn from_rgba_images<'a>(
+    images: impl IntoIterator<Item = (&'a [u8], u32, u32)>,
+  ) -> Result<Self, WindowError> {
+    let images = images
+      .into_iter()
+      .map(|(rgba, width, height)| {
+        let hicon = create_icon(rgba, width, height, None)?;
+        Ok((OwnedResource(Resource::Icon(hicon)), PhysicalSize::new(width, height)))
+      })
+      .collect::<Result<Vec<_>, WindowError>>()?;
+    Ok(Self {
+      images: Arc::new(ImageSet::new(images, WindowsAndMessaging::IMAGE_ICON)?),
+    })
+  }
+
+  /// Size of the largest image of the icon.
+  pub fn size(&self) -> PhysicalSize {
+    self.images.largest_size()
+  }
+
+  /// Icon closest to `size` pixels wide.
+  pub(crate) fn hicon(&self, size: u32) -> HICON {
+    self.images.select(size)
+  }
+}
+
+impl PartialEq for Icon {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.images, &other.images)
+  }
+}
+
+/// A cursor created from RGBA pixels, set with
+/// [`Window::set_custom_cursor`](crate::Window::set_custom_cursor). Cloning is
+/// cheap, and the underlying `HCURSOR`s are destroyed once the last clone is
+/// dropped.
+#[derive(Debug, Clone)]
+pub struct CustomCursor {
+  images: Arc<ImageSet>,
+}
+
+impl CustomCursor {
+  /// Create a cursor from tightly packed, non-premultiplied RGBA8 pixels, with
+  /// `hotspot` as the pixel which points at the cursor position.
+  pub fn from_rgba(
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    hotspot: PhysicalPosition,
+  ) -> Result<Self, WindowError> {
+    Self::from_rgba_images([(rgba, width, height, hotspot)])
+  }
+
+  /// Create a cursor from several images of the same picture at different
+  /// sizes, each with its width, height, and hotspot. The best fitting image is
+  /// shown for the window's DPI and the user's cursor size, and picked again
+  /// when either changes.
+  pub fn from_rgba_images<'a>(
+    images: impl IntoIterator<Item = (&'a [u8], u32, u32, PhysicalPosition)>,
+  ) -> Result<Self, WindowError> {
+    let images = images
+      .into_iter()
+      .map(|(rgba, width, height, hotspot)| {
+        if hotspot.x < 0
+          || hotspot.y < 0
+          || hotspot.x as u32 >= width
+          || hotspot.y as u32 >= height
+        {
+          return Err(WindowError::Error(format!(
+            "hotspot {hotspot:?} is outside the {width}x{height} cursor"
+          )));
+        }
+        let hcursor = HCURSOR(create_icon(rgba, width, height, Some(hotspot))?.0);
+        Ok((OwnedResource(Resource::Cursor(hcursor)), PhysicalSize::new(width, height)))
+      })
+      .collect::<Result<Vec<_>, WindowError>>()?;
+    Ok(Self {
+      images: Arc::new(ImageSet::new(images, WindowsAndMessaging::IMAGE_CURSOR)?),
+    })
+  }
+
+  /// Size of the largest image of the cursor.
+  pub fn size(&self) -> PhysicalSize {
+    self.images.largest_size()
+  }
+
+  /// Cursor to show in `hwnd` at its current DPI.
+  pub(crate) fn hcursor(&self, hwnd: HWND) -> HCURSOR {
+    let size =
+      unsafe { GetSystemMetricsForDpi(WindowsAndMessaging::SM_CXCURSOR, hwnd_dpi(hwnd)) };
+    HCURSOR(self.images.select(size.max(1) as u32).0)
+  }
+}
+
+impl PartialEq for CustomCursor {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.images, &other.images)
+  }
+}
+
+#[derive(Debug)]
+struct ImageSet {
+  kind: WindowsAndMessaging::GDI_IMAGE_TYPE,
+  /// Images as created, from smallest to largest.
+  images: Vec<(OwnedResource, PhysicalSize)>,
+  /// Copies scaled to sizes none of the images matched, by width.
+  scaled: Mutex<HashMap<u32, OwnedResource>>,
+}
+
+impl ImageSet {
+  fn new(
+    mut images: Vec<(OwnedResource, PhysicalSize)>,
+    kind: WindowsAndMessaging::GDI_IMAGE_TYPE,
+  ) -> Result<Self, WindowError> {
+    if images.is_empty() {
+      return Err(WindowError::Error("expected at least one image".to_owned()));
+    }
+    images.sort_by_key(|(_, size)| size.width);
+    Ok(Self {
+      kind,
+      images,
+      scaled: Mutex::new(HashMap::new()),
+    })
+  }
+
+  fn largest_size(&self) -> PhysicalSize {
+    self.images.last().unwrap().1
+  }
+
+  /// Image exactly `size` pixels wide, scaling the closest one if needed.
+  fn select(&self, size: u32) -> HICON {
+    // smallest image at least as large, otherwise the largest
+    let (closest, closest_size) = self
+      .images
+      .iter()
+      .find(|(_, image_size)| image_size.width >= size)
+      .unwrap_or_else(|| self.images.last().unwrap());
+    let closest = closest.handle();
+    if closest_size.width == size {
+      return closest;
+    }
+
+    let mut scaled = self.scaled.lock().unwrap();
+    if let Some(resource) = scaled.get(&size) {
+      return resource.handle();
+    }
+    let height = (closest_size.height as u64 * size as u64 / closest_size.width as u64)
+      .max(1) as i32;
+    let handle = match unsafe {
+      CopyImage(
+        HANDLE(closest.0),
+        self.kind,
+        size as i32,
+        height,
+        WindowsAndMessaging::LR_DEFAULTCOLOR,
+      )
+    } {
+      Ok(handle) => handle,
+      Err(e) => {
+        tracing::error!("{e}");
+        return closest;
+      }
+    };
+    let resource = OwnedResource(match self.kind {
+      WindowsAndMessaging::IMAGE_CURSOR => Resource::Cursor(HCURSOR(handle.0)),
+      _ => Resource::Icon(HICON(handle.0)),
+    });
+    let hicon = resource.handle();
+    scaled.insert(size, resource);
+    hicon
+  }
+}
+
+fn create_icon(
+  rgba: &[u8],
+  width: u32,
+  height: u32,
+  hotspot: Option<PhysicalPosition>,
+) -> Result<HICON, WindowError> {
+  let pixel_count = width as usize * height as usize;
+  if width == 0 || height == 0 || rgba.len() != pixel_count * 4 {
+    return Err(WindowError::Error(format!(
+      "expected {} bytes of RGBA for a {width}x{height} image, got {}",
+      pixel_count * 4,
+      rgba.len()
+    )));
+  }
+
+  // Windows expects BGRA
+  let bgra: Vec<u8> = rgba
+    .chunks_exact(4)
+    .flat_map(|pixel| [pixel[2], pixel[1], pixel[0], pixel[3]])
+    .collect();
+  // monochrome AND mask, rows padded to 16 bits; alpha takes precedence anyway
+  let and_mask = vec![0u8; width.div_ceil(16) as usize * 2 * height as usize];
+
+  let color = unsafe {
+    CreateBitmap(width as i32, height as i32, 1, 32, Some(bgra.as_ptr().cast()))
+  };
+  let mask = unsafe {
+    CreateBitmap(width as i32, height as i32, 1, 1, Some(and_mask.as_ptr().cast()))
+  };
+  let info = ICONINFO {
+    fIcon: BOOL::from(hotspot.is_none()),
+    xHotspot: hotspot.map_or(0, |hotspot| hotspot.x as u32),
+    yHotspot: hotspot.map_or(0, |hotspot| hotspot.y as u32),
+    hbmMask: mask,
+    hbmColor: color,
+  };
+  let hicon = unsafe { CreateIconIndirect(&info) };
+  // the icon keeps its own copies of the bitmaps
+  unsafe {
+    DeleteObject(color);
+    DeleteObject(mask);
+  }
+  Ok(hicon?)
+}
+
+/// Set the titlebar and taskbar icons of `hwnd` to the images of `icon` best
+/// fitting its current DPI, or the default icon if `None`.
+pub(crate) fn apply_window_icon(hwnd: HWND, icon: Option<&Icon>) {
+  let dpi = hwnd_dpi(hwnd);
+  for (kind, metric) in [
+    (WindowsAndMessaging::ICON_SMALL, WindowsAndMessaging::SM_CXSMICON),
+    (WindowsAndMessaging::ICON_BIG, WindowsAndMessaging::SM_CXICON),
+  ] {
+    let hicon = icon
+      .map(|icon| {
+        let size = unsafe { GetSystemMetricsForDpi(metric, dpi) };
+        icon.hicon(size.max(1) as u32)
+      })
+      .unwrap_or_default();
+    unsafe {
+      SendMessageW(
+        hwnd,
+        WindowsAndMessaging::WM_SETICON,
+        WPARAM(kind as usize),
+        LPARAM(hicon.0),
+      )
+    };
+  }
+}
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
 use windows::Win32::UI::Input::KeyboardAndMouse::{
   GetKeyState,
@@ -13,11 +13,23 @@ use windows::Win32::UI::Input::KeyboardAndMouse::{
 use self::state::KeyState;
 use crate::{
   utilities::is_flag_set,
-  window::input::{key::Key, mouse::MouseButton, state::ButtonState},
+  window::{
+    input::{
+      key::Key,
+      mouse::MouseButton,
+      state::{ButtonState, Modifiers},
+    },
+    message::{Message, RawInputMessage, ScrollDelta},
+  },
 };
 
+#[cfg(feature = "gamepad")]
+pub mod gamepad;
+pub mod joystick;
 pub mod key;
+pub mod layout;
 pub mod mouse;
+pub mod pointer;
 pub mod state;
 
 #[derive(Debug)]
@@ -25,14 +37,34 @@ pub struct InputState {}
 
 impl InputState {}
 
+/*
+  Besides the current state of every key and button, input is accumulated per frame for
+  poll-style code which looks at the input once per frame rather than handling each
+  message. A frame ends once `LoopMessage::Empty` or `Message::Paint` has been yielded,
+  and the next one starts when the iterator is asked for the message after it.
+
+  The state is updated on the app's thread as each message is yielded, rather than on
+  the window thread as it arrives, so accumulating and starting frames happen in message
+  order and input the app hasn't seen yet can't be wiped by a frame starting.
+*/
+
 #[derive(Debug)]
 pub struct Input {
   mouse_buttons: HashMap<MouseButton, ButtonState>,
   keys: HashMap<Key, KeyState>,
-  shift: ButtonState,
-  ctrl: ButtonState,
-  alt: ButtonState,
-  win: ButtonState,
+  modifiers: Modifiers,
+  frame: FrameInput,
+  frame_ended: bool,
+}
+
+/// Input accumulated since the current frame started.
+#[derive(Debug, Default)]
+struct FrameInput {
+  mouse_delta: (f32, f32),
+  scroll_delta: (f32, f32),
+  pressed: HashSet<Key>,
+  released: HashSet<Key>,
+  text: String,
 }
 
 impl Input {
@@ -43,16 +75,58 @@ impl Input {
     Self {
       mouse_buttons,
       keys,
-      shift: Default::default(),
-      ctrl: Default::default(),
-      alt: Default::default(),
-      win: Default::default(),
+      modifiers: Default::default(),
+      frame: Default::default(),
+      frame_ended: false,
     }
   }
 
   pub fn update_key_state(&mut self, keycode: Key, new_state: KeyState) {
-    if let Some(old_state) = self.keys.get_mut(&keycode) {
-      *old_state = new_state;
+    let previous = self.keys.insert(keycode, new_state).unwrap_or_default();
+    match (previous.is_pressed(), new_state.is_pressed()) {
+      (false, true) => {
+        self.frame.pressed.insert(keycode);
+      }
+      (true, false) => {
+        self.frame.released.insert(keycode);
+      }
+      _ => (),
+    }
+  }
+
+  pub(crate) fn push_text(&mut self, text: &str) {
+    self
+      .frame
+      .text
+      .extend(text.chars().filter(|c| !c.is_control()));
+  }
+
+  pub(crate) fn add_mouse_delta(&mut self, x: f32, y: f32) {
+    self.frame.mouse_delta.0 += x;
+    self.frame.mouse_delta.1 += y;
+  }
+
+  pub(crate) fn add_scroll_delta(&mut self, delta: ScrollDelta, scale_factor: f64) {
+    let (x, y) = match delta {
+      ScrollDelta::Lines { x, y } => (x, y),
+      ScrollDelta::Pixels { x, y } => {
+        let line = ScrollDelta::PIXELS_PER_LINE * scale_factor as f32;
+        (x / line, y / line)
+      }
+    };
+    self.frame.scroll_delta.0 += x;
+    self.frame.scroll_delta.1 += y;
+  }
+
+  /// Mark the current frame as over, see [`Input::start_frame`].
+  pub(crate) fn end_frame(&mut self) {
+    self.frame_ended = true;
+  }
+
+  /// Start accumulating a new frame if the current one is over.
+  pub(crate) fn start_frame(&mut self) {
+    if std::mem::take(&mut self.frame_ended) {
+      self.frame = FrameInput::default();
     }
   }
 
@@ -61,58 +135,65 @@ impl Input {
     button: MouseButton,
     new_state: ButtonState,
   ) {
-    if let Some(old_state) = self.mouse_buttons.get_mut(&button) {
-      *old_state = new_state;
-    }
+    self.mouse_buttons.insert(button, new_state);
   }
 
-  pub fn update_modifiers_state(
-    &mut self,
-    // shift: bool,
-    // ctrl: bool,
-    // alt: bool,
-    // win: bool,
-  ) -> (bool, ButtonState, ButtonState, ButtonState, ButtonState) {
-    let key = |keycode: VIRTUAL_KEY| -> bool {
+  /// Refresh the modifier keys from the thread's keyboard state, returning
+  /// the new snapshot if it changed.
+  pub fn update_modifiers_state(&mut self) -> Option<Modifiers> {
+    let key = |keycode: VIRTUAL_KEY| -> ButtonState {
       let state = unsafe { GetKeyState(keycode.0 as i32) };
-      is_flag_set(state, 0x1000)
-    };
-
-    let mut changed = false;
-
-    let old_value = self.shift;
-    self.shift = if key(VK_SHIFT) {
-      ButtonState::Pressed
-    } else {
-      ButtonState::Released
+      if is_flag_set(state, 0x1000) {
+        ButtonState::Pressed
+      } else {
+        ButtonState::Released
+      }
     };
-    changed |= self.shift != old_value;
 
-    let old_value = self.ctrl;
-    self.ctrl = if key(VK_CONTROL) {
-      ButtonState::Pressed
-    } else {
-      ButtonState::Released
+    let modifiers = Modifiers {
+      shift: key(VK_SHIFT),
+      ctrl: key(VK_CONTROL),
+      alt: key(VK_MENU),
+      win: if key(VK_LWIN).is_pressed() || key(VK_RWIN).is_pressed() {
+        ButtonState::Pressed
+      } else {
+        ButtonState::Released
+      },
     };
-    changed |= self.ctrl != old_value;
 
-    let old_value = self.alt;
-    self.alt = if key(VK_MENU) {
-      ButtonState::Pressed
-    } else {
-      ButtonState::Released
-    };
-    changed |= self.alt != old_value;
-
-    let old_value = self.win;
-    self.win = if key(VK_LWIN) || key(VK_RWIN) {
-      ButtonState::Pressed
-    } else {
-      ButtonState::Released
-    };
-    changed |= self.win != old_value;
+    if modifiers == self.modifiers {
+      return None;
+    }
+    self.modifiers = modifiers;
+    Some(modifiers)
+  }
 
-    (changed, self.shift, self.ctrl, self.alt, self.win)
+  /// Update the input state for a message being yielded by the iterator,
+  /// taking the modifiers it carries rather than reading them from the
+  /// keyboard.
+  pub(crate) fn apply_message(&mut self, message: &Message, scale_factor: f64) {
+    match message {
+      Message::Key {
+        key,
+        state,
+        during_composition: false,
+        modifiers,
+        ..
+      } => {
+        self.update_key_state(*key, *state);
+        self.modifiers = *modifiers;
+      }
+      Message::ModifiersChanged(modifiers) => self.modifiers = *modifiers,
+      Message::Text(text) => self.push_text(text),
+      Message::MouseButton { button, state, .. } => {
+        self.update_mouse_button_state(*button, *state);
+      }
+      Message::MouseWheel { delta, .. } => self.add_scroll_delta(*delta, scale_factor),
+      Message::RawInput(RawInputMessage::MouseMove { delta_x, delta_y }) => {
+        self.add_mouse_delta(*delta_x, *delta_y);
+      }
+      _ => (),
+    }
   }
 
   // KEYBOARD
@@ -125,6 +206,23 @@ impl Input {
       .unwrap_or(KeyState::Released)
   }
 
+  /// Whether `keycode` was pressed this frame. Auto-repeats don't count.
+  pub fn just_pressed(&self, keycode: Key) -> bool {
+    self.frame.pressed.contains(&keycode)
+  }
+
+  /// Whether `keycode` was released this frame.
+  pub fn just_released(&self, keycode: Key) -> bool {
+    self.frame.released.contains(&keycode)
+  }
+
+  /// Text typed this frame and not yet taken, such as for immediate-mode GUIs.
+  /// Control characters like backspace or enter are left out, handle those as
+  /// keys instead.
+  pub fn take_text(&mut self) -> String {
+    std::mem::take(&mut self.frame.text)
+  }
+
   // MOUSE
 
   pub fn mouse(&self, button: MouseButton) -> ButtonState {
@@ -135,22 +233,37 @@ impl Input {
       .unwrap_or(ButtonState::Released)
   }
 
+  /// Raw mouse motion this frame, unaffected by pointer acceleration or the
+  /// window's bounds, such as for first-person cameras.
+  pub fn mouse_delta(&self) -> (f32, f32) {
+    self.frame.mouse_delta
+  }
+
+  /// Horizontal and vertical scrolling this frame, in lines.
+  pub fn scroll_delta(&self) -> (f32, f32) {
+    self.frame.scroll_delta
+  }
+
   // MODS
 
+  pub fn modifiers(&self) -> Modifiers {
+    self.modifiers
+  }
+
   pub fn shift(&self) -> ButtonState {
-    self.shift
+    self.modifiers.shift
   }
 
   pub fn ctrl(&self) -> ButtonState {
-    self.ctrl
+    self.modifiers.ctrl
   }
 
   pub fn alt(&self) -> ButtonState {
-    self.alt
+    self.modifiers.alt
   }
 
   pub fn win(&self) -> ButtonState {
-    self.win
+    self.modifiers.win
   }
 }
 
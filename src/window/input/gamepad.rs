@@ -0,0 +1,223 @@
+use std::time::{Duration, Instant};
+
+use windows::Win32::{
+  Foundation::{ERROR_SUCCESS, HWND},
+  UI::{
+    Input::XboxController::{
+      self,
+      XInputGetState,
+      XInputSetState,
+      XINPUT_GAMEPAD,
+      XINPUT_STATE,
+      XINPUT_VIBRATION,
+    },
+    WindowsAndMessaging::SetTimer,
+  },
+};
+
+use super::state::ButtonState;
+
+/// Timer used to poll XInput on the window thread.
+pub(crate) const POLL_TIMER_ID: usize = 0x6770;
+const POLL_INTERVAL: Duration = Duration::from_millis(8);
+/// Querying empty slots is slow, so disconnected pads are checked less often.
+const RESCAN_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GamepadEvent {
+  Connected {
+    id: u32,
+  },
+  Disconnected {
+    id: u32,
+  },
+  Button {
+    id: u32,
+    button: GamepadButton,
+    state: ButtonState,
+  },
+  /// Sticks range from `-1.0` to `1.0` with the deadzone removed, triggers
+  /// from `0.0` to `1.0`.
+  Axis {
+    id: u32,
+    axis: GamepadAxis,
+    value: f32,
+  },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadButton {
+  A,
+  B,
+  X,
+  Y,
+  LeftShoulder,
+  RightShoulder,
+  LeftThumb,
+  RightThumb,
+  Back,
+  Start,
+  DPadUp,
+  DPadDown,
+  DPadLeft,
+  DPadRight,
+}
+
+impl GamepadButton {
+  const ALL: [(Self, XboxController::XINPUT_GAMEPAD_BUTTON_FLAGS); 14] = [
+    (Self::A, XboxController::XINPUT_GAMEPAD_A),
+    (Self::B, XboxController::XINPUT_GAMEPAD_B),
+    (Self::X, XboxController::XINPUT_GAMEPAD_X),
+    (Self::Y, XboxController::XINPUT_GAMEPAD_Y),
+    (Self::LeftShoulder, XboxController::XINPUT_GAMEPAD_LEFT_SHOULDER),
+    (Self::RightShoulder, XboxController::XINPUT_GAMEPAD_RIGHT_SHOULDER),
+    (Self::LeftThumb, XboxController::XINPUT_GAMEPAD_LEFT_THUMB),
+    (Self::RightThumb, XboxController::XINPUT_GAMEPAD_RIGHT_THUMB),
+    (Self::Back, XboxController::XINPUT_GAMEPAD_BACK),
+    (Self::Start, XboxController::XINPUT_GAMEPAD_START),
+    (Self::DPadUp, XboxController::XINPUT_GAMEPAD_DPAD_UP),
+    (Self::DPadDown, XboxController::XINPUT_GAMEPAD_DPAD_DOWN),
+    (Self::DPadLeft, XboxController::XINPUT_GAMEPAD_DPAD_LEFT),
+    (Self::DPadRight, XboxController::XINPUT_GAMEPAD_DPAD_RIGHT),
+  ];
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum GamepadAxis {
+  LeftStickX,
+  LeftStickY,
+  RightStickX,
+  RightStickY,
+  LeftTrigger,
+  RightTrigger,
+}
+
+impl GamepadAxis {
+  fn values(pad: &XINPUT_GAMEPAD) -> [(Self, f32); 6] {
+    let left_deadzone = XboxController::XINPUT_GAMEPAD_LEFT_THUMB_DEADZONE.0 as f32;
+    let right_deadzone = XboxController::XINPUT_GAMEPAD_RIGHT_THUMB_DEADZONE.0 as f32;
+    let trigger_threshold = XboxController::XINPUT_GAMEPAD_TRIGGER_THRESHOLD.0 as f32;
+    [
+      (Self::LeftStickX, stick(pad.sThumbLX, left_deadzone)),
+      (Self::LeftStickY, stick(pad.sThumbLY, left_deadzone)),
+      (Self::RightStickX, stick(pad.sThumbRX, right_deadzone)),
+      (Self::RightStickY, stick(pad.sThumbRY, right_deadzone)),
+      (Self::LeftTrigger, trigger(pad.bLeftTrigger, trigger_threshold)),
+      (Self::RightTrigger, trigger(pad.bRightTrigger, trigger_threshold)),
+    ]
+  }
+}
+
+fn stick(value: i16, deadzone: f32) -> f32 {
+  let value = value as f32;
+  if value.abs() <= deadzone {
+    0.0
+  } else {
+    let range = i16::MAX as f32 - deadzone;
+    ((value.abs() - deadzone) / range).min(1.0) * value.signum()
+  }
+}
+
+fn trigger(value: u8, threshold: f32) -> f32 {
+  let value = value as f32;
+  if value <= threshold {
+    0.0
+  } else {
+    (value - threshold) / (u8::MAX as f32 - threshold)
+  }
+}
+
+/// Sets the speed of the low-frequency (left) and high-frequency (right)
+/// rumble motors of a gamepad, each from `0.0` to `1.0`.
+pub(crate) fn set_rumble(id: u32, low_frequency: f32, high_frequency: f32) {
+  let vibration = XINPUT_VIBRATION {
+    wLeftMotorSpeed: (low_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+    wRightMotorSpeed: (high_frequency.clamp(0.0, 1.0) * u16::MAX as f32) as u16,
+  };
+  let result = unsafe { XInputSetState(id, &vibration) };
+  if result != ERROR_SUCCESS.0 {
+    tracing::error!("failed to set rumble for gamepad {id}: error {result}");
+  }
+}
+
+pub(crate) fn start_polling(hwnd: HWND) {
+  if unsafe { SetTimer(hwnd, POLL_TIMER_ID, POLL_INTERVAL.as_millis() as u32, None) } == 0
+  {
+    tracing::error!("{}", windows::core::Error::from_win32());
+  }
+}
+
+#[derive(Debug)]
+pub(crate) struct GamepadPoller {
+  pads: [Option<XINPUT_STATE>; XboxController::XUSER_MAX_COUNT as usize],
+  last_scan: Option<Instant>,
+}
+
+impl Default for GamepadPoller {
+  fn default() -> Self {
+    Self {
+      pads: [None; XboxController::XUSER_MAX_COUNT as usize],
+      last_scan: None,
+    }
+  }
+}
+
+impl GamepadPoller {
+  pub fn poll(&mut self) -> Vec<GamepadEvent> {
+    let rescan = self
+      .last_scan
+      .is_none_or(|last_scan| last_scan.elapsed() >= RESCAN_INTERVAL);
+    if rescan {
+      self.last_scan = Some(Instant::now());
+    }
+
+    let mut events = Vec::new();
+    for (id, slot) in self.pads.iter_mut().enumerate() {
+      let id = id as u32;
+      if slot.is_none() && !rescan {
+        continue;
+      }
+
+      let mut state = XINPUT_STATE::default();
+      if unsafe { XInputGetState(id, &mut state) } != ERROR_SUCCESS.0 {
+        if slot.take().is_some() {
+          events.push(GamepadEvent::Disconnected { id });
+        }
+        continue;
+      }
+
+      let previous = match slot.replace(state) {
+        Some(previous) if previous.dwPacketNumber == state.dwPacketNumber => continue,
+        Some(previous) => previous.Gamepad,
+        None => {
+          events.push(GamepadEvent::Connected { id });
+          XINPUT_GAMEPAD::default()
+        }
+      };
+
+      for (button, flag) in GamepadButton::ALL {
+        let was_pressed = previous.wButtons.contains(flag);
+        let is_pressed = state.Gamepad.wButtons.contains(flag);
+        if was_pressed != is_pressed {
+          let state = match is_pressed {
+            true => ButtonState::Pressed,
+            false => ButtonState::Released,
+          };
+          events.push(GamepadEvent::Button { id, button, state });
+        }
+      }
+
+      let old_axes = GamepadAxis::values(&previous);
+      let new_axes = GamepadAxis::values(&state.Gamepad);
+      for ((axis, old), (_, value)) in old_axes.into_iter().zip(new_axes) {
+        if old != value {
+          events.push(GamepadEvent::Axis { id, axis, value });
+        }
+      }
+    }
+
+    events
+  }
+}
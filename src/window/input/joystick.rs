@@ -0,0 +1,495 @@
+use std::collections::{HashMap, HashSet};
+
+use windows::Win32::{
+  Devices::HumanInterfaceDevice::{
+    self,
+    HidP_GetButtonCaps,
+    HidP_GetCaps,
+    HidP_GetUsageValue,
+    HidP_GetUsages,
+    HidP_GetValueCaps,
+    HidP_Input,
+    HidP_MaxUsageListLength,
+    HIDP_BUTTON_CAPS,
+    HIDP_CAPS,
+    HIDP_STATUS_SUCCESS,
+    HIDP_VALUE_CAPS,
+    PHIDP_PREPARSED_DATA,
+  },
+  Foundation::{HANDLE, HWND},
+  UI::{
+    Input::{
+      self,
+      GetRawInputData,
+      GetRawInputDeviceInfoW,
+      HRAWINPUT,
+      RAWINPUT,
+      RAWINPUTDEVICE,
+      RAWINPUTHEADER,
+      RID_DEVICE_INFO,
+    },
+    WindowsAndMessaging,
+  },
+};
+
+use super::state::ButtonState;
+use crate::utilities::register_raw_input_devices;
+
+/// Handle of a joystick, stable for as long as it stays connected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct JoystickId(isize);
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum JoystickEvent {
+  Connected {
+    id: JoystickId,
+    vendor_id: u32,
+    product_id: u32,
+  },
+  Disconnected {
+    id: JoystickId,
+  },
+  /// Buttons are numbered from `1`, as reported by the device.
+  Button {
+    id: JoystickId,
+    button: u16,
+    state: ButtonState,
+  },
+  /// Axes range from `-1.0` to `1.0` across the range reported by the device.
+  Axis {
+    id: JoystickId,
+    axis: JoystickAxis,
+    value: f32,
+  },
+  /// Hat switches are numbered from `0` in the order the device reports them.
+  /// `None` when the hat is centered.
+  Hat {
+    id: JoystickId,
+    hat: usize,
+    direction: Option<HatDirection>,
+  },
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum JoystickAxis {
+  X,
+  Y,
+  Z,
+  Rx,
+  Ry,
+  Rz,
+  Slider,
+  Dial,
+  Wheel,
+  Throttle,
+  Rudder,
+  Accelerator,
+  Brake,
+  Steering,
+  Other { usage_page: u16, usage: u16 },
+}
+
+impl JoystickAxis {
+  fn from_usage(usage_page: u16, usage: u16) -> Self {
+    match (usage_page, usage) {
+      (HumanInterfaceDevice::HID_USAGE_PAGE_GENERIC, usage) => match usage {
+        HumanInterfaceDevice::HID_USAGE_GENERIC_X => Self::X,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_Y => Self::Y,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_Z => Self::Z,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_RX => Self::Rx,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_RY => Self::Ry,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_RZ => Self::Rz,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_SLIDER => Self::Slider,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_DIAL => Self::Dial,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_WHEEL => Self::Wheel,
+        usage => Self::Other { usage_page, usage },
+      },
+      (HumanInterfaceDevice::HID_USAGE_PAGE_SIMULATION, usage) => match usage {
+        HumanInterfaceDevice::HID_USAGE_SIMULATION_THROTTLE => Self::Throttle,
+        HumanInterfaceDevice::HID_USAGE_SIMULATION_RUDDER => Self::Rudder,
+        HumanInterfaceDevice::HID_USAGE_SIMULATION_ACCELLERATOR => Self::Accelerator,
+        HumanInterfaceDevice::HID_USAGE_SIMULATION_BRAKE => Self::Brake,
+        HumanInterfaceDevice::HID_USAGE_SIMULATION_STEERING => Self::Steering,
+        usage => Self::Other { usage_page, usage },
+      },
+      (usage_page, usage) => Self::Other { usage_page, usage },
+    }
+  }
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum HatDirection {
+  Up,
+  UpRight,
+  Right,
+  DownRight,
+  Down,
+  DownLeft,
+  Left,
+  UpLeft,
+}
+
+impl HatDirection {
+  fn from_value(value: i32, logical_min: i32, logical_max: i32) -> Option<Self> {
+    const EIGHT_WAY: [HatDirection; 8] = [
+      HatDirection::Up,
+      HatDirection::UpRight,
+      HatDirection::Right,
+      HatDirection::DownRight,
+      HatDirection::Down,
+      HatDirection::DownLeft,
+      HatDirection::Left,
+      HatDirection::UpLeft,
+    ];
+    const FOUR_WAY: [HatDirection; 4] = [
+      HatDirection::Up,
+      HatDirection::Right,
+      HatDirection::Down,
+      HatDirection::Left,
+    ];
+
+    // values outside the logical range mean the hat is centered
+    let index = usize::try_from(value - logical_min).ok()?;
+    match logical_max - logical_min + 1 {
+      4 => FOUR_WAY.get(index).copied(),
+      _ => EIGHT_WAY.get(index).copied(),
+    }
+  }
+}
+
+/// Register the window for raw input from joysticks, gamepads and other
+/// multi-axis controllers such as wheels and flight sticks.
+pub(crate) fn register_for_raw_input(hwnd: HWND) -> bool {
+  let devices = [
+    HumanInterfaceDevice::HID_USAGE_GENERIC_JOYSTICK,
+    HumanInterfaceDevice::HID_USAGE_GENERIC_GAMEPAD,
+    HumanInterfaceDevice::HID_USAGE_GENERIC_MULTI_AXIS_CONTROLLER,
+  ]
+  .map(|usage| RAWINPUTDEVICE {
+    usUsagePage: HumanInterfaceDevice::HID_USAGE_PAGE_GENERIC,
+    usUsage: usage,
+    dwFlags: Input::RIDEV_DEVNOTIFY,
+    hwndTarget: hwnd,
+  });
+
+  register_raw_input_devices(&devices)
+}
+
+/// Whether a `WM_INPUT` message came from a HID device other than a mouse or
+/// keyboard.
+pub(crate) fn is_hid_input(handle: HRAWINPUT) -> bool {
+  let mut header = RAWINPUTHEADER::default();
+  let mut size = std::mem::size_of::<RAWINPUTHEADER>() as u32;
+  let status = unsafe {
+    GetRawInputData(
+      handle,
+      Input::RID_HEADER,
+      Some(std::ptr::addr_of_mut!(header) as _),
+      &mut size,
+      std::mem::size_of::<RAWINPUTHEADER>() as u32,
+    )
+  };
+  status != u32::MAX && header.dwType == Input::RIM_TYPEHID.0
+}
+
+/// Buffer aligned for the raw input and preparsed data structures, which
+/// don't have a fixed size.
+fn aligned_buffer(bytes: u32) -> Vec<u64> {
+  vec![0u64; (bytes as usize).div_ceil(std::mem::size_of::<u64>())]
+}
+
+#[derive(Default)]
+pub(crate) struct JoystickTracker {
+  devices: HashMap<isize, JoystickDevice>,
+}
+
+impl JoystickTracker {
+  /// Handle `WM_INPUT_DEVICE_CHANGE`, which is also sent for the devices
+  /// already connected when the window registers for raw input.
+  pub fn on_device_change(
+    &mut self,
+    wparam: u32,
+    device: HANDLE,
+  ) -> Option<JoystickEvent> {
+    let id = JoystickId(device.0);
+    match wparam {
+      WindowsAndMessaging::GIDC_ARRIVAL if !self.devices.contains_key(&device.0) => {
+        let (joystick, vendor_id, product_id) = JoystickDevice::new(device)?;
+        self.devices.insert(device.0, joystick);
+        Some(JoystickEvent::Connected {
+          id,
+          vendor_id,
+          product_id,
+        })
+      }
+      WindowsAndMessaging::GIDC_REMOVAL => self
+        .devices
+        .remove(&device.0)
+        .map(|_| JoystickEvent::Disconnected { id }),
+      _ => None,
+    }
+  }
+
+  pub fn on_input(&mut self, handle: HRAWINPUT) -> Vec<JoystickEvent> {
+    let header_size = std::mem::size_of::<RAWINPUTHEADER>() as u32;
+    let mut size = 0;
+    unsafe { GetRawInputData(handle, Input::RID_INPUT, None, &mut size, header_size) };
+    let mut buffer = aligned_buffer(size);
+    let status = unsafe {
+      GetRawInputData(
+        handle,
+        Input::RID_INPUT,
+        Some(buffer.as_mut_ptr() as _),
+        &mut size,
+        header_size,
+      )
+    };
+    if status == u32::MAX || status == 0 {
+      return Vec::new();
+    }
+
+    let raw = unsafe { &*(buffer.as_ptr() as *const RAWINPUT) };
+    let Some(device) = self.devices.get_mut(&raw.header.hDevice.0) else {
+      return Vec::new();
+    };
+    let id = JoystickId(raw.header.hDevice.0);
+    let (report_size, report_count) =
+      unsafe { (raw.data.hid.dwSizeHid as usize, raw.data.hid.dwCount as usize) };
+    let reports = unsafe {
+      std::slice::from_raw_parts(
+        std::ptr::addr_of!(raw.data.hid.bRawData) as *const u8,
+        report_size * report_count,
+      )
+    };
+
+    let mut events = Vec::new();
+    for report in reports.chunks_exact(report_size.max(1)) {
+      device.read_report(id, &mut report.to_vec(), &mut events);
+    }
+    events
+  }
+}
+
+struct JoystickDevice {
+  /// Opaque preparsed data, referenced by [`PHIDP_PREPARSED_DATA`].
+  preparsed: Vec<u64>,
+  button_caps: Vec<HIDP_BUTTON_CAPS>,
+  value_caps: Vec<HIDP_VALUE_CAPS>,
+  pressed: HashSet<u16>,
+  axes: HashMap<(u16, u16, u16), f32>,
+  hats: Vec<Option<HatDirection>>,
+}
+
+impl JoystickDevice {
+  fn new(device: HANDLE) -> Option<(Self, u32, u32)> {
+    let mut info = RID_DEVICE_INFO {
+      cbSize: std::mem::size_of::<RID_DEVICE_INFO>() as u32,
+      ..Default::default()
+    };
+    let mut size = info.cbSize;
+    let status = unsafe {
+      GetRawInputDeviceInfoW(
+        device,
+        Input::RIDI_DEVICEINFO,
+        Some(std::ptr::addr_of_mut!(info) as _),
+        &mut size,
+      )
+    };
+    if status == u32::MAX || info.dwType != Input::RIM_TYPEHID {
+      return None;
+    }
+    let hid = unsafe { info.Anonymous.hid };
+    let is_joystick = hid.usUsagePage == HumanInterfaceDevice::HID_USAGE_PAGE_GENERIC
+      && matches!(
+        hid.usUsage,
+        HumanInterfaceDevice::HID_USAGE_GENERIC_JOYSTICK
+          | HumanInterfaceDevice::HID_USAGE_GENERIC_GAMEPAD
+          | HumanInterfaceDevice::HID_USAGE_GENERIC_MULTI_AXIS_CONTROLLER
+      );
+    if !is_joystick {
+      return None;
+    }
+
+    let mut size = 0;
+    unsafe { GetRawInputDeviceInfoW(device, Input::RIDI_PREPARSEDDATA, None, &mut size) };
+    let mut preparsed = aligned_buffer(size);
+    let status = unsafe {
+      GetRawInputDeviceInfoW(
+        device,
+        Input::RIDI_PREPARSEDDATA,
+        Some(preparsed.as_mut_ptr() as _),
+        &mut size,
+      )
+    };
+    if status == u32::MAX {
+      return None;
+    }
+    let data = PHIDP_PREPARSED_DATA(preparsed.as_ptr() as isize);
+
+    let mut caps = HIDP_CAPS::default();
+    if unsafe { HidP_GetCaps(data, &mut caps) } != HIDP_STATUS_SUCCESS {
+      return None;
+    }
+
+    let mut button_caps =
+      vec![HIDP_BUTTON_CAPS::default(); caps.NumberInputButtonCaps as usize];
+    let mut length = caps.NumberInputButtonCaps;
+    if length > 0
+      && unsafe {
+        HidP_GetButtonCaps(HidP_Input, button_caps.as_mut_ptr(), &mut length, data)
+      } == HIDP_STATUS_SUCCESS
+    {
+      button_caps.truncate(length as usize);
+    } else {
+      button_caps.clear();
+    }
+    button_caps
+      .retain(|cap| cap.UsagePage == HumanInterfaceDevice::HID_USAGE_PAGE_BUTTON);
+    button_caps.dedup_by_key(|cap| cap.LinkCollection);
+
+    let mut value_caps =
+      vec![HIDP_VALUE_CAPS::default(); caps.NumberInputValueCaps as usize];
+    let mut length = caps.NumberInputValueCaps;
+    if length > 0
+      && unsafe {
+        HidP_GetValueCaps(HidP_Input, value_caps.as_mut_ptr(), &mut length, data)
+      } == HIDP_STATUS_SUCCESS
+    {
+      value_caps.truncate(length as usize);
+    } else {
+      value_caps.clear();
+    }
+
+    let hats = value_caps
+      .iter()
+      .filter(|cap| {
+        cap.UsagePage == HumanInterfaceDevice::HID_USAGE_PAGE_GENERIC
+          && unsafe { cap.Anonymous.NotRange.Usage }
+            == HumanInterfaceDevice::HID_USAGE_GENERIC_HATSWITCH
+      })
+      .count();
+
+    let joystick = Self {
+      preparsed,
+      button_caps,
+      value_caps,
+      pressed: HashSet::new(),
+      axes: HashMap::new(),
+      hats: vec![None; hats],
+    };
+    Some((joystick, hid.dwVendorId, hid.dwProductId))
+  }
+
+  fn read_report(
+    &mut self,
+    id: JoystickId,
+    report: &mut [u8],
+    events: &mut Vec<JoystickEvent>,
+  ) {
+    let data = PHIDP_PREPARSED_DATA(self.preparsed.as_ptr() as isize);
+
+    let mut pressed = HashSet::new();
+    for cap in &self.button_caps {
+      let mut length =
+        unsafe { HidP_MaxUsageListLength(HidP_Input, cap.UsagePage, data) };
+      let mut usages = vec![0u16; length as usize];
+      let status = unsafe {
+        HidP_GetUsages(
+          HidP_Input,
+          cap.UsagePage,
+          cap.LinkCollection,
+          usages.as_mut_ptr(),
+          &mut length,
+          data,
+          report,
+        )
+      };
+      if status == HIDP_STATUS_SUCCESS {
+        pressed.extend(&usages[..length as usize]);
+      }
+    }
+    for &button in pressed.difference(&self.pressed) {
+      events.push(JoystickEvent::Button {
+        id,
+        button,
+        state: ButtonState::Pressed,
+      });
+    }
+    for &button in self.pressed.difference(&pressed) {
+      events.push(JoystickEvent::Button {
+        id,
+        button,
+        state: ButtonState::Released,
+      });
+    }
+    self.pressed = pressed;
+
+    let mut hat = 0;
+    for cap in &self.value_caps {
+      let usages = match cap.IsRange.as_bool() {
+        true => unsafe { cap.Anonymous.Range.UsageMin..=cap.Anonymous.Range.UsageMax },
+        false => unsafe { cap.Anonymous.NotRange.Usage..=cap.Anonymous.NotRange.Usage },
+      };
+      for usage in usages {
+        let mut raw_value = 0u32;
+        let status = unsafe {
+          HidP_GetUsageValue(
+            HidP_Input,
+            cap.UsagePage,
+            cap.LinkCollection,
+            usage,
+            &mut raw_value,
+            data,
+            report,
+          )
+        };
+        let is_hat = cap.UsagePage == HumanInterfaceDevice::HID_USAGE_PAGE_GENERIC
+          && usage == HumanInterfaceDevice::HID_USAGE_GENERIC_HATSWITCH;
+        if status != HIDP_STATUS_SUCCESS {
+          hat += is_hat as usize;
+          continue;
+        }
+
+        let value = sign_extend(raw_value, cap.BitSize, cap.LogicalMin);
+        if is_hat {
+          let direction = HatDirection::from_value(value, cap.LogicalMin, cap.LogicalMax);
+          if let Some(previous) = self.hats.get_mut(hat) {
+            if *previous != direction {
+              *previous = direction;
+              events.push(JoystickEvent::Hat { id, hat, direction });
+            }
+          }
+          hat += 1;
+          continue;
+        }
+
+        let range = (cap.LogicalMax - cap.LogicalMin) as f32;
+        if range <= 0.0 {
+          continue;
+        }
+        let value =
+          ((value - cap.LogicalMin) as f32 / range * 2.0 - 1.0).clamp(-1.0, 1.0);
+        let key = (cap.UsagePage, usage, cap.LinkCollection);
+        if self.axes.insert(key, value) != Some(value) {
+          events.push(JoystickEvent::Axis {
+            id,
+            axis: JoystickAxis::from_usage(cap.UsagePage, usage),
+            value,
+          });
+        }
+      }
+    }
+  }
+}
+
+/// Values with a negative logical minimum are reported as two's complement in
+/// `bit_size` bits.
+fn sign_extend(value: u32, bit_size: u16, logical_min: i32) -> i32 {
+  if logical_min >= 0 || bit_size == 0 || bit_size >= 32 {
+    return value as i32;
+  }
+  let shift = 32 - bit_size as u32;
+  ((value << shift) as i32) >> shift
+}
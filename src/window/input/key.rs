@@ -1,14 +1,16 @@
 use windows::Win32::UI::{
   Input::{
-    KeyboardAndMouse::{MapVirtualKeyW, VIRTUAL_KEY},
+    KeyboardAndMouse::{MapVirtualKeyExW, MapVirtualKeyW, ToUnicodeEx, VIRTUAL_KEY},
     *,
   },
   WindowsAndMessaging,
 };
 
+use super::{layout::KeyboardLayout, state::Modifiers};
 use crate::utilities::is_flag_set;
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Key {
   Unknown = 0,
   // ASCII
@@ -160,6 +162,181 @@ pub enum Key {
   Copy,
 }
 
+/// Position of a key on the keyboard, independent of the active layout. Keys
+/// are named after the key at the same position on a US QWERTY keyboard, so
+/// [`Key::W`] is the key above [`Key::S`] even on an AZERTY keyboard, where it
+/// types "z". Use this for bindings that depend on where keys are, such as
+/// movement in games, and the logical [`Key`] for showing or matching what keys
+/// mean.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PhysicalKey {
+  Code(Key),
+  /// A key at an unknown position, containing its scan code.
+  Unidentified(u16),
+}
+
+impl PhysicalKey {
+  /// Identify a key from its set 1 scan code, with `0xE0` in the high byte
+  /// for extended keys.
+  pub fn from_scan_code(scan_code: u16) -> Self {
+    let key = match scan_code {
+      0x0001 => Key::Escape,
+      0x0002 => Key::_1,
+      0x0003 => Key::_2,
+      0x0004 => Key::_3,
+      0x0005 => Key::_4,
+      0x0006 => Key::_5,
+      0x0007 => Key::_6,
+      0x0008 => Key::_7,
+      0x0009 => Key::_8,
+      0x000A => Key::_9,
+      0x000B => Key::_0,
+      0x000C => Key::Minus,
+      0x000D => Key::Equals,
+      0x000E => Key::Backspace,
+      0x000F => Key::Tab,
+      0x0010 => Key::Q,
+      0x0011 => Key::W,
+      0x0012 => Key::E,
+      0x0013 => Key::R,
+      0x0014 => Key::T,
+      0x0015 => Key::Y,
+      0x0016 => Key::U,
+      0x0017 => Key::I,
+      0x0018 => Key::O,
+      0x0019 => Key::P,
+      0x001A => Key::LeftBracket,
+      0x001B => Key::RightBracket,
+      0x001C => Key::Enter,
+      0x001D => Key::LeftControl,
+      0x001E => Key::A,
+      0x001F => Key::S,
+      0x0020 => Key::D,
+      0x0021 => Key::F,
+      0x0022 => Key::G,
+      0x0023 => Key::H,
+      0x0024 => Key::J,
+      0x0025 => Key::K,
+      0x0026 => Key::L,
+      0x0027 => Key::Semicolon,
+      0x0028 => Key::Apostrophe,
+      0x0029 => Key::Accent,
+      0x002A => Key::LeftShift,
+      0x002B => Key::BackSlash,
+      0x002C => Key::Z,
+      0x002D => Key::X,
+      0x002E => Key::C,
+      0x002F => Key::V,
+      0x0030 => Key::B,
+      0x0031 => Key::N,
+      0x0032 => Key::M,
+      0x0033 => Key::Comma,
+      0x0034 => Key::Period,
+      0x0035 => Key::ForwardSlash,
+      0x0036 => Key::RightShift,
+      0x0037 => Key::NumMultiply,
+      0x0038 => Key::LeftAlt,
+      0x0039 => Key::Space,
+      0x003A => Key::CapsLock,
+      0x003B => Key::F1,
+      0x003C => Key::F2,
+      0x003D => Key::F3,
+      0x003E => Key::F4,
+      0x003F => Key::F5,
+      0x0040 => Key::F6,
+      0x0041 => Key::F7,
+      0x0042 => Key::F8,
+      0x0043 => Key::F9,
+      0x0044 => Key::F10,
+      // Pause and NumLock share a scan code, see `Key::from_raw`
+      0x0045 => Key::Pause,
+      0x0046 => Key::ScrollLock,
+      0x0047 => Key::Num7,
+      0x0048 => Key::Num8,
+      0x0049 => Key::Num9,
+      0x004A => Key::NumMinus,
+      0x004B => Key::Num4,
+      0x004C => Key::Num5,
+      0x004D => Key::Num6,
+      0x004E => Key::NumPlus,
+      0x004F => Key::Num1,
+      0x0050 => Key::Num2,
+      0x0051 => Key::Num3,
+      0x0052 => Key::Num0,
+      0x0053 => Key::NumPeriod,
+      0x0056 => Key::OEM102,
+      0x0057 => Key::F11,
+      0x0058 => Key::F12,
+      0x0059 => Key::NumEquals,
+      0x0064 => Key::F13,
+      0x0065 => Key::F14,
+      0x0066 => Key::F15,
+      0x0067 => Key::F16,
+      0x0068 => Key::F17,
+      0x0069 => Key::F18,
+      0x006A => Key::F19,
+      0x006B => Key::F20,
+      0x006C => Key::F21,
+      0x006D => Key::F22,
+      0x006E => Key::F23,
+      0x0070 => Key::Kana,
+      0x0073 => Key::AbntC1,
+      0x0076 => Key::F24,
+      0x0079 => Key::Convert,
+      0x007B => Key::NoConvert,
+      0x007E => Key::AbntC2,
+      0xE010 => Key::MediaPrevTrack,
+      0xE019 => Key::MediaNextTrack,
+      0xE01C => Key::NumEnter,
+      0xE01D => Key::RightControl,
+      0xE020 => Key::VolumeMute,
+      0xE022 => Key::MediaPlayPause,
+      0xE024 => Key::MediaStop,
+      0xE02E => Key::VolumeDown,
+      0xE030 => Key::VolumeUp,
+      0xE032 => Key::WebHome,
+      0xE035 => Key::NumDivide,
+      0xE037 => Key::PrintScreen,
+      0xE038 => Key::RightAlt,
+      0xE045 => Key::NumLock,
+      0xE046 => Key::Pause,
+      0xE047 => Key::Home,
+      0xE048 => Key::Up,
+      0xE049 => Key::PageUp,
+      0xE04B => Key::Left,
+      0xE04D => Key::Right,
+      0xE04F => Key::End,
+      0xE050 => Key::Down,
+      0xE051 => Key::PageDown,
+      0xE052 => Key::Insert,
+      0xE053 => Key::Delete,
+      0xE05B => Key::LeftSuper,
+      0xE05C => Key::RightSuper,
+      0xE05D => Key::Menu,
+      0xE05F => Key::Sleep,
+      0xE065 => Key::WebSearch,
+      0xE066 => Key::WebFavorites,
+      0xE067 => Key::WebRefresh,
+      0xE068 => Key::WebStop,
+      0xE069 => Key::WebForward,
+      0xE06A => Key::WebBack,
+      0xE06C => Key::Mail,
+      0xE06D => Key::MediaSelect,
+      _ => return Self::Unidentified(scan_code),
+    };
+    Self::Code(key)
+  }
+
+  /// The key at this position on a US QWERTY keyboard, if known.
+  pub fn key(&self) -> Option<Key> {
+    match self {
+      Self::Code(key) => Some(*key),
+      Self::Unidentified(_) => None,
+    }
+  }
+}
+
 impl From<VIRTUAL_KEY> for Key {
   fn from(value: VIRTUAL_KEY) -> Self {
     match value {
@@ -464,6 +641,67 @@ impl From<Key> for VIRTUAL_KEY {
 }
 
 impl Key {
+  /// Text the key produces with the given modifiers held under the keyboard
+  /// layout of the calling thread. See [`Key::to_text_with_layout`].
+  pub fn to_text(self, modifiers: Modifiers) -> Option<String> {
+    self.to_text_with_layout(modifiers, KeyboardLayout::current())
+  }
+
+  /// Text the key produces with the given modifiers held under `layout`, such
+  /// as `"é"` for [`Key::_2`] on a French AZERTY layout. Dead keys return the
+  /// accent they would apply. Returns `None` for keys that don't produce
+  /// printable text. Lock keys such as Caps Lock are ignored.
+  pub fn to_text_with_layout(
+    self,
+    modifiers: Modifiers,
+    layout: KeyboardLayout,
+  ) -> Option<String> {
+    let vk = VIRTUAL_KEY::from(self);
+    if vk.0 == 0 {
+      return None;
+    }
+    let scancode = unsafe {
+      MapVirtualKeyExW(vk.0 as u32, KeyboardAndMouse::MAPVK_VK_TO_VSC, layout.hkl())
+    };
+
+    let mut key_state = [0u8; 256];
+    let pressed = [
+      (KeyboardAndMouse::VK_SHIFT, modifiers.shift.is_pressed()),
+      (KeyboardAndMouse::VK_CONTROL, modifiers.ctrl.is_pressed()),
+      (KeyboardAndMouse::VK_MENU, modifiers.alt.is_pressed()),
+    ];
+    for (modifier, is_pressed) in pressed {
+      if is_pressed {
+        key_state[modifier.0 as usize] = 0x80;
+      }
+    }
+
+    // bit 2 leaves the dead key state of the keyboard untouched
+    const NO_STATE_CHANGE: u32 = 1 << 2;
+    let mut buffer = [0u16; 8];
+    let len = unsafe {
+      ToUnicodeEx(
+        vk.0 as u32,
+        scancode,
+        &key_state,
+        &mut buffer,
+        NO_STATE_CHANGE,
+        layout.hkl(),
+      )
+    };
+    // negative for dead keys, which still write their spacing character
+    let len = match len {
+      0 => return None,
+      len => (len.unsigned_abs() as usize).min(buffer.len()),
+    };
+
+    let text = String::from_utf16_lossy(&buffer[..len]);
+    if text.chars().all(char::is_control) {
+      return None;
+    }
+    Some(text)
+  }
+
   /*
    Stolen from winit, under the Apache-2.0 license. See winit's license for more details.
   */
@@ -0,0 +1,39 @@
+use windows::Win32::UI::{Input::KeyboardAndMouse::GetKeyboardLayout, TextServices::HKL};
+
+use crate::utilities::{hi_word, lo_word};
+
+/// Keyboard layout (input locale) active for a window's thread.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct KeyboardLayout(isize);
+
+impl KeyboardLayout {
+  /// Layout active for the calling thread.
+  pub(crate) fn current() -> Self {
+    Self::from_hkl(unsafe { GetKeyboardLayout(0) })
+  }
+
+  pub(crate) fn from_hkl(hkl: HKL) -> Self {
+    Self(hkl.0)
+  }
+
+  pub(crate) fn hkl(&self) -> HKL {
+    HKL(self.0)
+  }
+
+  /// Raw `HKL` value.
+  pub fn handle(&self) -> isize {
+    self.0
+  }
+
+  /// Language identifier (`LANGID`) of the input language, e.g. `0x0409` for
+  /// English (United States).
+  pub fn language_id(&self) -> u16 {
+    lo_word(self.0 as u32)
+  }
+
+  /// Identifier of the physical layout, which differs from
+  /// [`KeyboardLayout::language_id`] for alternate layouts such as Dvorak.
+  pub fn device_id(&self) -> u16 {
+    hi_word(self.0 as u32)
+  }
+}
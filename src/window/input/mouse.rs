@@ -1,8 +1,15 @@
-use windows::Win32::UI::WindowsAndMessaging;
+use std::time::Duration;
+
+use windows::Win32::UI::{
+  Input::KeyboardAndMouse::GetDoubleClickTime,
+  WindowsAndMessaging::{self, GetSystemMetrics},
+};
 
 use super::state::ButtonState;
+use crate::window::data::{PhysicalPosition, PhysicalSize};
 
 #[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(u16)]
 pub enum MouseButton {
   Unknown = 0,
@@ -26,6 +33,63 @@ impl MouseButton {
   }
 }
 
+/// Longest time between two clicks for them to count as a double click, as
+/// set by the user.
+pub fn double_click_time() -> Duration {
+  Duration::from_millis(unsafe { GetDoubleClickTime() } as u64)
+}
+
+/// Area around a click which the next click has to land in to count as a
+/// double click, centered on the first click.
+pub fn double_click_area() -> PhysicalSize {
+  let width = unsafe { GetSystemMetrics(WindowsAndMessaging::SM_CXDOUBLECLK) };
+  let height = unsafe { GetSystemMetrics(WindowsAndMessaging::SM_CYDOUBLECLK) };
+  PhysicalSize::new(width.max(0) as u32, height.max(0) as u32)
+}
+
+/// Counts clicks of the same button in a row, each within the double-click
+/// time and area of the one before, which Windows itself stops at two.
+#[derive(Debug, Default)]
+pub(crate) struct ClickTracker {
+  /// Button, message time, and position of the last press.
+  last: Option<(MouseButton, i32, PhysicalPosition)>,
+  count: u32,
+}
+
+impl ClickTracker {
+  /// Record a press at `time`, from `GetMessageTime`, returning how many
+  /// clicks in a row it makes.
+  pub fn press(
+    &mut self,
+    button: MouseButton,
+    position: PhysicalPosition,
+    time: i32,
+  ) -> u32 {
+    let area = double_click_area();
+    let in_row = self
+      .last
+      .is_some_and(|(last_button, last_time, last_position)| {
+        // message times wrap around
+        let elapsed = time.wrapping_sub(last_time) as u32;
+        last_button == button
+          && elapsed as u64 <= double_click_time().as_millis() as u64
+          && (position.x - last_position.x).unsigned_abs() <= area.width / 2
+          && (position.y - last_position.y).unsigned_abs() <= area.height / 2
+      });
+    self.count = if in_row { self.count + 1 } else { 1 };
+    self.last = Some((button, time, position));
+    self.count
+  }
+
+  /// Clicks in a row made by the last press of `button`, for its release.
+  pub fn release(&self, button: MouseButton) -> u32 {
+    match self.last {
+      Some((last_button, ..)) if last_button == button => self.count,
+      _ => 1,
+    }
+  }
+}
+
 pub(crate) fn mouse_button_states(flags: u16) -> [Option<ButtonState>; 5] {
   [
     ButtonState::from_flag(
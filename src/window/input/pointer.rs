@@ -0,0 +1,321 @@
+use std::{collections::HashMap, sync::Arc};
+
+use windows::Win32::{
+  Devices::HumanInterfaceDevice,
+  Foundation::{HANDLE, HWND},
+  Graphics::Gdi::ScreenToClient,
+  UI::{
+    Controls::{self, POINTER_DEVICE_INFO},
+    Input::Pointer::{
+      self,
+      GetPointerDevice,
+      GetPointerDeviceProperties,
+      GetPointerInfo,
+      GetPointerPenInfo,
+      GetPointerTouchInfo,
+      POINTER_INFO,
+    },
+    WindowsAndMessaging,
+  },
+};
+
+use crate::window::data::{PhysicalPosition, PhysicalSize};
+
+// missing from the digitizer usages in `HumanInterfaceDevice`
+const HID_USAGE_DIGITIZER_WIDTH: u16 = 0x48;
+const HID_USAGE_DIGITIZER_HEIGHT: u16 = 0x49;
+
+/// Pressure reported by `GetPointerPenInfo` and `GetPointerTouchInfo` is
+/// already normalized to this range, whatever the device's own range is.
+const MAX_PRESSURE: f32 = 1024.0;
+
+/// Pen or touch contact. Sent with
+/// [`Message::Pointer`](crate::window::message::Message::Pointer).
+///
+/// Windows still turns these into mouse messages for apps that only handle the
+/// mouse, so both are sent for the same contact.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerEvent {
+  /// Identifies the contact from [`PointerPhase::Down`] until it is lifted.
+  pub id: u32,
+  pub phase: PointerPhase,
+  /// Position in the client area.
+  pub position: PhysicalPosition,
+  /// `false` while a pen hovers above the screen.
+  pub in_contact: bool,
+  /// `true` for the first finger of a multi-touch interaction.
+  pub primary: bool,
+  pub kind: PointerKind,
+  /// Capabilities of the digitizer the contact came from.
+  pub device: Arc<PointerDevice>,
+}
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerPhase {
+  Down,
+  Moved,
+  Up,
+  /// The contact ended without completing, such as when a palm is rejected.
+  Cancelled,
+}
+
+/// Per-contact values. Each is `None` when the device does not report it.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerKind {
+  Pen {
+    /// From `0.0` to `1.0`.
+    pressure: Option<f32>,
+    /// Tilt towards positive x and y in degrees, from `-90` to `90`.
+    tilt: Option<(i32, i32)>,
+    /// Clockwise rotation in degrees, from `0` to `359`.
+    rotation: Option<u32>,
+    eraser: bool,
+    barrel_button: bool,
+  },
+  Touch {
+    /// From `0.0` to `1.0`.
+    pressure: Option<f32>,
+    /// Size of the contact area.
+    contact_size: Option<PhysicalSize>,
+    /// Clockwise orientation of the contact area in degrees, from `0` to `359`.
+    orientation: Option<u32>,
+  },
+}
+
+/// Handle of a pointer device, stable for as long as it stays connected.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+pub struct PointerDeviceId(isize);
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PointerDeviceKind {
+  IntegratedPen,
+  ExternalPen,
+  Touch,
+  TouchPad,
+  Unknown,
+}
+
+/// Range of a value as reported by the digitizer, before Windows normalizes
+/// it.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PointerPropertyRange {
+  pub logical_min: i32,
+  pub logical_max: i32,
+  pub physical_min: i32,
+  pub physical_max: i32,
+  /// HID unit code of the physical range.
+  pub unit: u32,
+  /// HID unit exponent of the physical range.
+  pub unit_exponent: u32,
+}
+
+/// Capabilities of a pen or touch digitizer, queried once per device.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct PointerDevice {
+  pub id: PointerDeviceId,
+  pub kind: PointerDeviceKind,
+  pub product: String,
+  pub max_contacts: u16,
+  pub pressure: Option<PointerPropertyRange>,
+  pub tilt_x: Option<PointerPropertyRange>,
+  pub tilt_y: Option<PointerPropertyRange>,
+  pub contact_width: Option<PointerPropertyRange>,
+  pub contact_height: Option<PointerPropertyRange>,
+}
+
+impl PointerDevice {
+  /// Number of distinct pressure levels the device reports, such as `4096`
+  /// for many pens.
+  pub fn pressure_levels(&self) -> Option<u32> {
+    self
+      .pressure
+      .map(|range| range.logical_max.abs_diff(range.logical_min) + 1)
+  }
+
+  pub fn supports_tilt(&self) -> bool {
+    self.tilt_x.is_some() && self.tilt_y.is_some()
+  }
+
+  pub fn supports_contact_size(&self) -> bool {
+    self.contact_width.is_some() && self.contact_height.is_some()
+  }
+
+  fn query(handle: HANDLE) -> windows::core::Result<Self> {
+    let mut info = POINTER_DEVICE_INFO::default();
+    unsafe { GetPointerDevice(handle, &mut info) }?;
+
+    let kind = match info.pointerDeviceType {
+      Controls::POINTER_DEVICE_TYPE_INTEGRATED_PEN => PointerDeviceKind::IntegratedPen,
+      Controls::POINTER_DEVICE_TYPE_EXTERNAL_PEN => PointerDeviceKind::ExternalPen,
+      Controls::POINTER_DEVICE_TYPE_TOUCH => PointerDeviceKind::Touch,
+      Controls::POINTER_DEVICE_TYPE_TOUCH_PAD => PointerDeviceKind::TouchPad,
+      _ => PointerDeviceKind::Unknown,
+    };
+    let product_len = info
+      .productString
+      .iter()
+      .position(|&c| c == 0)
+      .unwrap_or(info.productString.len());
+
+    let mut count = 0;
+    unsafe { GetPointerDeviceProperties(handle, &mut count, None) }?;
+    let mut properties = vec![Default::default(); count as usize];
+    unsafe {
+      GetPointerDeviceProperties(handle, &mut count, Some(properties.as_mut_ptr()))
+    }?;
+    properties.truncate(count as usize);
+
+    let range = |usage: u16| {
+      properties
+        .iter()
+        .find(|property| {
+          property.usagePageId == HumanInterfaceDevice::HID_USAGE_PAGE_DIGITIZER
+            && property.usageId == usage
+        })
+        .map(|property| PointerPropertyRange {
+          logical_min: property.logicalMin,
+          logical_max: property.logicalMax,
+          physical_min: property.physicalMin,
+          physical_max: property.physicalMax,
+          unit: property.unit,
+          unit_exponent: property.unitExponent,
+        })
+    };
+
+    Ok(Self {
+      id: PointerDeviceId(handle.0),
+      kind,
+      product: String::from_utf16_lossy(&info.productString[..product_len]),
+      max_contacts: info.maxActiveContacts,
+      pressure: range(HumanInterfaceDevice::HID_USAGE_DIGITIZER_TIP_PRESSURE),
+      tilt_x: range(HumanInterfaceDevice::HID_USAGE_DIGITIZER_X_TILT),
+      tilt_y: range(HumanInterfaceDevice::HID_USAGE_DIGITIZER_Y_TILT),
+      contact_width: range(HID_USAGE_DIGITIZER_WIDTH),
+      contact_height: range(HID_USAGE_DIGITIZER_HEIGHT),
+    })
+  }
+}
+
+/// Ask for `WM_POINTERDEVICECHANGE` so cached devices can be dropped when
+/// they are reconfigured or removed.
+pub(crate) fn register_device_notifications(hwnd: HWND) {
+  if let Err(e) = unsafe { Controls::RegisterPointerDeviceNotifications(hwnd, false) } {
+    tracing::error!("{e}");
+  }
+}
+
+/// Devices seen by a window, so their properties are only queried once.
+#[derive(Debug, Default)]
+pub(crate) struct PointerDevices {
+  devices: HashMap<isize, Arc<PointerDevice>>,
+}
+
+impl PointerDevices {
+  pub fn clear(&mut self) {
+    self.devices.clear();
+  }
+
+  fn get(&mut self, handle: HANDLE) -> Option<Arc<PointerDevice>> {
+    if let Some(device) = self.devices.get(&handle.0) {
+      return Some(device.clone());
+    }
+    match PointerDevice::query(handle) {
+      Ok(device) => {
+        let device = Arc::new(device);
+        self.devices.insert(handle.0, device.clone());
+        Some(device)
+      }
+      Err(e) => {
+        tracing::error!("{e}");
+        None
+      }
+    }
+  }
+
+  /// Read the pen or touch contact behind a `WM_POINTER*` message. `None` for
+  /// other pointer types.
+  pub fn read(&mut self, hwnd: HWND, msg: u32, pointer_id: u32) -> Option<PointerEvent> {
+    let (info, kind) = match read_kind(pointer_id) {
+      Ok(Some(pointer)) => pointer,
+      Ok(None) => return None,
+      Err(e) => {
+        tracing::error!("{e}");
+        return None;
+      }
+    };
+
+    let phase = match msg {
+      WindowsAndMessaging::WM_POINTERDOWN => PointerPhase::Down,
+      WindowsAndMessaging::WM_POINTERUP
+        if info.pointerFlags.contains(Pointer::POINTER_FLAG_CANCELED) =>
+      {
+        PointerPhase::Cancelled
+      }
+      WindowsAndMessaging::WM_POINTERUP => PointerPhase::Up,
+      _ => PointerPhase::Moved,
+    };
+
+    let mut point = info.ptPixelLocation;
+    let _ = unsafe { ScreenToClient(hwnd, &mut point) };
+
+    Some(PointerEvent {
+      id: pointer_id,
+      phase,
+      position: PhysicalPosition::new(point.x, point.y),
+      in_contact: info.pointerFlags.contains(Pointer::POINTER_FLAG_INCONTACT),
+      primary: info.pointerFlags.contains(Pointer::POINTER_FLAG_PRIMARY),
+      kind,
+      device: self.get(info.sourceDevice)?,
+    })
+  }
+}
+
+fn read_kind(
+  pointer_id: u32,
+) -> windows::core::Result<Option<(POINTER_INFO, PointerKind)>> {
+  let mut info = POINTER_INFO::default();
+  unsafe { GetPointerInfo(pointer_id, &mut info) }?;
+
+  match info.pointerType {
+    WindowsAndMessaging::PT_PEN => {
+      let mut pen = Pointer::POINTER_PEN_INFO::default();
+      unsafe { GetPointerPenInfo(pointer_id, &mut pen) }?;
+      let has = |mask| pen.penMask & mask != 0;
+      let kind = PointerKind::Pen {
+        pressure: has(WindowsAndMessaging::PEN_MASK_PRESSURE)
+          .then(|| pen.pressure as f32 / MAX_PRESSURE),
+        tilt: (has(WindowsAndMessaging::PEN_MASK_TILT_X)
+          || has(WindowsAndMessaging::PEN_MASK_TILT_Y))
+        .then_some((pen.tiltX, pen.tiltY)),
+        rotation: has(WindowsAndMessaging::PEN_MASK_ROTATION).then_some(pen.rotation),
+        eraser: pen.penFlags & WindowsAndMessaging::PEN_FLAG_ERASER != 0,
+        barrel_button: pen.penFlags & WindowsAndMessaging::PEN_FLAG_BARREL != 0,
+      };
+      Ok(Some((pen.pointerInfo, kind)))
+    }
+    WindowsAndMessaging::PT_TOUCH => {
+      let mut touch = Pointer::POINTER_TOUCH_INFO::default();
+      unsafe { GetPointerTouchInfo(pointer_id, &mut touch) }?;
+      let has = |mask| touch.touchMask & mask != 0;
+      let contact = touch.rcContact;
+      let kind = PointerKind::Touch {
+        pressure: has(WindowsAndMessaging::TOUCH_MASK_PRESSURE)
+          .then(|| touch.pressure as f32 / MAX_PRESSURE),
+        contact_size: has(WindowsAndMessaging::TOUCH_MASK_CONTACTAREA).then(|| {
+          PhysicalSize::new(
+            (contact.right - contact.left) as u32,
+            (contact.bottom - contact.top) as u32,
+          )
+        }),
+        orientation: has(WindowsAndMessaging::TOUCH_MASK_ORIENTATION)
+          .then_some(touch.orientation),
+      };
+      Ok(Some((touch.pointerInfo, kind)))
+    }
+    _ => Ok(None),
+  }
+}
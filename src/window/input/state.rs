@@ -1,6 +1,7 @@
 use crate::utilities::is_flag_set;
 
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum ButtonState {
   #[default]
   Released,
@@ -23,7 +24,26 @@ impl ButtonState {
   }
 }
 
+/// Snapshot of the modifier keys.
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Modifiers {
+  pub shift: ButtonState,
+  pub ctrl: ButtonState,
+  pub alt: ButtonState,
+  /// Either Windows key.
+  pub win: ButtonState,
+}
+
+impl Modifiers {
+  /// Returns `true` if no modifier is pressed.
+  pub fn is_empty(&self) -> bool {
+    *self == Self::default()
+  }
+}
+
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum KeyState {
   #[default]
   Released,
@@ -38,7 +58,25 @@ impl KeyState {
   }
 }
 
+/// Which auto-repeat [`KeyState::Held`] messages are sent while a key is held
+/// down. The window's key state is tracked either way. The delay and rate of
+/// repeats come from the system, see
+/// [`key_repeat_delay`](crate::utilities::key_repeat_delay) and
+/// [`key_repeat_rate`](crate::utilities::key_repeat_rate).
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeyRepeat {
+  /// Every repeat is sent.
+  #[default]
+  Enabled,
+  /// Only the first repeat of each press is sent, marking the key as held.
+  Collapsed,
+  /// No repeats are sent, only presses and releases.
+  Disabled,
+}
+
 #[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RawKeyState {
   #[default]
   Released,
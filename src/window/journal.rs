@@ -0,0 +1,124 @@
+use std::{sync::Arc, time::Duration};
+
+use windows::Win32::{Foundation::HWND, UI::WindowsAndMessaging::SetTimer};
+
+use super::{
+  data::{Backdrop, Theme, Visibility},
+  placement::WindowPlacement,
+  Window,
+};
+
+/// Timer used to debounce journal writes on the window thread.
+pub(crate) const JOURNAL_TIMER_ID: usize = 0x6a6e;
+
+/// Snapshot of the window's layout and key settings, handed to a
+/// [`JournalSink`] so the app can restore the window after a crash.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct JournalEntry {
+  pub placement: WindowPlacement,
+  pub title: String,
+  pub theme: Theme,
+  pub backdrop: Backdrop,
+  pub visibility: Visibility,
+  pub decorations: Visibility,
+}
+
+impl JournalEntry {
+  /// Apply the saved placement and settings to a window.
+  pub fn restore(&self, window: &Window) {
+    window.set_title(&self.title);
+    window.set_theme(self.theme);
+    window.set_system_backdrop(self.backdrop);
+    window.set_decorations(self.decorations);
+    window.set_placement(&self.placement);
+    window.set_visibility(self.visibility);
+  }
+}
+
+/// Receives [`JournalEntry`]s while journaling is enabled with
+/// [`WindowBuilder::with_journal`](crate::WindowBuilder::with_journal).
+///
+/// Entries are written after the window has stopped moving, resizing, or
+/// changing its title, visibility, or decorations for the debounce interval,
+/// so a drag produces a single write once it settles. Entries identical to
+/// the last one written are skipped.
+///
+/// Sinks are called on the window thread, and must not block on the window's
+/// iterator.
+///
+/// Closures with the same signature as [`JournalSink::write`] implement this
+/// trait.
+pub trait JournalSink: Send + Sync {
+  fn write(&self, entry: &JournalEntry);
+}
+
+impl<F> JournalSink for F
+where
+  F: Fn(&JournalEntry) + Send + Sync,
+{
+  fn write(&self, entry: &JournalEntry) {
+    self(entry)
+  }
+}
+
+impl std::fmt::Debug for dyn JournalSink {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("JournalSink")
+  }
+}
+
+pub(crate) struct Journal {
+  sink: Arc<dyn JournalSink>,
+  debounce: Duration,
+  last: Option<JournalEntry>,
+}
+
+impl Journal {
+  pub fn new(sink: Arc<dyn JournalSink>, debounce: Duration) -> Self {
+    Self {
+      sink,
+      debounce,
+      last: None,
+    }
+  }
+
+  /// (Re)start the debounce timer. Must be called on the window thread.
+  pub fn schedule(&self, hwnd: HWND) {
+    let elapse = self.debounce.as_millis().clamp(1, u32::MAX as u128) as u32;
+    if unsafe { SetTimer(hwnd, JOURNAL_TIMER_ID, elapse, None) } == 0 {
+      tracing::error!("{}", windows::core::Error::from_win32());
+    }
+  }
+}
+
+/// Write the window's current state to its journal, if it has one and the
+/// state changed since the last write.
+pub(crate) fn write(window: &Window) {
+  if window.0.data.lock().unwrap().journal.is_none() {
+    return;
+  }
+
+  // gather outside the lock, most getters take it themselves
+  let entry = JournalEntry {
+    placement: window.placement(),
+    title: window.title(),
+    theme: window.theme(),
+    backdrop: window.system_backdrop(),
+    visibility: window.visibility(),
+    decorations: window.decorations(),
+  };
+
+  let sink = {
+    let mut data = window.0.data.lock().unwrap();
+    let Some(journal) = data.journal.as_mut() else {
+      return;
+    };
+    if journal.last.as_ref() == Some(&entry) {
+      return;
+    }
+    journal.last = Some(entry.clone());
+    journal.sink.clone()
+  };
+  sink.write(&entry);
+}
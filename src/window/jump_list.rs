@@ -0,0 +1,324 @@
+use std::path::{Path, PathBuf};
+
+use windows::{
+  core::{w, Interface, HSTRING, PROPVARIANT, PWSTR},
+  Win32::{
+    Foundation::{CloseHandle, BOOL, HANDLE, HWND, LPARAM, WPARAM},
+    Storage::EnhancedStorage::PKEY_Title,
+    System::{
+      Com::{CoCreateInstance, CLSCTX_ALL, COINIT_APARTMENTTHREADED},
+      DataExchange::COPYDATASTRUCT,
+      Threading::{
+        OpenProcess,
+        QueryFullProcessImageNameW,
+        PROCESS_NAME_WIN32,
+        PROCESS_QUERY_LIMITED_INFORMATION,
+      },
+    },
+    UI::{
+      Shell::{
+        Common::{IObjectArray, IObjectCollection},
+        DestinationList,
+        EnumerableObjectCollection,
+        ICustomDestinationList,
+        IShellLinkW,
+        PropertiesSystem::IPropertyStore,
+        ShellLink,
+        KDC_FREQUENT,
+        KDC_RECENT,
+      },
+      WindowsAndMessaging::{
+        self,
+        AllowSetForegroundWindow,
+        EnumWindows,
+        GetPropW,
+        GetWindowThreadProcessId,
+        SendMessageW,
+        SetPropW,
+      },
+    },
+  },
+};
+
+use crate::{error::WindowError, utilities::ComGuard};
+
+/*
+  Clicking a task in the jump list starts the app again with the task's id as arguments,
+  even when it's already running. The new process finds the id with `launched_task`. To
+  hand it over to the instance already running instead, it calls
+  `forward_launched_task`, which sends it to the window showing the jump list with
+  `WM_COPYDATA`, where it's reported with `Message::JumpTask`.
+*/
+
+/// Argument the app is started with when a task is clicked, followed by the
+/// task's id.
+const TASK_ARGUMENT: &str = "--jump-task";
+/// `dwData` of the `WM_COPYDATA` forwarding a task to a running instance.
+pub(crate) const COPYDATA_TASK: usize = 0x7769_7400;
+/// Window property marking the window which set the jump list.
+const TARGET_PROPERTY: windows::core::PCWSTR = w!("witer.jump_list");
+
+/// Entry in the tasks section of a [`JumpList`].
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct JumpTask {
+  id: String,
+  title: String,
+  description: Option<String>,
+  icon: Option<(PathBuf, i32)>,
+}
+
+impl JumpTask {
+  /// Create a task shown as `title`, reported with `id` once clicked.
+  pub fn new(id: impl Into<String>, title: impl Into<String>) -> Self {
+    Self {
+      id: id.into(),
+      title: title.into(),
+      description: None,
+      icon: None,
+    }
+  }
+
+  /// Tooltip shown when hovering the task.
+  pub fn with_description(mut self, description: impl Into<String>) -> Self {
+    self.description = Some(description.into());
+    self
+  }
+
+  /// Show the icon at `index` in the `.exe`, `.dll`, or `.ico` at `path`.
+  /// Without an icon, the app's icon is shown.
+  pub fn with_icon(mut self, path: impl AsRef<Path>, index: i32) -> Self {
+    self.icon = Some((path.as_ref().to_owned(), index));
+    self
+  }
+
+  pub fn id(&self) -> &str {
+    &self.id
+  }
+
+  pub fn title(&self) -> &str {
+    &self.title
+  }
+}
+
+/// Contents of the menu shown when right-clicking the app's taskbar button,
+/// set with [`Window::set_jump_list`](crate::Window::set_jump_list).
+#[derive(Debug, Default, Clone, PartialEq, Eq, Hash)]
+pub struct JumpList {
+  tasks: Vec<JumpTask>,
+  recent: bool,
+  frequent: bool,
+}
+
+impl JumpList {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  /// Add a task, such as "New document", shown in the order added.
+  pub fn with_task(mut self, task: JumpTask) -> Self {
+    self.tasks.push(task);
+    self
+  }
+
+  /// Show the files the app recently opened. Only files of a type the app is
+  /// registered to open, or added with
+  /// [`add_to_recent_docs`](crate::add_to_recent_docs), are listed.
+  pub fn with_recent(mut self, recent: bool) -> Self {
+    self.recent = recent;
+    self
+  }
+
+  /// Show the files the app opens most often, see [`JumpList::with_recent`].
+  pub fn with_frequent(mut self, frequent: bool) -> Self {
+    self.frequent = frequent;
+    self
+  }
+
+  pub fn tasks(&self) -> &[JumpTask] {
+    &self.tasks
+  }
+
+  /// Replace the app's jump list with this one. Runs on a thread of its own,
+  /// so the caller's COM apartment is left alone.
+  pub(crate) fn apply(&self) -> Result<(), WindowError> {
+    std::thread::scope(|scope| {
+      std::thread::Builder::new()
+        .name("jump list".to_owned())
+        .spawn_scoped(scope, || self.commit())?
+        .join()
+        .unwrap_or_else(|e| std::panic::resume_unwind(e))
+    })
+  }
+
+  fn commit(&self) -> Result<(), WindowError> {
+    let _com = ComGuard::new(COINIT_APARTMENTTHREADED)?;
+
+    let list: ICustomDestinationList =
+      unsafe { CoCreateInstance(&DestinationList, None, CLSCTX_ALL) }?;
+    let mut max_slots = 0;
+    let _removed: IObjectArray = unsafe { list.BeginList(&mut max_slots) }?;
+
+    let result = self.fill(&list);
+    match result {
+      Ok(()) => unsafe { list.CommitList() }?,
+      Err(_) => unsafe { list.AbortList() }?,
+    }
+    result
+  }
+
+  fn fill(&self, list: &ICustomDestinationList) -> Result<(), WindowError> {
+    if self.frequent {
+      unsafe { list.AppendKnownCategory(KDC_FREQUENT) }?;
+    }
+    if self.recent {
+      unsafe { list.AppendKnownCategory(KDC_RECENT) }?;
+    }
+    if self.tasks.is_empty() {
+      return Ok(());
+    }
+
+    let exe = std::env::current_exe()?;
+    let collection: IObjectCollection =
+      unsafe { CoCreateInstance(&EnumerableObjectCollection, None, CLSCTX_ALL) }?;
+    for task in &self.tasks {
+      let link = task.shell_link(&exe)?;
+      unsafe { collection.AddObject(&link) }?;
+    }
+    unsafe { list.AddUserTasks(&collection.cast::<IObjectArray>()?) }?;
+    Ok(())
+  }
+}
+
+impl JumpTask {
+  fn shell_link(&self, exe: &Path) -> Result<IShellLinkW, WindowError> {
+    let link: IShellLinkW = unsafe { CoCreateInstance(&ShellLink, None, CLSCTX_ALL) }?;
+    unsafe {
+      link.SetPath(&HSTRING::from(exe.to_string_lossy().as_ref()))?;
+      link.SetArguments(&HSTRING::from(format!("{TASK_ARGUMENT} \"{}\"", self.id)))?;
+      if let Some(description) = &self.description {
+        link.SetDescription(&HSTRING::from(description))?;
+      }
+      if let Some((path, index)) = &self.icon {
+        link.SetIconLocation(&HSTRING::from(path.to_string_lossy().as_ref()), *index)?;
+      }
+    }
+
+    // tasks show their title rather than the link's name
+    let properties: IPropertyStore = link.cast()?;
+    unsafe {
+      properties.SetValue(&PKEY_Title, &PROPVARIANT::from(self.title.as_str()))?;
+      properties.Commit()?;
+    }
+    Ok(link)
+  }
+}
+
+/// Mark `hwnd` as the window tasks are forwarded to by
+/// [`forward_launched_task`].
+pub(crate) fn set_target(hwnd: HWND) {
+  if let Err(e) = unsafe { SetPropW(hwnd, TARGET_PROPERTY, HANDLE(1)) } {
+    tracing::error!("{e}");
+  }
+}
+
+/// Id of the jump list task this process was started from, if any.
+pub fn launched_task() -> Option<String> {
+  let mut args = std::env::args().skip(1);
+  args.find(|arg| arg == TASK_ARGUMENT)?;
+  args.next()
+}
+
+/// Hand the task this process was started from over to another instance of
+/// the app which set the jump list and is still running, reported there with
+/// [`Message::JumpTask`](crate::Message::JumpTask). Returns whether it was,
+/// after which this process can usually exit. Call it before creating any
+/// windows.
+pub fn forward_launched_task() -> bool {
+  let Some(task) = launched_task() else {
+    return false;
+  };
+  let Some(target) = find_target() else {
+    return false;
+  };
+
+  let data = COPYDATASTRUCT {
+    dwData: COPYDATA_TASK,
+    cbData: task.len() as u32,
+    lpData: task.as_ptr() as *mut _,
+  };
+  let mut process_id = 0;
+  unsafe { GetWindowThreadProcessId(target, Some(&mut process_id)) };
+  // lets the running instance bring itself to the foreground
+  let _ = unsafe { AllowSetForegroundWindow(process_id) };
+  let handled = unsafe {
+    SendMessageW(
+      target,
+      WindowsAndMessaging::WM_COPYDATA,
+      WPARAM(0),
+      LPARAM(&data as *const _ as isize),
+    )
+  };
+  handled.0 != 0
+}
+
+/// Task id carried by a `WM_COPYDATA` sent by [`forward_launched_task`].
+pub(crate) fn read_forwarded_task(lparam: LPARAM) -> Option<String> {
+  let data = unsafe { (lparam.0 as *const COPYDATASTRUCT).as_ref() }?;
+  if data.dwData != COPYDATA_TASK || data.lpData.is_null() {
+    return None;
+  }
+  let bytes =
+    unsafe { std::slice::from_raw_parts(data.lpData.cast::<u8>(), data.cbData as usize) };
+  String::from_utf8(bytes.to_vec()).ok()
+}
+
+/// Window of another process running the same executable which set the jump
+/// list.
+fn find_target() -> Option<HWND> {
+  struct Search {
+    exe: PathBuf,
+    found: Option<HWND>,
+  }
+
+  unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+    let search = unsafe { &mut *(lparam.0 as *mut Search) };
+    if unsafe { GetPropW(hwnd, TARGET_PROPERTY) }.0 == 0 {
+      return true.into();
+    }
+    let mut process_id = 0;
+    unsafe { GetWindowThreadProcessId(hwnd, Some(&mut process_id)) };
+    if process_id != std::process::id()
+      && process_exe(process_id) == Some(search.exe.clone())
+    {
+      search.found = Some(hwnd);
+      return false.into();
+    }
+    true.into()
+  }
+
+  let mut search = Search {
+    exe: std::env::current_exe().ok()?,
+    found: None,
+  };
+  // stopping early reports an error
+  let _ = unsafe { EnumWindows(Some(visit), LPARAM(&mut search as *mut _ as isize)) };
+  search.found
+}
+
+fn process_exe(process_id: u32) -> Option<PathBuf> {
+  let process =
+    unsafe { OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id) }.ok()?;
+  let mut buffer = [0u16; 1024];
+  let mut len = buffer.len() as u32;
+  let result = unsafe {
+    QueryFullProcessImageNameW(
+      process,
+      PROCESS_NAME_WIN32,
+      PWSTR(buffer.as_mut_ptr()),
+      &mut len,
+    )
+  };
+  let _ = unsafe { CloseHandle(process) };
+  result.ok()?;
+  Some(PathBuf::from(String::from_utf16_lossy(&buffer[..len as usize])))
+}
@@ -0,0 +1,130 @@
+use windows::{
+  core::{HSTRING, PWSTR},
+  Win32::{
+    Foundation::HWND,
+    UI::WindowsAndMessaging::{
+      self,
+      CheckMenuItem,
+      DeleteMenu,
+      EnableMenuItem,
+      GetSystemMenu,
+      InsertMenuItemW,
+      MENUITEMINFOW,
+    },
+  },
+};
+
+/// Action requested through the window menu (the menu opened from the
+/// titlebar icon or with Alt+Space), the titlebar buttons, or keyboard
+/// shortcuts such as Alt+F4.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SystemCommand {
+  Close,
+  Minimize,
+  Maximize,
+  Restore,
+  Move,
+  Size,
+  /// The window menu was opened with the keyboard.
+  KeyMenu,
+  /// The window menu was opened with the mouse.
+  MouseMenu,
+  ContextHelp,
+  MonitorPower,
+  /// An item added with
+  /// [`Window::add_system_menu_item`](crate::Window::add_system_menu_item)
+  /// was selected.
+  Custom(u16),
+  /// Any other command, containing the raw `SC_*` value.
+  Other(u32),
+}
+
+impl SystemCommand {
+  /// Largest id usable for custom system menu items.
+  pub const MAX_CUSTOM_ID: u16 = 0x0EFF;
+
+  pub(crate) fn from_wparam(wparam: usize) -> Self {
+    // the low four bits are used internally by Windows
+    let command = wparam as u32 & 0xFFF0;
+    match command {
+      WindowsAndMessaging::SC_CLOSE => Self::Close,
+      WindowsAndMessaging::SC_MINIMIZE => Self::Minimize,
+      WindowsAndMessaging::SC_MAXIMIZE => Self::Maximize,
+      WindowsAndMessaging::SC_RESTORE => Self::Restore,
+      WindowsAndMessaging::SC_MOVE => Self::Move,
+      WindowsAndMessaging::SC_SIZE => Self::Size,
+      WindowsAndMessaging::SC_KEYMENU => Self::KeyMenu,
+      WindowsAndMessaging::SC_MOUSEMENU => Self::MouseMenu,
+      WindowsAndMessaging::SC_CONTEXTHELP => Self::ContextHelp,
+      WindowsAndMessaging::SC_MONITORPOWER => Self::MonitorPower,
+      command if command < 0xF000 => Self::Custom((command >> 4) as u16),
+      command => Self::Other(command),
+    }
+  }
+}
+
+/// Custom ids are shifted clear of the bits Windows reserves and stay below the
+/// predefined `SC_*` range.
+fn command_id(id: u16) -> u32 {
+  (id.min(SystemCommand::MAX_CUSTOM_ID) as u32) << 4
+}
+
+/// Append an item to the window menu, or a separator if `label` is `None`.
+pub(crate) fn append_system_menu_item(hwnd: HWND, id: u16, label: Option<&HSTRING>) {
+  let menu = unsafe { GetSystemMenu(hwnd, false) };
+  let mut info = MENUITEMINFOW {
+    cbSize: std::mem::size_of::<MENUITEMINFOW>() as u32,
+    ..Default::default()
+  };
+  match label {
+    Some(label) => {
+      info.fMask = WindowsAndMessaging::MIIM_ID | WindowsAndMessaging::MIIM_STRING;
+      info.wID = command_id(id);
+      info.dwTypeData = PWSTR(label.as_ptr() as *mut u16);
+      info.cch = label.len() as u32;
+    }
+    None => {
+      info.fMask = WindowsAndMessaging::MIIM_FTYPE;
+      info.fType = WindowsAndMessaging::MFT_SEPARATOR;
+    }
+  }
+
+  // appends when the position is past the end of the menu
+  if let Err(e) = unsafe { InsertMenuItemW(menu, u32::MAX, true, &info) } {
+    tracing::error!("{e}");
+  }
+}
+
+pub(crate) fn set_system_menu_item_checked(hwnd: HWND, id: u16, checked: bool) {
+  let menu = unsafe { GetSystemMenu(hwnd, false) };
+  let state = if checked {
+    WindowsAndMessaging::MF_CHECKED
+  } else {
+    WindowsAndMessaging::MF_UNCHECKED
+  };
+  unsafe {
+    CheckMenuItem(menu, command_id(id), (WindowsAndMessaging::MF_BYCOMMAND | state).0)
+  };
+}
+
+pub(crate) fn set_system_menu_item_enabled(hwnd: HWND, id: u16, enabled: bool) {
+  let menu = unsafe { GetSystemMenu(hwnd, false) };
+  let state = if enabled {
+    WindowsAndMessaging::MF_ENABLED
+  } else {
+    WindowsAndMessaging::MF_GRAYED
+  };
+  unsafe {
+    EnableMenuItem(menu, command_id(id), WindowsAndMessaging::MF_BYCOMMAND | state)
+  };
+}
+
+pub(crate) fn remove_system_menu_item(hwnd: HWND, id: u16) {
+  let menu = unsafe { GetSystemMenu(hwnd, false) };
+  if let Err(e) =
+    unsafe { DeleteMenu(menu, command_id(id), WindowsAndMessaging::MF_BYCOMMAND) }
+  {
+    tracing::error!("{e}");
+  }
+}
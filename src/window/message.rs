@@ -1,3 +1,6 @@
+use std::time::Duration;
+
+use cursor_icon::CursorIcon;
 use windows::Win32::{
   Foundation::{HINSTANCE, HWND, LPARAM, RECT, WPARAM},
   System::SystemServices::{
@@ -14,20 +17,37 @@ use windows::Win32::{
   },
 };
 
+#[cfg(feature = "audio")]
+use super::audio::AudioEvent;
+#[cfg(feature = "gamepad")]
+use super::input::gamepad::GamepadEvent;
 use super::{
   command::Command,
-  data::{PhysicalPosition, PhysicalSize},
-  input::{mouse::MouseButton, state::RawKeyState},
+  data::{Color, PhysicalPosition, PhysicalSize},
+  gesture::Gesture,
+  input::{
+    joystick::JoystickEvent,
+    layout::KeyboardLayout,
+    mouse::MouseButton,
+    pointer::PointerEvent,
+    state::RawKeyState,
+  },
+  menu::SystemCommand,
+  power::PowerStatus,
+  resize::ResizeSignal,
+  translator::CustomMessage,
 };
 use crate::{
+  error::SharedError,
   utilities::{hi_word, is_flag_set, lo_byte, lo_word, signed_hi_word, signed_lo_word},
   window::input::{
-    key::Key,
-    state::{ButtonState, KeyState},
+    key::{Key, PhysicalKey},
+    state::{ButtonState, KeyState, Modifiers},
   },
 };
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum Focus {
   Gained,
   Lost,
@@ -47,29 +67,49 @@ pub enum Message {
   /// Message sent when Windows requests the window be repainted.
   Paint,
   /// Message sent when a key is pressed, held, or released.
+  ///
+  /// `key` is the key as labeled by the active keyboard layout, while
+  /// `physical_key` is its position on the keyboard regardless of layout.
+  ///
+  /// `during_composition` is set while an input method editor (IME) is
+  /// composing text, such as when typing CJK characters. These keystrokes
+  /// belong to the composition and are not reflected in the window's key
+  /// state, so most shortcut or game-style handling should ignore them.
   Key {
     key: Key,
+    physical_key: PhysicalKey,
     state: KeyState,
     scan_code: u16,
     is_extended_key: bool,
+    during_composition: bool,
+    modifiers: Modifiers,
   },
   /// Message sent when a text character is typed containing that character.
   Text(String),
-  ModifiersChanged {
-    shift: ButtonState,
-    ctrl: ButtonState,
-    alt: ButtonState,
-    win: ButtonState,
-  },
+  /// Message sent when any of shift, ctrl, alt, or the Windows keys are
+  /// pressed or released.
+  ModifiersChanged(Modifiers),
   /// Message sent when a mouse button is pressed or released.
   MouseButton {
     button: MouseButton,
     state: ButtonState,
     position: PhysicalPosition,
     is_double_click: bool,
+    /// Presses of the button in a row, each within the
+    /// [`double_click_time`](crate::double_click_time) and
+    /// [`double_click_area`](crate::double_click_area) of the one before: 1
+    /// for a single click, 2 for a double click, 3 for a triple click, and so
+    /// on. Releases repeat the count of the press they end.
+    click_count: u32,
+    modifiers: Modifiers,
+  },
+  /// Message sent when the scroll wheel or a precision touchpad is scrolled.
+  MouseWheel {
+    delta: ScrollDelta,
+    /// Cursor position in the client area, such as to zoom around.
+    position: PhysicalPosition,
+    modifiers: Modifiers,
   },
-  /// Message sent when the scroll wheel is actuated.
-  MouseWheel { delta_x: f32, delta_y: f32 },
   /// Message sent when the cursor is moved within the window bounds. Don't
   /// use this for mouse input in cases such as first-person cameras as it is
   /// locked to the bounds of the window.
@@ -79,6 +119,12 @@ pub enum Message {
   },
   /// Message sent when the window is resized. Sent after [`BoundsChanged`]
   Resized(PhysicalSize),
+  /// Sent after [`Message::Resized`] while the window waits to show the new
+  /// size. Resizing the swapchain and presenting before it continues shows
+  /// the first frame at the new size along with it, instead of the stretched
+  /// previous frame flickering through during live resizes. See
+  /// [`ResizeSignal`] for renderers on another thread.
+  Resizing(ResizeSignal),
   /// Message sent when the window is moved. Sent after [`BoundsChanged`]
   Moved(PhysicalPosition),
   /// Message sent first when the window is moved or resized.
@@ -88,12 +134,122 @@ pub enum Message {
   },
   /// Message sent by Windows when certain actions are taken. WIP
   Command,
-  /// Message sent by Windows when certain actions are taken. WIP
-  SystemCommand,
+  /// Message sent when a command is chosen from the window menu or triggered
+  /// by the titlebar buttons.
+  SystemCommand(SystemCommand),
   /// Message sent when the window gains or loses focus.
   Focus(Focus),
   /// Message sent when the scale factor of the window has changed.
   ScaleFactorChanged(f64),
+  /// Message sent for touch gestures when enabled with
+  /// [`WindowBuilder::with_gestures`](crate::WindowBuilder::with_gestures).
+  Gesture(Gesture),
+  /// Message sent for HID game controllers when enabled with
+  /// [`WindowBuilder::with_joysticks`](crate::WindowBuilder::with_joysticks).
+  Joystick(JoystickEvent),
+  /// Message sent for pen and touch contacts, along with the capabilities of
+  /// the device they came from.
+  Pointer(PointerEvent),
+  /// Message sent for XInput gamepads while the window is focused.
+  #[cfg(feature = "gamepad")]
+  Gamepad(GamepadEvent),
+  /// Message sent for audio device and volume changes when enabled with
+  /// [`Window::set_audio_notifications`](crate::Window::set_audio_notifications).
+  #[cfg(feature = "audio")]
+  Audio(AudioEvent),
+  /// Message sent once a request made through a setter has been applied by
+  /// the window, such as [`Window::set_fullscreen`](crate::Window::set_fullscreen).
+  /// Any resulting `Resized` or `Moved` messages are sent before this one, so
+  /// it is safe to recreate size-dependent resources (such as a swapchain)
+  /// when it arrives.
+  CommandCompleted(CommandKind),
+  /// Message sent when the cursor shown over the window changes, either
+  /// because it moved over a different part of the window (such as a resize
+  /// border) or because a new icon was set with
+  /// [`Window::set_cursor_icon`](crate::Window::set_cursor_icon).
+  CursorIconChanged(CursorIcon),
+  /// Message sent when the cursor stops being confined to the window while in
+  /// [`CursorMode::Confined`](crate::CursorMode::Confined), such as when the
+  /// window loses focus.
+  CursorModeLost,
+  /// Message sent when the cursor is confined to the window again after
+  /// [`Message::CursorModeLost`].
+  CursorModeRestored,
+  /// Message sent when the keyboard layout (input language) of the window
+  /// changes.
+  KeyboardLayoutChanged(KeyboardLayout),
+  /// Message sent when the window's tray icon is clicked. A left click also
+  /// restores the window from the tray.
+  TrayIcon {
+    button: MouseButton,
+    is_double_click: bool,
+  },
+  /// Message sent by a
+  /// [`BackgroundWorker`](crate::window::worker::BackgroundWorker) when one of
+  /// its registered hotkeys is pressed.
+  Hotkey { id: i32 },
+  /// Message sent by a
+  /// [`BackgroundWorker`](crate::window::worker::BackgroundWorker) listening
+  /// for clipboard changes.
+  ClipboardUpdated,
+  /// Message produced by a user-provided
+  /// [`MessageTranslator`](crate::window::translator::MessageTranslator).
+  Custom(CustomMessage),
+  /// Message sent when a timer set with
+  /// [`Window::set_timer`](crate::Window::set_timer) fires, with its id.
+  Timer(u32),
+  /// Message sent once there has been no keyboard or mouse input to any app
+  /// for the [idle threshold](crate::Window::set_idle_threshold), with how
+  /// long it has been.
+  UserIdle(Duration),
+  /// Message sent when input resumes after [`Message::UserIdle`].
+  UserActive,
+  /// Message sent from another thread through an
+  /// [`EventProxy`](crate::window::proxy::EventProxy).
+  User(CustomMessage),
+  /// Message sent when the computer is about to sleep or hibernate. Pause
+  /// audio and rendering until [`Message::Resumed`].
+  Suspended,
+  /// Message sent when the computer wakes up after [`Message::Suspended`].
+  Resumed,
+  /// Message sent when the computer switches between AC and battery power,
+  /// or the battery level or battery saver changes.
+  PowerStatusChanged(PowerStatus),
+  /// Message sent when the user's session is locked, including when switching
+  /// to another user.
+  SessionLocked,
+  /// Message sent when the user's session is unlocked after
+  /// [`Message::SessionLocked`].
+  SessionUnlocked,
+  /// Message sent when high contrast mode is turned on or off, with whether
+  /// it's now on. See [`Window::is_high_contrast`](crate::Window::is_high_contrast).
+  HighContrastChanged(bool),
+  /// Message sent when animations are turned on or off in the system
+  /// settings, with whether they're now on. See
+  /// [`Window::animations_enabled`](crate::Window::animations_enabled).
+  AnimationsChanged(bool),
+  /// Message sent when transparency effects are turned on or off in the
+  /// system settings, with whether they're now on. See
+  /// [`Window::transparency_enabled`](crate::Window::transparency_enabled).
+  TransparencyChanged(bool),
+  /// Message sent when the user's accent color changes, with the new color.
+  /// See [`Window::accent_color`](crate::Window::accent_color).
+  AccentColorChanged(Color),
+  /// Message sent when a task of the
+  /// [jump list](crate::Window::set_jump_list) was clicked, with its id,
+  /// after the new process started for it
+  /// [forwarded](crate::window::jump_list::forward_launched_task) it to this
+  /// one.
+  JumpTask(String),
+  /// Message sent when handling a window message panicked on the window
+  /// thread, with the panic message. The panic is caught rather than unwinding
+  /// into Windows, and the window then closes, ending its loop with
+  /// [`LoopMessage::Exit`].
+  Panic(String),
+  /// Message sent when a setter or the window thread failed to apply a
+  /// change, such as a backdrop or cursor confinement the system refused, or a
+  /// command which couldn't be posted. The error is also logged.
+  Error(SharedError),
 }
 
 /// Artificial window messages sent by the window loop.
@@ -103,11 +259,86 @@ pub enum LoopMessage {
   Command(Command),
   /// Sent when the message pump is polled, but there are no messages.
   Empty,
-  /// Sent when the message pump is exiting.
-  Exit,
+  /// Sent when [`quit`](crate::quit) is called. The window closes right after
+  /// this message is yielded.
+  QuitRequested,
+  /// Sent when the message pump is exiting, with the code passed to
+  /// [`Window::exit_with_code`](crate::Window::exit_with_code), or `0`.
+  Exit(i32),
+  /// Sent when commands to the window were dropped or refused since the last
+  /// report, because the window thread had too many waiting. See
+  /// [`QueueFullPolicy`](crate::QueueFullPolicy).
+  QueueFull { dropped: usize, refused: usize },
+}
+
+/// Request which completes on the window thread some time after the setter
+/// returns, reported with [`Message::CommandCompleted`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum CommandKind {
+  Visibility,
+  Decorations,
+  Size,
+  Position,
+  Fullscreen,
+  Snap,
+}
+
+/// Amount scrolled by a single [`Message::MouseWheel`].
+///
+/// Both units follow the system's lines-per-notch setting. Notched wheels
+/// report lines, while precision touchpads and free-spinning wheels, which
+/// report fractions of a notch, are converted to physical pixels so they can
+/// be applied directly.
+#[derive(Debug, Copy, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum ScrollDelta {
+  Lines { x: f32, y: f32 },
+  Pixels { x: f32, y: f32 },
+}
+
+impl ScrollDelta {
+  /// Logical height of a scrolled line used for [`ScrollDelta::Pixels`].
+  pub const PIXELS_PER_LINE: f32 = 20.0;
+
+  pub(crate) fn from_wheel(
+    wheel_delta: i16,
+    horizontal: bool,
+    scale_factor: f64,
+    lines_per_notch: f32,
+    smooth: bool,
+  ) -> Self {
+    let notches = wheel_delta as f32 / WindowsAndMessaging::WHEEL_DELTA as f32;
+    let axis = |amount: f32| match horizontal {
+      true => (amount, 0.0),
+      false => (0.0, amount),
+    };
+
+    let lines = notches * lines_per_notch;
+    if smooth || !is_whole_notch(wheel_delta) {
+      let (x, y) = axis(lines * Self::PIXELS_PER_LINE * scale_factor as f32);
+      Self::Pixels { x, y }
+    } else {
+      let (x, y) = axis(lines);
+      Self::Lines { x, y }
+    }
+  }
+
+  /// Horizontal and vertical components, regardless of unit.
+  pub fn xy(&self) -> (f32, f32) {
+    match *self {
+      Self::Lines { x, y } | Self::Pixels { x, y } => (x, y),
+    }
+  }
+}
+
+/// Whether a wheel delta is a multiple of a notch, as notched wheels report.
+pub(crate) fn is_whole_notch(wheel_delta: i16) -> bool {
+  wheel_delta as i32 % WindowsAndMessaging::WHEEL_DELTA as i32 == 0
 }
 
 #[derive(Debug, PartialEq, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum RawInputMessage {
   /// Raw keyboard input
   Keyboard { key: Key, state: RawKeyState },
@@ -122,12 +353,21 @@ pub enum RawInputMessage {
 }
 
 impl Message {
-  pub(crate) fn new_keyboard_message(l_param: LPARAM) -> Message {
+  pub(crate) fn new_keyboard_message(
+    l_param: LPARAM,
+    during_composition: bool,
+    modifiers: Modifiers,
+  ) -> Message {
     let flags = hi_word(unsafe { std::mem::transmute::<i32, u32>(l_param.0 as i32) });
 
     let is_extended_key = is_flag_set(flags, WindowsAndMessaging::KF_EXTENDED as u16);
 
     let mut scan_code = lo_byte(flags) as u16;
+    let physical_key = PhysicalKey::from_scan_code(if is_extended_key {
+      scan_code | 0xE000
+    } else {
+      scan_code
+    });
 
     let key_code: Key = {
       let extended_scan_code = u16::from_le_bytes([scan_code as u8, 0xE0]);
@@ -162,9 +402,12 @@ impl Message {
 
     Message::Key {
       key: key_code,
+      physical_key,
       state,
       scan_code,
       is_extended_key,
+      during_composition,
+      modifiers,
     }
   }
 
@@ -172,6 +415,7 @@ impl Message {
     message: u32,
     w_param: WPARAM,
     l_param: LPARAM,
+    modifiers: Modifiers,
   ) -> Message {
     let flags = w_param.0 as u32;
 
@@ -256,6 +500,8 @@ impl Message {
       state,
       position,
       is_double_click,
+      click_count: 1,
+      modifiers,
     }
   }
 
@@ -281,6 +527,7 @@ impl Message {
   Adapted for windows crate.
 */
 #[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum CursorMoveKind {
   /// Cursor entered to the window.
   Entered,
@@ -0,0 +1,157 @@
+use windows::Win32::{
+  Foundation::{HWND, RECT},
+  Graphics::Gdi::{
+    self,
+    BitBlt,
+    CreateCompatibleBitmap,
+    CreateCompatibleDC,
+    CreateSolidBrush,
+    DeleteDC,
+    DeleteObject,
+    FillRect,
+    GetDC,
+    ReleaseDC,
+    SelectObject,
+    SetBkMode,
+    SetTextColor,
+    TextOutW,
+    HDC,
+  },
+};
+
+use super::{
+  data::{Color, PhysicalPosition, PhysicalSize},
+  registry::{OwnedResource, Resource},
+};
+
+/// Immediate-mode GDI drawing surface passed to
+/// [`Window::paint`](crate::Window::paint).
+///
+/// Drawing happens into an off-screen buffer which is copied to the window
+/// once the closure returns. The buffer starts out black, so most callers will
+/// want to [`fill`](PaintContext::fill) it first.
+pub struct PaintContext {
+  hdc: HDC,
+  size: PhysicalSize,
+}
+
+impl PaintContext {
+  /// Size of the client area being painted.
+  pub fn size(&self) -> PhysicalSize {
+    self.size
+  }
+
+  /// Fill the whole client area with a solid color.
+  pub fn fill(&mut self, color: impl Into<Color>) {
+    self.fill_rect(PhysicalPosition::default(), self.size, color);
+  }
+
+  /// Fill a rectangle with a solid color.
+  pub fn fill_rect(
+    &mut self,
+    position: impl Into<PhysicalPosition>,
+    size: impl Into<PhysicalSize>,
+    color: impl Into<Color>,
+  ) {
+    let position = position.into();
+    let size = size.into();
+    let rect = RECT {
+      left: position.x,
+      top: position.y,
+      right: position.x + size.width as i32,
+      bottom: position.y + size.height as i32,
+    };
+    let brush = unsafe { CreateSolidBrush(color.into().to_colorref()) };
+    let _owned = OwnedResource(Resource::Brush(brush));
+    unsafe { FillRect(self.hdc, &rect, brush) };
+  }
+
+  /// Set the color used by subsequent calls to [`PaintContext::text`].
+  pub fn set_text_color(&mut self, color: impl Into<Color>) {
+    unsafe { SetTextColor(self.hdc, color.into().to_colorref()) };
+  }
+
+  /// Draw a single line of text with its top-left corner at `position`.
+  pub fn text(&mut self, position: impl Into<PhysicalPosition>, text: impl AsRef<str>) {
+    let position = position.into();
+    let text: Vec<u16> = text.as_ref().encode_utf16().collect();
+    unsafe { TextOutW(self.hdc, position.x, position.y, &text) };
+  }
+}
+
+/// Device context of a window's client area. While the window thread is
+/// handling `WM_PAINT` this is the one from `BeginPaint`, which clips to the
+/// update region and is validated by `EndPaint` once the app has painted.
+/// Otherwise it's retrieved with `GetDC` and released on drop.
+pub(crate) struct WindowDc {
+  hwnd: HWND,
+  hdc: HDC,
+  owned: bool,
+}
+
+impl WindowDc {
+  pub fn get(hwnd: HWND, paint_dc: Option<HDC>) -> Option<Self> {
+    if let Some(hdc) = paint_dc {
+      return Some(Self {
+        hwnd,
+        hdc,
+        owned: false,
+      });
+    }
+    let hdc = unsafe { GetDC(hwnd) };
+    (!hdc.is_invalid()).then_some(Self {
+      hwnd,
+      hdc,
+      owned: true,
+    })
+  }
+
+  pub fn hdc(&self) -> HDC {
+    self.hdc
+  }
+}
+
+impl Drop for WindowDc {
+  fn drop(&mut self) {
+    if self.owned {
+      unsafe { ReleaseDC(self.hwnd, self.hdc) };
+    }
+  }
+}
+
+pub(crate) fn paint(
+  hwnd: HWND,
+  paint_dc: Option<HDC>,
+  size: PhysicalSize,
+  f: impl FnOnce(&mut PaintContext),
+) {
+  let (width, height) = (size.width as i32, size.height as i32);
+
+  let Some(window_dc) = WindowDc::get(hwnd, paint_dc) else {
+    tracing::error!("failed to get device context for painting");
+    return;
+  };
+
+  let memory_dc = unsafe { CreateCompatibleDC(window_dc.hdc()) };
+  let bitmap = unsafe { CreateCompatibleBitmap(window_dc.hdc(), width, height) };
+  let previous = unsafe { SelectObject(memory_dc, bitmap) };
+  unsafe { SetBkMode(memory_dc, Gdi::TRANSPARENT) };
+
+  let mut ctx = PaintContext {
+    hdc: memory_dc,
+    size,
+  };
+  f(&mut ctx);
+
+  if let Err(e) =
+    unsafe { BitBlt(window_dc.hdc(), 0, 0, width, height, memory_dc, 0, 0, Gdi::SRCCOPY) }
+  {
+    tracing::error!("{e}");
+  }
+
+  unsafe {
+    SelectObject(memory_dc, previous);
+    DeleteObject(bitmap);
+    DeleteDC(memory_dc);
+  }
+}
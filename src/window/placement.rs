@@ -0,0 +1,125 @@
+use windows::Win32::UI::WindowsAndMessaging::{
+  self,
+  GetWindowPlacement,
+  ShowWindowAsync,
+};
+
+use super::{
+  data::{Fullscreen, PhysicalPosition, PhysicalSize},
+  Window,
+};
+
+/// Saved position, size, and state of a window, for restoring it the next
+/// time the app starts.
+///
+/// The monitor is stored by its device name rather than its handle, which
+/// changes between sessions. When restoring, the window is placed relative to
+/// that monitor's current position, falling back to the primary monitor if it
+/// is no longer connected.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WindowPlacement {
+  /// Outer position of the window while neither maximized nor fullscreen.
+  pub position: PhysicalPosition,
+  /// Outer size of the window while neither maximized nor fullscreen.
+  pub size: PhysicalSize,
+  pub maximized: bool,
+  pub fullscreen: Option<Fullscreen>,
+  /// Device name of the monitor the window was on, such as `\\.\DISPLAY2`.
+  pub monitor: String,
+  /// Position of the monitor when the placement was saved.
+  pub monitor_position: PhysicalPosition,
+}
+
+impl Window {
+  /// Capture the window's current placement. See [`WindowPlacement`].
+  pub fn placement(&self) -> WindowPlacement {
+    let monitor = self.current_monitor();
+    let (fullscreen, maximized, last_windowed) = {
+      let data = self.0.data.lock().unwrap();
+      (
+        data.style.fullscreen,
+        data.style.maximized,
+        (
+          data.last_windowed_position.as_physical(data.scale_factor),
+          data.last_windowed_size.as_physical(data.scale_factor),
+        ),
+      )
+    };
+
+    let (position, size) = if fullscreen.is_some() {
+      last_windowed
+    } else {
+      let mut placement = WindowsAndMessaging::WINDOWPLACEMENT {
+        length: std::mem::size_of::<WindowsAndMessaging::WINDOWPLACEMENT>() as u32,
+        ..Default::default()
+      };
+      match unsafe { GetWindowPlacement(self.0.hwnd, &mut placement) } {
+        Ok(()) => {
+          // the restored rect is in work area coordinates
+          let work_area = monitor.work_area_position();
+          let origin = monitor.position();
+          let rect = placement.rcNormalPosition;
+          (
+            PhysicalPosition::new(
+              rect.left + work_area.x - origin.x,
+              rect.top + work_area.y - origin.y,
+            ),
+            PhysicalSize::new(
+              (rect.right - rect.left) as u32,
+              (rect.bottom - rect.top) as u32,
+            ),
+          )
+        }
+        Err(e) => {
+          tracing::error!("{e}");
+          (self.outer_position(), self.outer_size())
+        }
+      }
+    };
+
+    WindowPlacement {
+      position,
+      size,
+      maximized,
+      fullscreen,
+      monitor: monitor.name(),
+      monitor_position: monitor.position(),
+    }
+  }
+
+  /// Move and resize the window to a placement saved with
+  /// [`Window::placement`], then maximize it or make it fullscreen as saved.
+  pub fn set_placement(&self, placement: &WindowPlacement) {
+    let monitor = self
+      .available_monitors()
+      .into_iter()
+      .find(|monitor| monitor.name() == placement.monitor)
+      .unwrap_or_else(|| {
+        tracing::debug!(
+          "monitor `{}` not found, restoring to primary monitor",
+          placement.monitor
+        );
+        self.primary_monitor()
+      });
+
+    // keep the offset from the monitor, which may have moved or be a different one
+    let origin = monitor.position();
+    let position = PhysicalPosition::new(
+      origin.x + placement.position.x - placement.monitor_position.x,
+      origin.y + placement.position.y - placement.monitor_position.y,
+    );
+
+    self.set_fullscreen(None);
+    if self.is_maximized() && !placement.maximized {
+      unsafe { ShowWindowAsync(self.0.hwnd, WindowsAndMessaging::SW_RESTORE) };
+    }
+    // the current bounds may be stale while leaving fullscreen, so always apply
+    self.force_set_outer_position(position.into());
+    self.force_set_outer_size(placement.size.into());
+    if placement.maximized {
+      unsafe { ShowWindowAsync(self.0.hwnd, WindowsAndMessaging::SW_MAXIMIZE) };
+    }
+    self.set_fullscreen(placement.fullscreen);
+  }
+}
@@ -0,0 +1,122 @@
+use std::time::Duration;
+
+use windows::Win32::{
+  Foundation::HWND,
+  System::{
+    Power::{self, GetSystemPowerStatus, SetThreadExecutionState, SYSTEM_POWER_STATUS},
+    RemoteDesktop::{
+      WTSRegisterSessionNotification,
+      WTSUnRegisterSessionNotification,
+      NOTIFY_FOR_THIS_SESSION,
+    },
+  },
+};
+
+/// Ask for `WM_WTSSESSION_CHANGE` so locking and unlocking the session can be
+/// reported. Power broadcasts are sent to every top-level window without
+/// registering.
+pub(crate) fn register_session_notifications(hwnd: HWND) {
+  if let Err(e) = unsafe { WTSRegisterSessionNotification(hwnd, NOTIFY_FOR_THIS_SESSION) }
+  {
+    tracing::error!("{e}");
+  }
+}
+
+pub(crate) fn unregister_session_notifications(hwnd: HWND) {
+  let _ = unsafe { WTSUnRegisterSessionNotification(hwnd) };
+}
+
+/// Where the computer draws its power from, see [`PowerStatus`].
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum PowerSource {
+  Ac,
+  Battery,
+  Unknown,
+}
+
+/// Power and battery state of the computer, returned by [`power_status`] and
+/// sent with [`Message::PowerStatusChanged`](crate::Message::PowerStatusChanged).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct PowerStatus {
+  pub source: PowerSource,
+  /// Remaining battery charge from 0 to 100, `None` without a battery or if
+  /// unknown.
+  pub battery_percent: Option<u8>,
+  /// Estimated battery time left while on battery, if known.
+  pub battery_time_remaining: Option<Duration>,
+  /// Whether battery saver is on, which apps are expected to respect by doing
+  /// less work.
+  pub battery_saver: bool,
+}
+
+impl PowerStatus {
+  /// Whether the computer is running on battery, a good hint to throttle the
+  /// frame rate.
+  pub fn on_battery(&self) -> bool {
+    self.source == PowerSource::Battery
+  }
+}
+
+/// What [`Window::set_keep_awake`](crate::Window::set_keep_awake) keeps from
+/// going to sleep.
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum KeepAwake {
+  /// Let the computer and display sleep as usual.
+  #[default]
+  None,
+  /// Keep the computer awake but let the display turn off, such as for long
+  /// renders or downloads.
+  System,
+  /// Keep the computer awake and the display on, such as for video playback.
+  Display,
+}
+
+/// Keep the computer or display awake while the calling thread runs, until
+/// changed again.
+pub(crate) fn set_keep_awake(keep_awake: KeepAwake) {
+  let flags = match keep_awake {
+    KeepAwake::None => Power::ES_CONTINUOUS,
+    KeepAwake::System => Power::ES_CONTINUOUS | Power::ES_SYSTEM_REQUIRED,
+    KeepAwake::Display => {
+      Power::ES_CONTINUOUS | Power::ES_SYSTEM_REQUIRED | Power::ES_DISPLAY_REQUIRED
+    }
+  };
+  if unsafe { SetThreadExecutionState(flags) }.0 == 0 {
+    tracing::error!("failed to set the thread execution state");
+  }
+}
+
+/// Current power and battery state of the computer.
+pub fn power_status() -> PowerStatus {
+  let mut status = SYSTEM_POWER_STATUS::default();
+  if let Err(e) = unsafe { GetSystemPowerStatus(&mut status) } {
+    tracing::error!("{e}");
+    return PowerStatus {
+      source: PowerSource::Unknown,
+      battery_percent: None,
+      battery_time_remaining: None,
+      battery_saver: false,
+    };
+  }
+
+  const NO_SYSTEM_BATTERY: u8 = 128;
+  const UNKNOWN: u8 = 255;
+  let has_battery =
+    status.BatteryFlag & NO_SYSTEM_BATTERY == 0 && status.BatteryFlag != UNKNOWN;
+  PowerStatus {
+    source: match status.ACLineStatus {
+      0 => PowerSource::Battery,
+      1 => PowerSource::Ac,
+      _ => PowerSource::Unknown,
+    },
+    battery_percent: (has_battery && status.BatteryLifePercent <= 100)
+      .then_some(status.BatteryLifePercent),
+    // u32::MAX when unknown or charging
+    battery_time_remaining: (has_battery && status.BatteryLifeTime != u32::MAX)
+      .then(|| Duration::from_secs(status.BatteryLifeTime as u64)),
+    battery_saver: status.SystemStatusFlag == 1,
+  }
+}
@@ -1,4 +1,9 @@
-use std::sync::{Arc, Mutex};
+use std::{
+  any::Any,
+  collections::{HashSet, VecDeque},
+  panic::{self, AssertUnwindSafe},
+  sync::{atomic::AtomicBool, Arc, Mutex},
+};
 
 use cursor_icon::CursorIcon;
 // use crossbeam::channel::{Receiver, Sender};
@@ -11,25 +16,47 @@ use windows::Win32::{
       DefWindowProcW,
       DestroyWindow,
       GetWindowLongPtrW,
+      KillTimer,
       PostQuitMessage,
       SetWindowLongPtrW,
+      SetWindowPos,
       CREATESTRUCTW,
+      SHOW_WINDOW_CMD,
     },
   },
 };
 
+#[cfg(feature = "accessibility")]
+use super::accessibility;
+#[cfg(feature = "audio")]
+use super::audio;
+#[cfg(feature = "gamepad")]
+use super::input::gamepad;
 #[allow(unused)]
 use super::message::Message;
 use super::{
   command::Command,
-  data::{Data, Position, Size, SyncData, Visibility},
+  data::{Backdrop, Data, Position, Size, SyncData, Visibility},
   frame::Style,
+  gesture::{self, GestureTracker},
+  input::{
+    joystick::{self, JoystickTracker},
+    layout::KeyboardLayout,
+    pointer,
+  },
+  journal::{self, Journal},
+  power::{self, KeepAwake},
+  queue,
+  registry::WindowClass,
   settings::WindowSettings,
+  thread,
+  tray,
   Window,
 };
 use crate::{
   prelude::Input,
   utilities::{
+    self,
     dpi_to_scale_factor,
     hwnd_dpi,
     register_all_mice_and_keyboards_for_raw_input,
@@ -37,7 +64,7 @@ use crate::{
   window::{
     cursor::Cursor,
     data::{Internal, PhysicalPosition},
-    stage::Stage,
+    stage::{AtomicStage, Stage},
   },
   LoopMessage,
 };
@@ -47,9 +74,8 @@ pub struct CreateInfo {
   pub size: Size,
   pub position: Option<Position>,
   pub settings: WindowSettings,
-  pub class_atom: u16,
+  pub(crate) class: Option<WindowClass>,
   pub window: Option<Window>,
-  pub message: Arc<Mutex<Option<Message>>>,
   pub sync: SyncData,
   pub style: Style,
 }
@@ -68,9 +94,28 @@ pub extern "system" fn wnd_proc(
   wparam: WPARAM,
   lparam: LPARAM,
 ) -> LRESULT {
+  // unwinding out of an `extern "system"` function aborts the process
+  match panic::catch_unwind(AssertUnwindSafe(|| {
+    handle_message(hwnd, msg, wparam, lparam)
+  })) {
+    Ok(result) => result,
+    Err(payload) => on_panic(hwnd, msg, payload),
+  }
+}
+
+fn handle_message(hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> LRESULT {
   let user_data_ptr =
     unsafe { GetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_USERDATA) };
 
+  if msg == queue::DRAIN_MESSAGE_ID {
+    // each command is handled as if it had been sent on its own
+    while let Some(command) = queue::pop(hwnd) {
+      let addr = Box::into_raw(Box::new(command)) as usize;
+      wnd_proc(hwnd, Command::MESSAGE_ID, WPARAM(addr), LPARAM(0));
+    }
+    return LRESULT(0);
+  }
+
   match (user_data_ptr, msg) {
     (0, WindowsAndMessaging::WM_NCCREATE) => on_nccreate(hwnd, msg, wparam, lparam),
     (0, WindowsAndMessaging::WM_CREATE) => on_create(hwnd, msg, wparam, lparam),
@@ -80,13 +125,23 @@ pub extern "system" fn wnd_proc(
         match command {
           Command::Destroy => {
             unsafe { DestroyWindow(hwnd) }.unwrap();
+            thread::release_class(hwnd);
             LRESULT(0)
           }
           _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
         }
       }
       WindowsAndMessaging::WM_DESTROY => {
-        unsafe { PostQuitMessage(0) };
+        queue::forget(hwnd);
+        power::unregister_session_notifications(hwnd);
+        #[cfg(feature = "accessibility")]
+        accessibility::disconnect(hwnd);
+        #[cfg(feature = "audio")]
+        audio::stop(hwnd);
+        // a shared thread keeps pumping for its other windows
+        if !thread::is_shared_thread() {
+          unsafe { PostQuitMessage(0) };
+        }
         unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
       }
       _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
@@ -98,9 +153,21 @@ pub extern "system" fn wnd_proc(
           match command {
             Command::Exit => {
               let user_data = unsafe { Box::from_raw(state_ptr as *mut UserData) };
+              user_data.state.end_modal();
+              // the taskbar keeps the window's properties otherwise
+              if user_data.state.data.lock().unwrap().app_id.is_some() {
+                if let Err(e) = utilities::set_window_app_id(hwnd, None) {
+                  tracing::error!("{e}");
+                }
+              }
+              // a shared thread outlives the window
+              if user_data.state.data.lock().unwrap().keep_awake != KeepAwake::None {
+                power::set_keep_awake(KeepAwake::None);
+              }
+              let code = user_data.state.data.lock().unwrap().exit_code;
               user_data
                 .state
-                .send_message_to_main(Message::Loop(LoopMessage::Exit));
+                .send_message_to_main(Message::Loop(LoopMessage::Exit(code)));
               drop(user_data);
               unsafe { SetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_USERDATA, 0) };
               LRESULT(0)
@@ -114,6 +181,13 @@ pub extern "system" fn wnd_proc(
             }
           }
         }
+        WindowsAndMessaging::WM_TIMER if wparam.0 == journal::JOURNAL_TIMER_ID => {
+          let _ = unsafe { KillTimer(hwnd, journal::JOURNAL_TIMER_ID) };
+          if let Some(user_data) = unsafe { (state_ptr as *mut UserData).as_mut() } {
+            journal::write(&Window(user_data.state.clone()));
+          }
+          LRESULT(0)
+        }
         _ => {
           if let Some(user_data) = unsafe { (state_ptr as *mut UserData).as_mut() } {
             user_data.state.on_message(hwnd, msg, wparam, lparam)
@@ -127,6 +201,55 @@ pub extern "system" fn wnd_proc(
   }
 }
 
+/// Whether `msg` is one of the messages the crate posts to its own windows,
+/// which filters and subclasses never see.
+pub(crate) fn is_internal_message(msg: u32) -> bool {
+  matches!(msg, Command::MESSAGE_ID | queue::DRAIN_MESSAGE_ID | tray::TRAY_MESSAGE_ID)
+}
+
+/// Report a panic caught in the window procedure to the window's iterator and
+/// close the window, as the panicking handler may have left it half updated.
+pub(crate) fn on_panic(hwnd: HWND, msg: u32, payload: Box<dyn Any + Send>) -> LRESULT {
+  let message = match payload.downcast::<String>() {
+    Ok(message) => *message,
+    Err(payload) => match payload.downcast::<&'static str>() {
+      Ok(message) => (*message).to_owned(),
+      Err(_) => "Box<dyn Any>".to_owned(),
+    },
+  };
+  tracing::error!("window procedure panicked handling message {msg:#x}: {message}");
+
+  // failing creation makes `CreateWindowExW` return an error instead
+  match msg {
+    WindowsAndMessaging::WM_NCCREATE => return LRESULT(0),
+    WindowsAndMessaging::WM_CREATE => return LRESULT(-1),
+    _ => (),
+  }
+
+  let user_data_ptr =
+    unsafe { GetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_USERDATA) };
+  let Some(user_data) = (unsafe { (user_data_ptr as *const UserData).as_ref() }) else {
+    return LRESULT(0);
+  };
+  let state = user_data.state.clone();
+  // the handler may have panicked while holding these, which later messages need
+  state.data.clear_poison();
+  state.flow.clear_poison();
+  state.input.clear_poison();
+
+  // nobody waits on messages past the loop, such as while being destroyed, and
+  // handing one off would block forever
+  let loop_exited = matches!(state.stage.load(), Stage::ExitLoop | Stage::Destroyed);
+  if !state.is_closing() {
+    state.stage.store(Stage::Closing);
+    Command::Exit.post(hwnd);
+  }
+  if !loop_exited {
+    state.send_message_to_main(Message::Panic(message));
+  }
+  LRESULT(0)
+}
+
 fn on_nccreate(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
   if let Err(e) = unsafe { EnableNonClientDpiScaling(hwnd) } {
     tracing::error!("{e}");
@@ -137,6 +260,19 @@ fn on_nccreate(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESUL
   unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) }
 }
 
+/// How to show the window the first time, if not normally. Maximizing always
+/// activates the window.
+fn initial_show(settings: &WindowSettings) -> Option<SHOW_WINDOW_CMD> {
+  let active = settings.active && !settings.no_activate;
+  match (settings.minimized, settings.maximized, active) {
+    (true, _, true) => Some(WindowsAndMessaging::SW_SHOWMINIMIZED),
+    (true, _, false) => Some(WindowsAndMessaging::SW_SHOWMINNOACTIVE),
+    (false, true, _) => Some(WindowsAndMessaging::SW_SHOWMAXIMIZED),
+    (false, false, true) => None,
+    (false, false, false) => Some(WindowsAndMessaging::SW_SHOWNOACTIVATE),
+  }
+}
+
 fn on_create(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT {
   let create_struct = unsafe { (l_param.0 as *mut CREATESTRUCTW).as_mut().unwrap() };
   let create_info = unsafe {
@@ -160,14 +296,70 @@ fn on_create(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT
   let state = Arc::new(Internal {
     hinstance: create_struct.hInstance,
     hwnd,
-    class_atom: create_info.class_atom,
-    message: create_info.message.clone(),
     sync: create_info.sync.clone(),
+    stage: AtomicStage::new(Stage::Setup),
+    requested_redraw: AtomicBool::new(false),
+    flow: Mutex::new(create_info.settings.flow),
+    input: Mutex::new(input),
+    pending_errors: Mutex::new(VecDeque::new()),
     thread: Mutex::new(None),
     data: Mutex::new(Data {
       title: create_info.title.clone(),
       subtitle: Default::default(),
+      title_wide: create_info.title.encode_utf16().collect(),
+      subtitle_wide: Vec::new(),
+      window_text_pending: false,
       theme: Default::default(),
+      backdrop: Default::default(),
+      caption_color: None,
+      caption_text_color: None,
+      border_color: None,
+      icon: None,
+      shown_icon: None,
+      paint_dc: None,
+      app_id: create_info.settings.app_id.clone(),
+      keep_awake: KeepAwake::None,
+      high_contrast: utilities::is_high_contrast(),
+      animations: utilities::animations_enabled(),
+      transparency: utilities::transparency_enabled(),
+      accent_color: utilities::accent_color(),
+      wheel_scroll_lines: utilities::wheel_scroll_lines(),
+      wheel_scroll_chars: utilities::wheel_scroll_chars(),
+      smooth_wheel_devices: HashSet::new(),
+      wheel_device: None,
+      tray_behavior: create_info.settings.tray_behavior,
+      in_tray: false,
+      modal_owner: None,
+      double_clicks: create_info.settings.double_clicks,
+      clicks: Default::default(),
+      ime_composing: false,
+      window_input: Input::new(),
+      pending_surrogate: None,
+      key_repeat: create_info.settings.key_repeat,
+      gesture_tracker: create_info.settings.gestures.then(GestureTracker::default),
+      tab_strip: None,
+      joysticks: create_info
+        .settings
+        .joysticks
+        .then(JoystickTracker::default),
+      pointer_devices: Default::default(),
+      #[cfg(feature = "gamepad")]
+      gamepad_poller: Default::default(),
+      keyboard_layout: KeyboardLayout::current(),
+      message_translator: create_info.settings.message_translator.clone(),
+      message_filter: create_info.settings.message_filter.clone(),
+      message_replier: create_info.settings.message_replier.clone(),
+      #[cfg(feature = "accessibility")]
+      accessible: Default::default(),
+      #[cfg(feature = "accessibility")]
+      accessibility_handler: None,
+      hit_tester: None,
+      journal: create_info
+        .settings
+        .journal
+        .clone()
+        .map(|(sink, debounce)| Journal::new(sink, debounce)),
+      recorder: None,
       style: create_info.style.clone(),
       scale_factor,
       last_windowed_position: position,
@@ -178,13 +370,40 @@ fn on_create(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT
         inside_window: false,
         last_position: PhysicalPosition::default(),
         selected_icon: CursorIcon::Default,
+        custom: None,
+        shown_custom: None,
+        effective_icon: None,
+        grabbed: false,
+        grab_lost: false,
       },
-      flow: create_info.settings.flow,
+      background_interval: create_info.settings.background_interval,
+      visible_after_first_draw: create_info.settings.visible_after_first_draw,
+      initial_show: initial_show(&create_info.settings),
       close_on_x: create_info.settings.close_on_x,
-      stage: Stage::Setup,
-      input,
-      requested_redraw: false,
+      handling_resize: false,
+      handling_move: false,
+      deferred_commands: Vec::new(),
+      pump_started: false,
+      wake_deadline: None,
+      idle_threshold: create_info.settings.idle_threshold,
+      user_idle: false,
+      timers: Default::default(),
+      frame_pacing: create_info.settings.frame_pacing,
+      last_paint_refresh: None,
+      redraw_deferred: false,
+      exit_code: 0,
+      suspended: false,
     }),
+    broadcaster: Default::default(),
+    before_destroy: Mutex::new(Vec::new()),
+    _class: thread::hold_class(
+      hwnd,
+      create_info
+        .class
+        .take()
+        .expect("window class should be registered before the window is created"),
+    ),
+    _shared_thread: create_info.settings.thread.clone(),
   });
 
   // create data ptr
@@ -200,30 +419,81 @@ fn on_create(hwnd: HWND, msg: u32, w_param: WPARAM, l_param: LPARAM) -> LRESULT
 
   let window = Window(state.clone());
   window.force_set_theme(create_info.settings.theme);
+  if create_info.settings.backdrop != Backdrop::None {
+    window.force_set_system_backdrop(create_info.settings.backdrop);
+  }
+  if let Some(color) = create_info.settings.caption_color {
+    window.force_set_caption_color(Some(color));
+  }
+  if let Some(color) = create_info.settings.caption_text_color {
+    window.force_set_caption_text_color(Some(color));
+  }
+  if let Some(color) = create_info.settings.border_color {
+    window.force_set_border_color(Some(color));
+  }
+  if let Some(icon) = create_info.settings.icon.clone() {
+    window.force_set_icon(Some(icon));
+  }
+  if let Some(app_id) = &create_info.settings.app_id {
+    if let Err(e) = utilities::set_window_app_id(hwnd, Some(app_id)) {
+      tracing::error!("{e}");
+    }
+  }
+  if create_info.settings.gestures {
+    gesture::enable_gestures(hwnd);
+  }
+  if create_info.settings.joysticks {
+    joystick::register_for_raw_input(hwnd);
+  }
+  pointer::register_device_notifications(hwnd);
+  power::register_session_notifications(hwnd);
+  if let Some(threshold) = create_info.settings.idle_threshold {
+    utilities::start_idle_timer(hwnd, threshold);
+  }
+  #[cfg(feature = "gamepad")]
+  gamepad::start_polling(hwnd);
+  if let Some(journal) = &window.0.data.lock().unwrap().journal {
+    journal.schedule(hwnd);
+  }
 
-  if let Some(position) = create_info.position {
-    Command::SetPosition(position).send(hwnd);
+  match create_info.position {
+    Some(position) => Command::SetBounds(position, size).send(hwnd),
+    None => Command::SetSize(size).send(hwnd),
   }
-  Command::SetSize(size).send(hwnd);
   Command::SetDecorations(create_info.settings.decorations).send(hwnd);
   Command::SetVisibility(create_info.settings.visibility).send(hwnd);
   Command::SetFullscreen(create_info.settings.fullscreen).send(hwnd);
+  // the first `WM_NCCALCSIZE` arrives before `WM_CREATE`, so the replier never saw it
+  if create_info.settings.message_replier.is_some() {
+    if let Err(e) = unsafe {
+      SetWindowPos(
+        hwnd,
+        None,
+        0,
+        0,
+        0,
+        0,
+        WindowsAndMessaging::SWP_NOZORDER
+          | WindowsAndMessaging::SWP_NOMOVE
+          | WindowsAndMessaging::SWP_NOSIZE
+          | WindowsAndMessaging::SWP_NOACTIVATE
+          | WindowsAndMessaging::SWP_FRAMECHANGED,
+      )
+    } {
+      tracing::error!("{e}");
+    }
+  }
 
   tracing::trace!("[`{}`]: window is ready", create_info.title);
-  window.0.data.lock().unwrap().stage = Stage::Ready;
+  window.0.stage.store(Stage::Ready);
   *window.0.sync.skip_wait.lock().unwrap() = false;
 
   create_info.window = Some(window);
 
-  create_info
-    .message
-    .lock()
-    .unwrap()
-    .replace(Message::Created {
-      hwnd,
-      hinstance: create_struct.hInstance,
-    });
-  create_info.sync.signal_new_message();
+  create_info.sync.deliver(Message::Created {
+    hwnd,
+    hinstance: create_struct.hInstance,
+  });
 
   unsafe { DefWindowProcW(hwnd, msg, w_param, l_param) }
 }
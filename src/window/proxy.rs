@@ -0,0 +1,33 @@
+use std::any::Any;
+
+use windows::Win32::Foundation::HWND;
+
+use super::{command::Command, translator::CustomMessage};
+use crate::error::WindowError;
+
+/// Handle for other threads to send [`Message::User`](crate::Message::User)
+/// to a window's iterator, waking it if it is waiting. Returned by
+/// [`Window::event_proxy`](crate::Window::event_proxy).
+///
+/// Unlike the window, the proxy doesn't keep the window open. Sending fails
+/// once the window has been destroyed.
+#[derive(Debug, Clone)]
+pub struct EventProxy {
+  hwnd: HWND,
+}
+
+impl EventProxy {
+  pub(crate) fn new(hwnd: HWND) -> Self {
+    Self { hwnd }
+  }
+
+  /// Send `value` to the window's iterator, wrapped in a [`CustomMessage`].
+  pub fn send<T: Any + Send + Sync>(&self, value: T) -> Result<(), WindowError> {
+    self.send_message(CustomMessage::new(value))
+  }
+
+  /// Send an already wrapped message to the window's iterator.
+  pub fn send_message(&self, message: CustomMessage) -> Result<(), WindowError> {
+    Command::User(message).try_post(self.hwnd)
+  }
+}
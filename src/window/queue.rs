@@ -0,0 +1,180 @@
+use std::{
+  collections::{HashMap, VecDeque},
+  sync::{Condvar, Mutex, MutexGuard, OnceLock},
+  time::Duration,
+};
+
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, WPARAM},
+  System::Threading::GetCurrentThreadId,
+  UI::WindowsAndMessaging::{self, GetWindowThreadProcessId, PostMessageW},
+};
+
+use super::command::Command;
+use crate::error::WindowError;
+
+/*
+  Commands posted to a window are buffered here rather than each being posted to the
+  window thread's Win32 message queue, which is capped at 10,000 messages by default.
+  Calling many setters per frame used to fill it, after which posting failed and input
+  or paint messages stopped fitting.
+
+  Only the first command buffered while the window thread isn't already draining posts
+  a single wake message. The window thread then runs every buffered command in order,
+  including any buffered while it drains. A command replacing the state set by the
+  command right before it, such as two titles or sizes in a row, replaces it in the
+  buffer instead of running twice. A size and a position in a row are likewise combined
+  into one command, which moves and resizes the window at once.
+
+  Commands sent synchronously with `Command::send` skip the buffer.
+*/
+
+/// Message posted to wake the window thread to drain its buffered commands.
+pub(crate) const DRAIN_MESSAGE_ID: u32 = WindowsAndMessaging::WM_USER + 73;
+
+/// Commands waiting for a window before the [`QueueFullPolicy`] applies.
+const SOFT_LIMIT: usize = 8_000;
+/// Longest [`QueueFullPolicy::Block`] waits before buffering anyway.
+const BLOCK_TIMEOUT: Duration = Duration::from_millis(250);
+
+/// What posting a command to a window does while the window thread has too
+/// many commands waiting, such as when setters are called in a tight loop while
+/// the window thread waits for the iterator. Set with
+/// [`set_queue_full_policy`].
+#[derive(Default, Debug, Copy, Clone, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum QueueFullPolicy {
+  /// Wait for the window thread to catch up. The window thread can't drain its
+  /// queue while it waits for the iterator, so after a short while the command
+  /// is posted anyway.
+  #[default]
+  Block,
+  /// Skip the oldest command still waiting to make room. Closing and quitting
+  /// are never skipped.
+  DropOldest,
+  /// Refuse the new command, logging
+  /// [`WindowError::QueueFull`](crate::error::WindowError::QueueFull).
+  /// Closing and quitting are never refused.
+  Error,
+}
+
+static POLICY: Mutex<QueueFullPolicy> = Mutex::new(QueueFullPolicy::Block);
+static DRAINED: Condvar = Condvar::new();
+
+/// Choose what happens to commands posted to a window whose thread has too
+/// many commands waiting. Applies to every window. Commands dropped or
+/// refused are reported to the window's iterator with
+/// [`LoopMessage::QueueFull`](crate::LoopMessage::QueueFull).
+pub fn set_queue_full_policy(policy: QueueFullPolicy) {
+  *POLICY.lock().unwrap() = policy;
+}
+
+pub fn queue_full_policy() -> QueueFullPolicy {
+  *POLICY.lock().unwrap()
+}
+
+#[derive(Default)]
+struct PendingCommands {
+  commands: VecDeque<Command>,
+  /// Whether a wake message was posted and the window thread hasn't finished
+  /// draining since.
+  wake_pending: bool,
+  dropped: usize,
+  refused: usize,
+}
+
+fn queues() -> MutexGuard<'static, HashMap<isize, PendingCommands>> {
+  static QUEUES: OnceLock<Mutex<HashMap<isize, PendingCommands>>> = OnceLock::new();
+  QUEUES
+    .get_or_init(|| Mutex::new(HashMap::new()))
+    .lock()
+    .unwrap()
+}
+
+/// Buffer a command for `hwnd`, waking its thread if it isn't already.
+pub(crate) fn push(hwnd: HWND, command: Command) -> Result<(), WindowError> {
+  let policy = queue_full_policy();
+  let mut queues = queues();
+
+  let is_full = |queues: &HashMap<isize, PendingCommands>| {
+    queues
+      .get(&hwnd.0)
+      .is_some_and(|queue| queue.commands.len() >= SOFT_LIMIT)
+  };
+  if is_full(&queues) {
+    match policy {
+      QueueFullPolicy::Block => {
+        // the window thread can't drain its own queue while waiting on itself
+        let on_window_thread =
+          unsafe { GetWindowThreadProcessId(hwnd, None) == GetCurrentThreadId() };
+        if !on_window_thread {
+          queues = DRAINED
+            .wait_timeout_while(queues, BLOCK_TIMEOUT, |queues| is_full(queues))
+            .unwrap()
+            .0;
+        }
+        if is_full(&queues) {
+          tracing::warn!("window message queue is still full, posting anyway");
+        }
+      }
+      QueueFullPolicy::DropOldest => {
+        let queue = queues.get_mut(&hwnd.0).unwrap();
+        let oldest = queue.commands.iter().position(Command::is_droppable);
+        if let Some(index) = oldest {
+          queue.commands.remove(index);
+          queue.dropped += 1;
+        }
+      }
+      // closing the window must always get through
+      QueueFullPolicy::Error if command.is_droppable() => {
+        queues.get_mut(&hwnd.0).unwrap().refused += 1;
+        return Err(WindowError::QueueFull);
+      }
+      QueueFullPolicy::Error => (),
+    }
+  }
+
+  let queue = queues.entry(hwnd.0).or_default();
+  // the window thread can't drain before the command is in, the lock is held
+  if !queue.wake_pending {
+    unsafe { PostMessageW(hwnd, DRAIN_MESSAGE_ID, WPARAM(0), LPARAM(0)) }?;
+    queue.wake_pending = true;
+  }
+  let unabsorbed = match queue.commands.back_mut() {
+    Some(last) => last.absorb(command),
+    None => Some(command),
+  };
+  if let Some(command) = unabsorbed {
+    queue.commands.push_back(command);
+  }
+  Ok(())
+}
+
+/// Next buffered command for the window thread to run, or `None` once the
+/// buffer is empty, after which the next push wakes the thread again.
+pub(crate) fn pop(hwnd: HWND) -> Option<Command> {
+  let mut queues = queues();
+  let queue = queues.get_mut(&hwnd.0)?;
+  let command = queue.commands.pop_front();
+  if command.is_none() {
+    queue.wake_pending = false;
+  }
+  DRAINED.notify_all();
+  command
+}
+
+/// Commands dropped and refused since the last report, if any.
+pub(crate) fn take_report(hwnd: HWND) -> Option<(usize, usize)> {
+  let mut queues = queues();
+  let queue = queues.get_mut(&hwnd.0)?;
+  let report = (std::mem::take(&mut queue.dropped), std::mem::take(&mut queue.refused));
+  (report != (0, 0)).then_some(report)
+}
+
+/// Drop the commands still buffered for a destroyed window.
+pub(crate) fn forget(hwnd: HWND) {
+  // dropped outside the lock, commands may hold resources with their own cleanup
+  let queue = queues().remove(&hwnd.0);
+  DRAINED.notify_all();
+  drop(queue);
+}
@@ -0,0 +1,308 @@
+use std::{
+  thread::JoinHandle,
+  time::{Duration, Instant},
+};
+
+use super::{
+  command::Command,
+  data::{PhysicalPosition, PhysicalSize},
+  gesture::Gesture,
+  input::{
+    key::{Key, PhysicalKey},
+    mouse::MouseButton,
+    state::{ButtonState, KeyState, Modifiers},
+  },
+  message::{CursorMoveKind, Focus, Message, RawInputMessage, ScrollDelta},
+  Window,
+};
+
+/*
+  Recording hooks into the iterator rather than the window procedure, so it captures the
+  messages the app actually saw, after translation and key repeat filtering. Only input
+  and the window changes input handling depends on are kept; paint and loop messages are
+  produced again by the replaying window, and the rest refer to state which doesn't
+  carry over to another run, such as device handles or custom payloads.
+
+  Recorded messages are kept as `RecordedInput` rather than `Message`, so recordings can
+  be saved with serde without `Message` having to serialize variants which can't be, and
+  new `Message` variants stay out of recordings until they are added here.
+
+  Replaying injects the messages with `Window::inject`, so the input state follows them
+  the same way it did while recording.
+*/
+
+/// Message yielded by the iterator while recording, with the time since the
+/// recording started.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct RecordedMessage {
+  pub time: Duration,
+  pub input: RecordedInput,
+}
+
+/// The [`Message`] variants kept in a [`Recording`], with the same fields.
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum RecordedInput {
+  RawInput(RawInputMessage),
+  CloseRequested,
+  Key {
+    key: Key,
+    physical_key: PhysicalKey,
+    state: KeyState,
+    scan_code: u16,
+    is_extended_key: bool,
+    during_composition: bool,
+    modifiers: Modifiers,
+  },
+  Text(String),
+  ModifiersChanged(Modifiers),
+  MouseButton {
+    button: MouseButton,
+    state: ButtonState,
+    position: PhysicalPosition,
+    is_double_click: bool,
+    click_count: u32,
+    modifiers: Modifiers,
+  },
+  MouseWheel {
+    delta: ScrollDelta,
+    position: PhysicalPosition,
+    modifiers: Modifiers,
+  },
+  CursorMove {
+    position: PhysicalPosition,
+    kind: CursorMoveKind,
+  },
+  Resized(PhysicalSize),
+  Moved(PhysicalPosition),
+  BoundsChanged {
+    outer_position: PhysicalPosition,
+    outer_size: PhysicalSize,
+  },
+  Focus(Focus),
+  ScaleFactorChanged(f64),
+  Gesture(Gesture),
+}
+
+impl RecordedInput {
+  /// The recorded form of `message`, or `None` if it isn't recorded.
+  pub fn from_message(message: &Message) -> Option<Self> {
+    Some(match message.clone() {
+      Message::RawInput(input) => Self::RawInput(input),
+      Message::CloseRequested => Self::CloseRequested,
+      Message::Key {
+        key,
+        physical_key,
+        state,
+        scan_code,
+        is_extended_key,
+        during_composition,
+        modifiers,
+      } => Self::Key {
+        key,
+        physical_key,
+        state,
+        scan_code,
+        is_extended_key,
+        during_composition,
+        modifiers,
+      },
+      Message::Text(text) => Self::Text(text),
+      Message::ModifiersChanged(modifiers) => Self::ModifiersChanged(modifiers),
+      Message::MouseButton {
+        button,
+        state,
+        position,
+        is_double_click,
+        click_count,
+        modifiers,
+      } => Self::MouseButton {
+        button,
+        state,
+        position,
+        is_double_click,
+        click_count,
+        modifiers,
+      },
+      Message::MouseWheel {
+        delta,
+        position,
+        modifiers,
+      } => Self::MouseWheel {
+        delta,
+        position,
+        modifiers,
+      },
+      Message::CursorMove { position, kind } => Self::CursorMove { position, kind },
+      Message::Resized(size) => Self::Resized(size),
+      Message::Moved(position) => Self::Moved(position),
+      Message::BoundsChanged {
+        outer_position,
+        outer_size,
+      } => Self::BoundsChanged {
+        outer_position,
+        outer_size,
+      },
+      Message::Focus(focus) => Self::Focus(focus),
+      Message::ScaleFactorChanged(scale_factor) => Self::ScaleFactorChanged(scale_factor),
+      Message::Gesture(gesture) => Self::Gesture(gesture),
+      _ => return None,
+    })
+  }
+}
+
+impl From<RecordedInput> for Message {
+  fn from(input: RecordedInput) -> Self {
+    match input {
+      RecordedInput::RawInput(input) => Message::RawInput(input),
+      RecordedInput::CloseRequested => Message::CloseRequested,
+      RecordedInput::Key {
+        key,
+        physical_key,
+        state,
+        scan_code,
+        is_extended_key,
+        during_composition,
+        modifiers,
+      } => Message::Key {
+        key,
+        physical_key,
+        state,
+        scan_code,
+        is_extended_key,
+        during_composition,
+        modifiers,
+      },
+      RecordedInput::Text(text) => Message::Text(text),
+      RecordedInput::ModifiersChanged(modifiers) => Message::ModifiersChanged(modifiers),
+      RecordedInput::MouseButton {
+        button,
+        state,
+        position,
+        is_double_click,
+        click_count,
+        modifiers,
+      } => Message::MouseButton {
+        button,
+        state,
+        position,
+        is_double_click,
+        click_count,
+        modifiers,
+      },
+      RecordedInput::MouseWheel {
+        delta,
+        position,
+        modifiers,
+      } => Message::MouseWheel {
+        delta,
+        position,
+        modifiers,
+      },
+      RecordedInput::CursorMove { position, kind } => {
+        Message::CursorMove { position, kind }
+      }
+      RecordedInput::Resized(size) => Message::Resized(size),
+      RecordedInput::Moved(position) => Message::Moved(position),
+      RecordedInput::BoundsChanged {
+        outer_position,
+        outer_size,
+      } => Message::BoundsChanged {
+        outer_position,
+        outer_size,
+      },
+      RecordedInput::Focus(focus) => Message::Focus(focus),
+      RecordedInput::ScaleFactorChanged(scale_factor) => {
+        Message::ScaleFactorChanged(scale_factor)
+      }
+      RecordedInput::Gesture(gesture) => Message::Gesture(gesture),
+    }
+  }
+}
+
+/// Input a window received, recorded with
+/// [`Window::start_recording`](crate::Window::start_recording), such as for
+/// reproducing a bug from a user's session.
+///
+/// Keys, text, mouse, gesture, focus, size, position, scale factor, and close
+/// requests are recorded. With the `serde` feature, recordings can be saved
+/// and loaded with any serde format.
+#[derive(Debug, Default, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Recording {
+  messages: Vec<RecordedMessage>,
+}
+
+impl Recording {
+  pub fn messages(&self) -> &[RecordedMessage] {
+    &self.messages
+  }
+
+  /// Time from the start of the recording to the last message.
+  pub fn duration(&self) -> Duration {
+    self
+      .messages
+      .last()
+      .map(|message| message.time)
+      .unwrap_or_default()
+  }
+
+  /// Feed the messages back to `window` at the pace they were recorded, as if
+  /// the user did it all again. Sent from a background thread, which finishes
+  /// after the last message or once the window is closed.
+  pub fn replay(&self, window: &Window) -> JoinHandle<()> {
+    let hwnd = window.0.hwnd;
+    let messages = self.messages.clone();
+    std::thread::spawn(move || {
+      let start = Instant::now();
+      for RecordedMessage { time, input } in messages {
+        std::thread::sleep((start + time).saturating_duration_since(Instant::now()));
+        // fails once the window is gone
+        if Command::Inject(Box::new(input.into()))
+          .try_post(hwnd)
+          .is_err()
+        {
+          return;
+        }
+      }
+    })
+  }
+
+  /// Feed every message back to `window` right away, in order, such as for
+  /// tests which don't depend on timing.
+  pub fn replay_instantly(&self, window: &Window) {
+    for recorded in &self.messages {
+      window.inject(recorded.input.clone().into());
+    }
+  }
+}
+
+/// Recording in progress for a window.
+#[derive(Debug)]
+pub(crate) struct Recorder {
+  start: Instant,
+  recording: Recording,
+}
+
+impl Recorder {
+  pub fn new() -> Self {
+    Self {
+      start: Instant::now(),
+      recording: Recording::default(),
+    }
+  }
+
+  pub fn record(&mut self, message: &Message) {
+    if let Some(input) = RecordedInput::from_message(message) {
+      self.recording.messages.push(RecordedMessage {
+        time: self.start.elapsed(),
+        input,
+      });
+    }
+  }
+
+  pub fn finish(self) -> Recording {
+    self.recording
+  }
+}
@@ -0,0 +1,168 @@
+use std::{
+  collections::HashMap,
+  sync::{Mutex, OnceLock},
+};
+
+use windows::{
+  core::{HSTRING, PCWSTR},
+  Win32::{
+    Foundation::HINSTANCE,
+    Graphics::Gdi::{DeleteObject, HBRUSH},
+    UI::WindowsAndMessaging::{
+      self,
+      DestroyCursor,
+      DestroyIcon,
+      LoadCursorW,
+      RegisterClassExW,
+      UnregisterClassW,
+      HCURSOR,
+      HICON,
+      WNDCLASSEXW,
+      WNDPROC,
+    },
+  },
+};
+
+use crate::error::WindowError;
+
+/*
+  Window classes are shared by every window registered under the same name and are
+  reference counted here, so that they are unregistered only once the last window
+  using them is dropped. This keeps long-running processes which open and close many
+  windows from leaking classes, and allows several windows to share a class name.
+*/
+
+struct ClassEntry {
+  atom: u16,
+  windows: usize,
+}
+
+fn classes() -> &'static Mutex<HashMap<String, ClassEntry>> {
+  static CLASSES: OnceLock<Mutex<HashMap<String, ClassEntry>>> = OnceLock::new();
+  CLASSES.get_or_init(Default::default)
+}
+
+/// Handle to a registered window class. The class is unregistered when the last
+/// handle with the same name is dropped.
+#[derive(Debug)]
+pub(crate) struct WindowClass {
+  name: String,
+  atom: u16,
+  hinstance: HINSTANCE,
+}
+
+impl WindowClass {
+  pub fn acquire(
+    name: &str,
+    hinstance: HINSTANCE,
+    wnd_proc: WNDPROC,
+  ) -> Result<Self, WindowError> {
+    let mut classes = classes().lock().unwrap();
+
+    if let Some(entry) = classes.get_mut(name) {
+      tracing::trace!("[`{}`]: reusing window class", name);
+      entry.windows += 1;
+      return Ok(Self {
+        name: name.to_owned(),
+        atom: entry.atom,
+        hinstance,
+      });
+    }
+
+    tracing::trace!("[`{}`]: registering window class", name);
+
+    let class_name = HSTRING::from(name);
+    let wc = WNDCLASSEXW {
+      cbSize: std::mem::size_of::<WNDCLASSEXW>() as u32,
+      style: WindowsAndMessaging::CS_VREDRAW
+        | WindowsAndMessaging::CS_HREDRAW
+        | WindowsAndMessaging::CS_DBLCLKS
+        | WindowsAndMessaging::CS_OWNDC,
+      cbWndExtra: std::mem::size_of::<WNDCLASSEXW>() as i32,
+      lpfnWndProc: wnd_proc,
+      hInstance: hinstance,
+      hCursor: unsafe { LoadCursorW(None, WindowsAndMessaging::IDC_ARROW)? },
+      lpszClassName: PCWSTR(class_name.as_ptr()),
+      ..Default::default()
+    };
+
+    let atom = unsafe { RegisterClassExW(&wc) };
+    if atom == 0 {
+      return Err(WindowError::Win32Error(windows::core::Error::from_win32()));
+    }
+
+    classes.insert(name.to_owned(), ClassEntry { atom, windows: 1 });
+
+    Ok(Self {
+      name: name.to_owned(),
+      atom,
+      hinstance,
+    })
+  }
+
+  pub fn atom(&self) -> u16 {
+    self.atom
+  }
+}
+
+impl Drop for WindowClass {
+  fn drop(&mut self) {
+    let mut classes = classes().lock().unwrap();
+    let Some(entry) = classes.get_mut(&self.name) else {
+      return;
+    };
+
+    entry.windows -= 1;
+    if entry.windows > 0 {
+      return;
+    }
+
+    classes.remove(&self.name);
+    tracing::trace!("[`{}`]: unregistering window class", self.name);
+    if let Err(e) =
+      unsafe { UnregisterClassW(PCWSTR(self.atom as *const u16), self.hinstance) }
+    {
+      tracing::error!("{e}");
+    }
+  }
+}
+
+/// A USER or GDI object owned by the crate, such as an icon.
+#[derive(Debug)]
+pub(crate) enum Resource {
+  Icon(HICON),
+  Cursor(HCURSOR),
+  Brush(HBRUSH),
+}
+
+/// Destroys the wrapped object on drop. Share it with [`std::sync::Arc`] so the
+/// object lives exactly as long as the last window (or user handle) using it.
+#[derive(Debug)]
+pub(crate) struct OwnedResource(pub Resource);
+
+impl OwnedResource {
+  /// Handle of an icon or cursor, which share a handle type.
+  pub fn handle(&self) -> HICON {
+    match self.0 {
+      Resource::Icon(hicon) => hicon,
+      Resource::Cursor(hcursor) => HICON(hcursor.0),
+      Resource::Brush(_) => unreachable!("brushes have no icon handle"),
+    }
+  }
+}
+
+impl Drop for OwnedResource {
+  fn drop(&mut self) {
+    let result = match self.0 {
+      Resource::Icon(hicon) => unsafe { DestroyIcon(hicon) },
+      Resource::Cursor(hcursor) => unsafe { DestroyCursor(hcursor) },
+      Resource::Brush(hbrush) => match unsafe { DeleteObject(hbrush) }.as_bool() {
+        true => Ok(()),
+        false => Err(windows::core::Error::from_win32()),
+      },
+    };
+    if let Err(e) = result {
+      tracing::error!("{e}");
+    }
+  }
+}
@@ -0,0 +1,80 @@
+use std::{
+  fmt::Debug,
+  sync::{Arc, Condvar, Mutex},
+  time::Duration,
+};
+
+/// Longest the window waits for deferred resizes, so a renderer which never
+/// finishes can't freeze it.
+const RESIZE_TIMEOUT: Duration = Duration::from_millis(100);
+
+#[derive(Default)]
+struct Handshake {
+  deferred: Mutex<usize>,
+  finished: Condvar,
+}
+
+/// Sent with [`Message::Resizing`](crate::Message::Resizing) while the window
+/// waits to show its new size.
+///
+/// The window continues once the next message is asked for, so resizing the
+/// swapchain while handling the message is enough. A renderer on another
+/// thread can hold the window back with [`ResizeSignal::defer`] instead,
+/// until it has resized and presented.
+#[derive(Clone, Default)]
+pub struct ResizeSignal(Arc<Handshake>);
+
+impl ResizeSignal {
+  /// Keep the window waiting until the returned [`ResizeCompletion`] is
+  /// [`done`](ResizeCompletion::done) or dropped, for up to 100 ms.
+  pub fn defer(&self) -> ResizeCompletion {
+    *self.0.deferred.lock().unwrap() += 1;
+    ResizeCompletion(self.0.clone())
+  }
+
+  /// Wait for the deferred resizes to finish. Called by the window thread.
+  pub(crate) fn wait(&self) {
+    let deferred = self.0.deferred.lock().unwrap();
+    let _ = self
+      .0
+      .finished
+      .wait_timeout_while(deferred, RESIZE_TIMEOUT, |deferred| *deferred > 0)
+      .unwrap();
+  }
+}
+
+impl PartialEq for ResizeSignal {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.0, &other.0)
+  }
+}
+
+impl Debug for ResizeSignal {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("ResizeSignal")
+      .field("deferred", &*self.0.deferred.lock().unwrap())
+      .finish()
+  }
+}
+
+/// Releases the window waiting on a deferred [`ResizeSignal`] when dropped.
+#[must_use = "the window continues as soon as this is dropped"]
+pub struct ResizeCompletion(Arc<Handshake>);
+
+impl ResizeCompletion {
+  /// Let the window show its new size.
+  pub fn done(self) {}
+}
+
+impl Drop for ResizeCompletion {
+  fn drop(&mut self) {
+    *self.0.deferred.lock().unwrap() -= 1;
+    self.0.finished.notify_all();
+  }
+}
+
+impl Debug for ResizeCompletion {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_struct("ResizeCompletion").finish_non_exhaustive()
+  }
+}
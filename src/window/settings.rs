@@ -1,5 +1,26 @@
+use std::{sync::Arc, time::Duration};
+
+use windows::Win32::Foundation::HWND;
+
 use super::{
-  data::{CursorMode, Flow, Fullscreen, LogicalSize, Position, Size, Theme, Visibility},
+  data::{
+    Backdrop,
+    Color,
+    CursorMode,
+    Flow,
+    Fullscreen,
+    LogicalSize,
+    Position,
+    Size,
+    Theme,
+    Visibility,
+  },
+  icon::Icon,
+  input::state::KeyRepeat,
+  journal::JournalSink,
+  thread::WindowThread,
+  translator::{MessageFilter, MessageReplier, MessageTranslator},
+  tray::TrayBehavior,
   Window,
 };
 use crate::error::WindowError;
@@ -9,9 +30,40 @@ use crate::error::WindowError;
 pub struct WindowSettings {
   pub flow: Flow,
   pub theme: Theme,
+  pub backdrop: Backdrop,
+  pub caption_color: Option<Color>,
+  pub caption_text_color: Option<Color>,
+  pub border_color: Option<Color>,
+  pub icon: Option<Icon>,
+  pub parent: Option<HWND>,
+  /// Whether `parent` belongs to another framework or process.
+  pub embedded: bool,
+  pub owner: Option<HWND>,
+  pub class_name: Option<String>,
+  pub app_id: Option<String>,
+  pub tray_behavior: TrayBehavior,
+  pub message_translator: Option<Arc<dyn MessageTranslator>>,
+  pub message_filter: Option<Arc<dyn MessageFilter>>,
+  pub message_replier: Option<Arc<dyn MessageReplier>>,
+  pub journal: Option<(Arc<dyn JournalSink>, Duration)>,
+  pub thread: Option<WindowThread>,
+  pub background_interval: Option<Duration>,
+  pub key_repeat: KeyRepeat,
+  pub gestures: bool,
+  pub joysticks: bool,
+  pub idle_threshold: Option<Duration>,
+  pub frame_pacing: bool,
+  pub visible_after_first_draw: bool,
+  pub no_redirection_bitmap: bool,
   pub visibility: Visibility,
   pub decorations: Visibility,
   pub resizeable: bool,
+  pub maximized: bool,
+  pub minimized: bool,
+  pub skip_taskbar: bool,
+  pub active: bool,
+  pub no_activate: bool,
+  pub double_clicks: bool,
   pub fullscreen: Option<Fullscreen>,
   pub cursor_mode: CursorMode,
   pub close_on_x: bool,
@@ -21,21 +73,81 @@ impl Default for WindowSettings {
   fn default() -> Self {
     let flow = Flow::default();
     let theme = Theme::default();
+    let backdrop = Backdrop::default();
+    let caption_color = None;
+    let caption_text_color = None;
+    let border_color = None;
+    let icon = None;
+    let parent = None;
+    let embedded = false;
+    let owner = None;
+    let class_name = None;
+    let app_id = None;
+    let tray_behavior = TrayBehavior::default();
+    let message_translator = None;
+    let message_filter = None;
+    let message_replier = None;
+    let journal = None;
+    let thread = None;
+    let background_interval = Some(Duration::from_millis(100));
+    let key_repeat = KeyRepeat::default();
+    let gestures = false;
+    let joysticks = false;
+    let idle_threshold = None;
+    let frame_pacing = false;
+    let visible_after_first_draw = false;
+    let no_redirection_bitmap = false;
     let fullscreen = None;
     let cursor_mode = CursorMode::default();
     let visibility = Visibility::default();
     let decorations = Visibility::default();
     let resizeable = true;
+    let maximized = false;
+    let minimized = false;
+    let skip_taskbar = false;
+    let active = true;
+    let no_activate = false;
+    let double_clicks = true;
     let close_on_x = true;
 
     Self {
       flow,
       theme,
+      backdrop,
+      caption_color,
+      caption_text_color,
+      border_color,
+      icon,
+      parent,
+      embedded,
+      owner,
+      class_name,
+      app_id,
+      tray_behavior,
+      message_translator,
+      message_filter,
+      message_replier,
+      journal,
+      thread,
+      background_interval,
+      key_repeat,
+      gestures,
+      joysticks,
+      idle_threshold,
+      frame_pacing,
+      visible_after_first_draw,
+      no_redirection_bitmap,
       visibility,
       decorations,
       close_on_x,
       fullscreen,
       resizeable,
+      maximized,
+      minimized,
+      skip_taskbar,
+      active,
+      no_activate,
+      double_clicks,
       cursor_mode,
     }
   }
@@ -52,6 +164,138 @@ impl WindowSettings {
     self
   }
 
+  pub fn with_backdrop(mut self, backdrop: Backdrop) -> Self {
+    self.backdrop = backdrop;
+    self
+  }
+
+  pub fn with_caption_color(mut self, color: impl Into<Option<Color>>) -> Self {
+    self.caption_color = color.into();
+    self
+  }
+
+  pub fn with_caption_text_color(mut self, color: impl Into<Option<Color>>) -> Self {
+    self.caption_text_color = color.into();
+    self
+  }
+
+  pub fn with_border_color(mut self, color: impl Into<Option<Color>>) -> Self {
+    self.border_color = color.into();
+    self
+  }
+
+  pub fn with_icon(mut self, icon: impl Into<Option<Icon>>) -> Self {
+    self.icon = icon.into();
+    self
+  }
+
+  pub fn with_class_name(mut self, class_name: impl Into<Option<String>>) -> Self {
+    self.class_name = class_name.into();
+    self
+  }
+
+  pub fn with_app_id(mut self, app_id: impl Into<Option<String>>) -> Self {
+    self.app_id = app_id.into();
+    self
+  }
+
+  pub fn with_parent(mut self, parent: Option<&Window>) -> Self {
+    self.parent = parent.map(|parent| parent.0.hwnd);
+    self.embedded = false;
+    self
+  }
+
+  pub fn with_embed_parent(mut self, parent: Option<HWND>) -> Self {
+    self.parent = parent;
+    self.embedded = parent.is_some();
+    self
+  }
+
+  pub fn with_owner(mut self, owner: Option<&Window>) -> Self {
+    self.owner = owner.map(|owner| owner.0.hwnd);
+    self
+  }
+
+  pub fn with_tray_behavior(mut self, tray_behavior: TrayBehavior) -> Self {
+    self.tray_behavior = tray_behavior;
+    self
+  }
+
+  pub fn with_message_translator(
+    mut self,
+    translator: impl MessageTranslator + 'static,
+  ) -> Self {
+    self.message_translator = Some(Arc::new(translator));
+    self
+  }
+
+  pub fn with_message_filter(mut self, filter: impl MessageFilter + 'static) -> Self {
+    self.message_filter = Some(Arc::new(filter));
+    self
+  }
+
+  pub fn with_message_replier(mut self, replier: impl MessageReplier + 'static) -> Self {
+    self.message_replier = Some(Arc::new(replier));
+    self
+  }
+
+  pub fn with_journal(
+    mut self,
+    sink: impl JournalSink + 'static,
+    debounce: Duration,
+  ) -> Self {
+    self.journal = Some((Arc::new(sink), debounce));
+    self
+  }
+
+  pub fn with_thread(mut self, thread: &WindowThread) -> Self {
+    self.thread = Some(thread.clone());
+    self
+  }
+
+  pub fn with_background_interval(
+    mut self,
+    interval: impl Into<Option<Duration>>,
+  ) -> Self {
+    self.background_interval = interval.into();
+    self
+  }
+
+  pub fn with_key_repeat(mut self, key_repeat: KeyRepeat) -> Self {
+    self.key_repeat = key_repeat;
+    self
+  }
+
+  pub fn with_gestures(mut self, gestures: bool) -> Self {
+    self.gestures = gestures;
+    self
+  }
+
+  pub fn with_joysticks(mut self, joysticks: bool) -> Self {
+    self.joysticks = joysticks;
+    self
+  }
+
+  pub fn with_idle_threshold(mut self, threshold: impl Into<Option<Duration>>) -> Self {
+    self.idle_threshold = threshold.into();
+    self
+  }
+
+  pub fn with_frame_pacing(mut self, frame_pacing: bool) -> Self {
+    self.frame_pacing = frame_pacing;
+    self
+  }
+
+  pub fn with_visible_after_first_draw(mut self, visible_after_first_draw: bool) -> Self {
+    self.visible_after_first_draw = visible_after_first_draw;
+    self
+  }
+
+  pub fn with_no_redirection_bitmap(mut self, no_redirection_bitmap: bool) -> Self {
+    self.no_redirection_bitmap = no_redirection_bitmap;
+    self
+  }
+
   pub fn with_visibility(mut self, visibility: Visibility) -> Self {
     self.visibility = visibility;
     self
@@ -82,6 +326,36 @@ impl WindowSettings {
     self.resizeable = resizeable;
     self
   }
+
+  pub fn with_maximized(mut self, maximized: bool) -> Self {
+    self.maximized = maximized;
+    self
+  }
+
+  pub fn with_minimized(mut self, minimized: bool) -> Self {
+    self.minimized = minimized;
+    self
+  }
+
+  pub fn with_skip_taskbar(mut self, skip_taskbar: bool) -> Self {
+    self.skip_taskbar = skip_taskbar;
+    self
+  }
+
+  pub fn with_active(mut self, active: bool) -> Self {
+    self.active = active;
+    self
+  }
+
+  pub fn with_no_activate(mut self, no_activate: bool) -> Self {
+    self.no_activate = no_activate;
+    self
+  }
+
+  pub fn with_double_clicks(mut self, double_clicks: bool) -> Self {
+    self.double_clicks = double_clicks;
+    self
+  }
 }
 
 pub struct WindowBuilder {
@@ -142,6 +416,223 @@ impl WindowBuilder {
     self
   }
 
+  pub fn with_backdrop(mut self, backdrop: Backdrop) -> Self {
+    self.settings = self.settings.with_backdrop(backdrop);
+    self
+  }
+
+  /// Tint the native titlebar. Requires Windows 11.
+  pub fn with_caption_color(mut self, color: impl Into<Option<Color>>) -> Self {
+    self.settings = self.settings.with_caption_color(color);
+    self
+  }
+
+  /// Color of the titlebar text. Requires Windows 11.
+  pub fn with_caption_text_color(mut self, color: impl Into<Option<Color>>) -> Self {
+    self.settings = self.settings.with_caption_text_color(color);
+    self
+  }
+
+  /// Color of the window border. Requires Windows 11.
+  pub fn with_border_color(mut self, color: impl Into<Option<Color>>) -> Self {
+    self.settings = self.settings.with_border_color(color);
+    self
+  }
+
+  /// Icon shown in the titlebar and taskbar.
+  pub fn with_icon(mut self, icon: impl Into<Option<Icon>>) -> Self {
+    self.settings = self.settings.with_icon(icon);
+    self
+  }
+
+  /// Name of the window class to register the window under, which other
+  /// programs such as `FindWindowW` and automation tools see. Windows with the
+  /// same class name share one class, registered for the first and
+  /// unregistered once the last is destroyed. Defaults to the title.
+  pub fn with_class_name(mut self, class_name: impl Into<String>) -> Self {
+    self.settings = self.settings.with_class_name(class_name.into());
+    self
+  }
+
+  /// AppUserModelID to group the window under in the taskbar, such as
+  /// `"Company.App"`, for apps which weren't given one by an installer. The
+  /// whole process takes it on too, which notifications and
+  /// [jump lists](crate::Window::set_jump_list) are attributed to. Pinned
+  /// shortcuts only group with the window if they have the same id.
+  pub fn with_app_id(mut self, app_id: impl Into<String>) -> Self {
+    self.settings = self.settings.with_app_id(app_id.into());
+    self
+  }
+
+  /// Create the window inside the client area of `parent`, such as a render
+  /// view embedded in an editor's UI. The window has no frame, is positioned
+  /// relative to the parent's client area, moves with the parent, and is
+  /// destroyed along with it. It receives its own input while the cursor is
+  /// over it and takes keyboard focus when clicked.
+  ///
+  /// Iterate the messages of both windows, as the parent and child share
+  /// their input and wait on each other.
+  pub fn with_parent(mut self, parent: &Window) -> Self {
+    self.settings = self.settings.with_parent(Some(parent));
+    self
+  }
+
+  /// Create the window inside the client area of a window which doesn't
+  /// belong to this crate, such as the editor view a plugin host hands to a
+  /// plugin, or a viewport in another UI framework. Behaves like
+  /// [`WindowBuilder::with_parent`], except that the window takes on the DPI
+  /// awareness of `parent`, so sizes are in the same units the host uses, and
+  /// the process's DPI awareness is left to the host. Resize the window when
+  /// the host asks, as it won't follow the parent's size on its own.
+  ///
+  /// Building fails if `parent` isn't a Win32 handle to an existing window.
+  #[cfg(feature = "rwh_06")]
+  pub fn with_embed_parent(mut self, parent: rwh_06::RawWindowHandle) -> Self {
+    let parent = match parent {
+      rwh_06::RawWindowHandle::Win32(handle) => HWND(handle.hwnd.get()),
+      // refused once the window is built
+      _ => HWND(0),
+    };
+    self.settings = self.settings.with_embed_parent(Some(parent));
+    self
+  }
+
+  /// Make `owner` own the window, such as for a dialog or tool palette. The
+  /// window stays above its owner, is hidden while the owner is minimized,
+  /// and is destroyed along with it. Ignored for child windows, see
+  /// [`WindowBuilder::with_parent`].
+  pub fn with_owner(mut self, owner: &Window) -> Self {
+    self.settings = self.settings.with_owner(Some(owner));
+    self
+  }
+
+  /// Hide the window to the notification area when minimized and/or closed.
+  pub fn with_tray_behavior(mut self, tray_behavior: TrayBehavior) -> Self {
+    self.settings = self.settings.with_tray_behavior(tray_behavior);
+    self
+  }
+
+  /// Translate raw window messages before the built-in translation runs. See
+  /// [`MessageTranslator`].
+  pub fn with_message_translator(
+    mut self,
+    translator: impl MessageTranslator + 'static,
+  ) -> Self {
+    self.settings = self.settings.with_message_translator(translator);
+    self
+  }
+
+  /// Filter raw window messages before the crate handles them, starting
+  /// right after `WM_CREATE`, before the window is first shown. See
+  /// [`MessageFilter`].
+  pub fn with_message_filter(mut self, filter: impl MessageFilter + 'static) -> Self {
+    self.settings = self.settings.with_message_filter(filter);
+    self
+  }
+
+  /// Answer raw window messages the crate leaves to `DefWindowProc`. Set
+  /// here rather than with
+  /// [`Window::set_message_replier`](crate::Window::set_message_replier), the
+  /// frame is recalculated once the window is created, so a custom frame from
+  /// its `WM_NCCALCSIZE` answer is in place before the window is first shown.
+  /// See [`MessageReplier`].
+  pub fn with_message_replier(mut self, replier: impl MessageReplier + 'static) -> Self {
+    self.settings = self.settings.with_message_replier(replier);
+    self
+  }
+
+  /// Write the window's placement and key settings to `sink` whenever they
+  /// change, waiting until they have been unchanged for `debounce` so drags
+  /// and resizes are written once. See [`JournalSink`].
+  pub fn with_journal(
+    mut self,
+    sink: impl JournalSink + 'static,
+    debounce: Duration,
+  ) -> Self {
+    self.settings = self.settings.with_journal(sink, debounce);
+    self
+  }
+
+  /// Run the window on a shared [`WindowThread`] instead of spawning a thread
+  /// for it.
+  pub fn with_thread(mut self, thread: &WindowThread) -> Self {
+    self.settings = self.settings.with_thread(thread);
+    self
+  }
+
+  /// How long to wait for messages while hidden or minimized with
+  /// [`Flow::Poll`]. Defaults to 100ms. See
+  /// [`Window::set_background_interval`].
+  pub fn with_background_interval(
+    mut self,
+    interval: impl Into<Option<Duration>>,
+  ) -> Self {
+    self.settings = self.settings.with_background_interval(interval);
+    self
+  }
+
+  /// Which auto-repeat key messages are sent. Defaults to
+  /// [`KeyRepeat::Enabled`]. See [`Window::set_key_repeat`].
+  pub fn with_key_repeat(mut self, key_repeat: KeyRepeat) -> Self {
+    self.settings = self.settings.with_key_repeat(key_repeat);
+    self
+  }
+
+  /// Recognize touch gestures (pinch, pan, rotate, and taps) and send them
+  /// as [`Message::Gesture`](crate::Message::Gesture). Defaults to `false`.
+  pub fn with_gestures(mut self, gestures: bool) -> Self {
+    self.settings = self.settings.with_gestures(gestures);
+    self
+  }
+
+  /// Read joysticks, flight sticks, wheels and other HID game controllers
+  /// through raw input and send them as
+  /// [`Message::Joystick`](crate::Message::Joystick). Unlike XInput this has no
+  /// limit on the number of devices, but Xbox controllers are reported here
+  /// too, with both triggers combined on one axis. Defaults to `false`.
+  pub fn with_joysticks(mut self, joysticks: bool) -> Self {
+    self.settings = self.settings.with_joysticks(joysticks);
+    self
+  }
+
+  /// Send [`Message::UserIdle`](crate::Message::UserIdle) once there has been
+  /// no keyboard or mouse input to any app for `threshold`, and
+  /// [`Message::UserActive`](crate::Message::UserActive) when input resumes.
+  /// Defaults to `None`. See [`Window::set_idle_threshold`].
+  pub fn with_idle_threshold(mut self, threshold: impl Into<Option<Duration>>) -> Self {
+    self.settings = self.settings.with_idle_threshold(threshold);
+    self
+  }
+
+  /// Deliver [`Message::Paint`](crate::Message::Paint) at most once per
+  /// display refresh. Defaults to `false`. See [`Window::set_frame_pacing`].
+  pub fn with_frame_pacing(mut self, frame_pacing: bool) -> Self {
+    self.settings = self.settings.with_frame_pacing(frame_pacing);
+    self
+  }
+
+  /// Build the window hidden and show it once
+  /// [`Window::notify_first_frame`] is called.
+  pub fn with_visible_after_first_draw(mut self, visible_after_first_draw: bool) -> Self {
+    self.settings = self
+      .settings
+      .with_visible_after_first_draw(visible_after_first_draw);
+    self
+  }
+
+  /// Create the window without the bitmap Windows normally draws its client
+  /// area into, for windows whose content is entirely hosted with
+  /// DirectComposition, such as through `Composition` with the `composition`
+  /// feature. Avoids a copy per frame and allows transparent swapchains, but
+  /// GDI drawing such as [`Window::paint`] no longer shows. Can't be changed
+  /// after the window is built. Defaults to `false`.
+  pub fn with_no_redirection_bitmap(mut self, no_redirection_bitmap: bool) -> Self {
+    self.settings = self
+      .settings
+      .with_no_redirection_bitmap(no_redirection_bitmap);
+    self
+  }
+
   pub fn with_visibility(mut self, visibility: Visibility) -> Self {
     self.settings = self.settings.with_visibility(visibility);
     self
@@ -173,6 +664,55 @@ impl WindowBuilder {
     self
   }
 
+  /// Show the window maximized the first time it's shown, rather than
+  /// showing it at its size first. Defaults to `false`.
+  pub fn with_maximized(mut self, maximized: bool) -> Self {
+    self.settings = self.settings.with_maximized(maximized);
+    self
+  }
+
+  /// Show the window minimized the first time it's shown. Takes precedence
+  /// over [`WindowBuilder::with_maximized`]. Defaults to `false`.
+  pub fn with_minimized(mut self, minimized: bool) -> Self {
+    self.settings = self.settings.with_minimized(minimized);
+    self
+  }
+
+  /// Leave the window out of the taskbar and Alt+Tab as a tool window, such
+  /// as for palettes and popups. Tool windows have a smaller titlebar.
+  /// Defaults to `false`. See [`Window::set_skip_taskbar`].
+  pub fn with_skip_taskbar(mut self, skip_taskbar: bool) -> Self {
+    self.settings = self.settings.with_skip_taskbar(skip_taskbar);
+    self
+  }
+
+  /// Whether showing the window for the first time activates it, taking focus
+  /// from the app the user is in. Pass `false` for windows which shouldn't
+  /// interrupt, such as secondary tool windows. Maximized windows are always
+  /// activated. Defaults to `true`.
+  pub fn with_active(mut self, active: bool) -> Self {
+    self.settings = self.settings.with_active(active);
+    self
+  }
+
+  /// Never activate the window, neither when it's shown nor when it's clicked,
+  /// such as for notification popups. It still receives mouse input, but no
+  /// keyboard input. Can't be changed after the window is built. Defaults to
+  /// `false`.
+  pub fn with_no_activate(mut self, no_activate: bool) -> Self {
+    self.settings = self.settings.with_no_activate(no_activate);
+    self
+  }
+
+  /// Whether a second press within the double-click time and area is reported
+  /// as a double click. Pass `false` to get plain presses and releases
+  /// instead, such as for apps counting clicks themselves; `click_count` on
+  /// `Message::MouseButton` keeps counting either way. Defaults to `true`.
+  pub fn with_double_clicks(mut self, double_clicks: bool) -> Self {
+    self.settings = self.settings.with_double_clicks(double_clicks);
+    self
+  }
+
   pub fn build(self) -> Result<Window, WindowError> {
     Window::new(self.title, self.size, self.position, self.settings)
   }
@@ -0,0 +1,30 @@
+use std::path::Path;
+
+use windows::{
+  core::HSTRING,
+  Win32::UI::Shell::{SHAddToRecentDocs, SHARD_PATHW},
+};
+
+use crate::error::WindowError;
+
+/// Add the file at `path` to the user's recent documents, listed in the
+/// app's [jump list](crate::Window::set_jump_list) with
+/// [`JumpList::with_recent`](crate::JumpList::with_recent) and in the Start
+/// menu. Call this whenever the app opens or saves a file. The file is
+/// attributed to the process's AppUserModelID, see
+/// [`WindowBuilder::with_app_id`](crate::WindowBuilder::with_app_id).
+pub fn add_to_recent_docs(path: impl AsRef<Path>) -> Result<(), WindowError> {
+  // the shell expects a full path
+  let path = std::path::absolute(path)?;
+  if !path.is_file() {
+    return Err(WindowError::Error(format!("`{}` is not a file", path.display())));
+  }
+  let path = HSTRING::from(path.to_string_lossy().as_ref());
+  unsafe { SHAddToRecentDocs(SHARD_PATHW.0 as u32, Some(path.as_ptr().cast())) };
+  Ok(())
+}
+
+/// Clear the user's recent documents, for every app.
+pub fn clear_recent_docs() {
+  unsafe { SHAddToRecentDocs(SHARD_PATHW.0 as u32, None) };
+}
@@ -0,0 +1,148 @@
+use windows::Win32::{
+  Foundation::{HWND, RECT},
+  Graphics::{
+    Dwm::{self, DwmGetWindowAttribute},
+    Gdi::{self, MonitorFromWindow},
+  },
+  UI::WindowsAndMessaging::{
+    self,
+    GetWindowRect,
+    IsIconic,
+    IsZoomed,
+    SetWindowPos,
+    ShowWindow,
+  },
+};
+
+use crate::utilities::Monitor;
+
+/// Region of a monitor's work area a window can be snapped to, matching the
+/// layouts offered by Snap in Windows.
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum SnapZone {
+  Left,
+  Right,
+  TopLeft,
+  TopRight,
+  BottomLeft,
+  BottomRight,
+}
+
+impl SnapZone {
+  pub const ALL: [SnapZone; 6] = [
+    SnapZone::Left,
+    SnapZone::Right,
+    SnapZone::TopLeft,
+    SnapZone::TopRight,
+    SnapZone::BottomLeft,
+    SnapZone::BottomRight,
+  ];
+
+  fn rect(self, work_area: RECT) -> RECT {
+    let mid_x = work_area.left + (work_area.right - work_area.left) / 2;
+    let mid_y = work_area.top + (work_area.bottom - work_area.top) / 2;
+    let (left, right) = match self {
+      SnapZone::Left | SnapZone::TopLeft | SnapZone::BottomLeft => {
+        (work_area.left, mid_x)
+      }
+      SnapZone::Right | SnapZone::TopRight | SnapZone::BottomRight => {
+        (mid_x, work_area.right)
+      }
+    };
+    let (top, bottom) = match self {
+      SnapZone::Left | SnapZone::Right => (work_area.top, work_area.bottom),
+      SnapZone::TopLeft | SnapZone::TopRight => (work_area.top, mid_y),
+      SnapZone::BottomLeft | SnapZone::BottomRight => (mid_y, work_area.bottom),
+    };
+    RECT {
+      left,
+      top,
+      right,
+      bottom,
+    }
+  }
+}
+
+/// Windows rounds odd work area sizes differently depending on the zone.
+const TOLERANCE: i32 = 2;
+
+fn work_area(hwnd: HWND) -> RECT {
+  let monitor =
+    Monitor::new(unsafe { MonitorFromWindow(hwnd, Gdi::MONITOR_DEFAULTTONEAREST) });
+  let position = monitor.work_area_position();
+  let size = monitor.work_area_size();
+  RECT {
+    left: position.x,
+    top: position.y,
+    right: position.x + size.width as i32,
+    bottom: position.y + size.height as i32,
+  }
+}
+
+/// Bounds of the visible frame, excluding the invisible resize borders.
+fn frame_bounds(hwnd: HWND) -> Option<RECT> {
+  let mut rect = RECT::default();
+  unsafe {
+    DwmGetWindowAttribute(
+      hwnd,
+      Dwm::DWMWA_EXTENDED_FRAME_BOUNDS,
+      std::ptr::addr_of_mut!(rect) as _,
+      std::mem::size_of::<RECT>() as u32,
+    )
+  }
+  .ok()?;
+  Some(rect)
+}
+
+pub(crate) fn snap_zone(hwnd: HWND) -> Option<SnapZone> {
+  if unsafe { IsZoomed(hwnd) }.as_bool() || unsafe { IsIconic(hwnd) }.as_bool() {
+    return None;
+  }
+
+  let frame = frame_bounds(hwnd)?;
+  let work_area = work_area(hwnd);
+  let close = |a: i32, b: i32| (a - b).abs() <= TOLERANCE;
+
+  SnapZone::ALL.into_iter().find(|zone| {
+    let rect = zone.rect(work_area);
+    close(rect.left, frame.left)
+      && close(rect.top, frame.top)
+      && close(rect.right, frame.right)
+      && close(rect.bottom, frame.bottom)
+  })
+}
+
+pub(crate) fn snap(hwnd: HWND, zone: SnapZone) {
+  if unsafe { IsZoomed(hwnd) }.as_bool() || unsafe { IsIconic(hwnd) }.as_bool() {
+    unsafe { ShowWindow(hwnd, WindowsAndMessaging::SW_RESTORE) };
+  }
+
+  // grow the target by the invisible borders so the visible frame fills the zone
+  let mut outer = RECT::default();
+  if let Err(e) = unsafe { GetWindowRect(hwnd, &mut outer) } {
+    tracing::error!("{e}");
+    return;
+  }
+  let frame = frame_bounds(hwnd).unwrap_or(outer);
+  let target = zone.rect(work_area(hwnd));
+
+  let left = target.left - (frame.left - outer.left);
+  let top = target.top - (frame.top - outer.top);
+  let right = target.right + (outer.right - frame.right);
+  let bottom = target.bottom + (outer.bottom - frame.bottom);
+
+  if let Err(e) = unsafe {
+    SetWindowPos(
+      hwnd,
+      None,
+      left,
+      top,
+      right - left,
+      bottom - top,
+      WindowsAndMessaging::SWP_NOZORDER | WindowsAndMessaging::SWP_NOACTIVATE,
+    )
+  } {
+    tracing::error!("{e}");
+  }
+}
@@ -1,4 +1,8 @@
+use std::sync::atomic::{AtomicU8, Ordering};
+
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[repr(u8)]
 pub enum Stage {
   Setup,
   Ready,
@@ -7,3 +11,46 @@ pub enum Stage {
   ExitLoop,
   Destroyed,
 }
+
+/// [`Stage`] readable and writable from either thread without taking the
+/// window's data lock, as it is checked on every message.
+#[derive(Debug)]
+pub(crate) struct AtomicStage(AtomicU8);
+
+impl AtomicStage {
+  pub fn new(stage: Stage) -> Self {
+    Self(AtomicU8::new(stage as u8))
+  }
+
+  pub fn load(&self) -> Stage {
+    Self::from_u8(self.0.load(Ordering::Acquire))
+  }
+
+  pub fn store(&self, stage: Stage) {
+    self.0.store(stage as u8, Ordering::Release);
+  }
+
+  /// Set the stage, returning the previous one.
+  pub fn swap(&self, stage: Stage) -> Stage {
+    Self::from_u8(self.0.swap(stage as u8, Ordering::AcqRel))
+  }
+
+  /// Move from `current` to `new`, returning whether the stage was `current`.
+  pub fn transition(&self, current: Stage, new: Stage) -> bool {
+    self
+      .0
+      .compare_exchange(current as u8, new as u8, Ordering::AcqRel, Ordering::Acquire)
+      .is_ok()
+  }
+
+  fn from_u8(value: u8) -> Stage {
+    match value {
+      0 => Stage::Setup,
+      1 => Stage::Ready,
+      2 => Stage::Looping,
+      3 => Stage::Closing,
+      4 => Stage::ExitLoop,
+      _ => Stage::Destroyed,
+    }
+  }
+}
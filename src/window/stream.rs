@@ -0,0 +1,66 @@
+use std::{
+  future::Future,
+  pin::Pin,
+  task::{Context, Poll},
+};
+
+use super::{message::Message, Window};
+
+/// Timer waking a task waiting for a message under
+/// [`Flow::WaitUntil`](crate::Flow::WaitUntil).
+pub(crate) const WAKE_TIMER_ID: usize = 0x7761;
+
+/// Future returned by [`Window::next_message`].
+pub struct NextMessage<'a> {
+  window: &'a Window,
+  started: bool,
+}
+
+impl<'a> NextMessage<'a> {
+  pub(crate) fn new(window: &'a Window) -> Self {
+    Self {
+      window,
+      started: false,
+    }
+  }
+}
+
+impl Future for NextMessage<'_> {
+  type Output = Option<Message>;
+
+  fn poll(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+    let this = &mut *self;
+    this.window.poll_next_message(&mut this.started, cx)
+  }
+}
+
+/// Stream of the messages of a window, returned by [`Window::messages`]. Ends
+/// once the window has closed.
+#[cfg(feature = "async")]
+pub struct MessageStream<'a> {
+  window: &'a Window,
+  started: bool,
+}
+
+#[cfg(feature = "async")]
+impl<'a> MessageStream<'a> {
+  pub(crate) fn new(window: &'a Window) -> Self {
+    Self {
+      window,
+      started: false,
+    }
+  }
+}
+
+#[cfg(feature = "async")]
+impl futures_core::Stream for MessageStream<'_> {
+  type Item = Message;
+
+  fn poll_next(
+    mut self: Pin<&mut Self>,
+    cx: &mut Context<'_>,
+  ) -> Poll<Option<Self::Item>> {
+    let this = &mut *self;
+    this.window.poll_next_message(&mut this.started, cx)
+  }
+}
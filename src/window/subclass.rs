@@ -0,0 +1,142 @@
+use std::{
+  panic::{self, AssertUnwindSafe},
+  sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+  },
+};
+
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, LRESULT, WPARAM},
+  UI::{
+    Shell::{
+      DefSubclassProc,
+      GetWindowSubclass,
+      RemoveWindowSubclass,
+      SetWindowSubclass,
+    },
+    WindowsAndMessaging,
+  },
+};
+
+use super::{command::PendingSubclass, procedure};
+
+/*
+  Subclasses are installed with `SetWindowSubclass`, which chains them in front of the
+  crate's window procedure: the subclass added last sees each message first, and passing
+  a message on hands it to the one added before it, ending with the crate. Each subclass
+  holds a leaked box of its handler as its reference data, released once the subclass is
+  removed or the window is destroyed.
+*/
+
+/// Window procedure chained in front of the crate's own, for libraries which
+/// need to handle a window's messages themselves, such as debug overlays or
+/// input method helpers. Added with
+/// [`Window::add_subclass`](crate::Window::add_subclass).
+///
+/// Returning `None` passes the message on to the next subclass, or to the
+/// crate once there are none left. Returning `Some` answers Windows with the
+/// result instead, and neither the crate nor Windows handles the message, so
+/// skipping messages the crate relies on leaves its state out of date. The
+/// crate's own internal messages are never passed to subclasses.
+///
+/// Subclasses are called on the window thread, and must not block on the
+/// window's iterator.
+///
+/// Closures with the same signature as [`Subclass::window_proc`] implement
+/// this trait.
+pub trait Subclass: Send + Sync {
+  fn window_proc(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> Option<LRESULT>;
+}
+
+impl<F> Subclass for F
+where
+  F: Fn(HWND, u32, WPARAM, LPARAM) -> Option<LRESULT> + Send + Sync,
+{
+  fn window_proc(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> Option<LRESULT> {
+    self(hwnd, msg, wparam, lparam)
+  }
+}
+
+impl std::fmt::Debug for dyn Subclass {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("Subclass")
+  }
+}
+
+/// Identifies a [`Subclass`] added to a window, to remove it with
+/// [`Window::remove_subclass`](crate::Window::remove_subclass).
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub struct SubclassId(usize);
+
+impl SubclassId {
+  pub(crate) fn next() -> Self {
+    static NEXT: AtomicUsize = AtomicUsize::new(1);
+    Self(NEXT.fetch_add(1, Ordering::Relaxed))
+  }
+}
+
+/// Chain a subclass in front of the window procedure. Must be called on the
+/// window's thread.
+pub(crate) fn install(hwnd: HWND, pending: PendingSubclass) {
+  let data = Box::into_raw(Box::new(pending.subclass)) as usize;
+  if !unsafe { SetWindowSubclass(hwnd, Some(subclass_proc), pending.id.0, data) }
+    .as_bool()
+  {
+    tracing::error!("failed to add subclass {:?}", pending.id);
+    drop(unsafe { Box::from_raw(data as *mut Arc<dyn Subclass>) });
+  }
+}
+
+/// Remove a subclass added with [`install`], if it's still there. Must be
+/// called on the window's thread.
+pub(crate) fn remove(hwnd: HWND, id: SubclassId) {
+  let mut data = 0;
+  if !unsafe { GetWindowSubclass(hwnd, Some(subclass_proc), id.0, Some(&mut data)) }
+    .as_bool()
+  {
+    return;
+  }
+  unsafe { RemoveWindowSubclass(hwnd, Some(subclass_proc), id.0) };
+  drop(unsafe { Box::from_raw(data as *mut Arc<dyn Subclass>) });
+}
+
+unsafe extern "system" fn subclass_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+  id: usize,
+  data: usize,
+) -> LRESULT {
+  if msg == WindowsAndMessaging::WM_NCDESTROY {
+    remove(hwnd, SubclassId(id));
+    return unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) };
+  }
+  if procedure::is_internal_message(msg) {
+    return unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) };
+  }
+
+  // held for the call, as a nested message may remove the subclass
+  let subclass = unsafe { &*(data as *const Arc<dyn Subclass>) }.clone();
+  // unwinding out of an `extern "system"` function aborts the process
+  match panic::catch_unwind(AssertUnwindSafe(|| {
+    subclass.window_proc(hwnd, msg, wparam, lparam)
+  })) {
+    Ok(Some(result)) => result,
+    Ok(None) => unsafe { DefSubclassProc(hwnd, msg, wparam, lparam) },
+    Err(payload) => procedure::on_panic(hwnd, msg, payload),
+  }
+}
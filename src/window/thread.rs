@@ -0,0 +1,203 @@
+use std::{
+  cell::{Cell, RefCell},
+  collections::HashMap,
+  sync::{
+    mpsc::{sync_channel, SyncSender},
+    Arc,
+    Mutex,
+  },
+  thread::JoinHandle,
+};
+
+use windows::Win32::{
+  Foundation::{HWND, LPARAM, WPARAM},
+  System::Threading::GetCurrentThreadId,
+  UI::WindowsAndMessaging::{
+    self,
+    DispatchMessageW,
+    GetMessageW,
+    PeekMessageW,
+    PostThreadMessageW,
+    TranslateMessage,
+    MSG,
+  },
+};
+
+use super::{procedure::CreateInfo, registry::WindowClass, Window};
+use crate::error::WindowError;
+
+/*
+  Windows built with a `WindowThread` have their window procedure run on that thread
+  instead of a thread of their own. Requests to create windows are posted to the
+  thread's message queue, and the thread keeps pumping until the last handle to it
+  (including those held by its windows) is dropped.
+
+  Window classes can't be unregistered while a window of that class still exists, and
+  the windows on a shared thread are destroyed asynchronously, so the class handles of
+  those windows are kept on the thread and released right after `DestroyWindow`.
+*/
+
+const CREATE_WINDOW_ID: u32 = WindowsAndMessaging::WM_USER + 71;
+
+thread_local! {
+  static IS_SHARED: Cell<bool> = const { Cell::new(false) };
+  static CLASSES: RefCell<HashMap<isize, WindowClass>> = RefCell::new(HashMap::new());
+}
+
+struct CreateRequest {
+  create_info: CreateInfo,
+  sender: SyncSender<Result<Window, WindowError>>,
+}
+
+struct SharedThread {
+  thread_id: u32,
+  handle: Mutex<Option<JoinHandle<()>>>,
+}
+
+impl Drop for SharedThread {
+  fn drop(&mut self) {
+    if let Err(e) = unsafe {
+      PostThreadMessageW(
+        self.thread_id,
+        WindowsAndMessaging::WM_QUIT,
+        WPARAM(0),
+        LPARAM(0),
+      )
+    } {
+      tracing::error!("{e}");
+    }
+
+    // the last handle may be released by a window on the thread itself
+    if unsafe { GetCurrentThreadId() } == self.thread_id {
+      return;
+    }
+
+    if let Some(handle) = self.handle.lock().unwrap().take() {
+      tracing::trace!("joining shared window thread");
+      let _ = handle.join();
+    }
+  }
+}
+
+/// A single thread which runs the message pump for every window built with
+/// [`WindowBuilder::with_thread`](crate::WindowBuilder::with_thread).
+///
+/// Use this when opening many small windows (palettes, notifications, etc.)
+/// to avoid spawning a thread per window. Windows on the same thread are
+/// processed one message at a time, so a window whose messages aren't being
+/// taken by its iterator will hold up the rest. Windows sharing a thread
+/// should either be iterated from the same loop or use [`Flow::Poll`].
+///
+/// The thread exits once this handle and every window created on it have
+/// been dropped.
+///
+/// [`Flow::Poll`]: crate::Flow::Poll
+#[derive(Clone)]
+pub struct WindowThread(Arc<SharedThread>);
+
+impl WindowThread {
+  pub fn new() -> Result<Self, WindowError> {
+    let (id_sender, id_receiver) = sync_channel(0);
+
+    let handle = std::thread::Builder::new()
+      .name("shared window".to_owned())
+      .spawn(move || {
+        IS_SHARED.set(true);
+
+        // make sure the message queue exists before anything is posted to it
+        let mut msg = MSG::default();
+        unsafe {
+          PeekMessageW(
+            &mut msg,
+            None,
+            WindowsAndMessaging::WM_USER,
+            WindowsAndMessaging::WM_USER,
+            WindowsAndMessaging::PM_NOREMOVE,
+          )
+        };
+        id_sender
+          .send(unsafe { GetCurrentThreadId() })
+          .expect("failed to send thread id");
+
+        tracing::trace!("pumping shared window messages");
+        while unsafe { GetMessageW(&mut msg, None, 0, 0) }.as_bool() {
+          if msg.hwnd.0 == 0 && msg.message == CREATE_WINDOW_ID {
+            let request = unsafe { Box::from_raw(msg.wParam.0 as *mut CreateRequest) };
+            let window = Window::create_hwnd(request.create_info);
+            let _ = request.sender.send(window);
+            continue;
+          }
+
+          unsafe {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+          }
+        }
+        tracing::trace!("shared window thread exiting");
+      })?;
+
+    let thread_id = id_receiver
+      .recv()
+      .map_err(|e| WindowError::Error(e.to_string()))?;
+
+    Ok(Self(Arc::new(SharedThread {
+      thread_id,
+      handle: Mutex::new(Some(handle)),
+    })))
+  }
+
+  pub(crate) fn create_window(
+    &self,
+    create_info: CreateInfo,
+  ) -> Result<Window, WindowError> {
+    let (sender, receiver) = sync_channel(0);
+    let request = Box::into_raw(Box::new(CreateRequest {
+      create_info,
+      sender,
+    }));
+
+    if let Err(e) = unsafe {
+      PostThreadMessageW(
+        self.0.thread_id,
+        CREATE_WINDOW_ID,
+        WPARAM(request as usize),
+        LPARAM(0),
+      )
+    } {
+      drop(unsafe { Box::from_raw(request) });
+      return Err(e.into());
+    }
+
+    receiver
+      .recv()
+      .map_err(|e| WindowError::Error(e.to_string()))?
+  }
+}
+
+impl std::fmt::Debug for WindowThread {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_tuple("WindowThread")
+      .field(&self.0.thread_id)
+      .finish()
+  }
+}
+
+/// Keep the class handle of a window on a shared thread until it's destroyed.
+/// Returns the class back if the window has a thread of its own.
+pub(crate) fn hold_class(hwnd: HWND, class: WindowClass) -> Option<WindowClass> {
+  if !is_shared_thread() {
+    return Some(class);
+  }
+  CLASSES.with_borrow_mut(|classes| classes.insert(hwnd.0, class));
+  None
+}
+
+/// Release the class handle held for a destroyed window, if any.
+pub(crate) fn release_class(hwnd: HWND) {
+  let class = CLASSES.with_borrow_mut(|classes| classes.remove(&hwnd.0));
+  drop(class);
+}
+
+pub(crate) fn is_shared_thread() -> bool {
+  IS_SHARED.get()
+}
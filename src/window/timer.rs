@@ -0,0 +1,71 @@
+use std::{collections::HashMap, time::Duration};
+
+use windows::Win32::{
+  Foundation::HWND,
+  UI::WindowsAndMessaging::{KillTimer, SetTimer},
+};
+
+/// Timer event ids from here on belong to timers set with
+/// `Window::set_timer`, keeping them clear of the crate's own timers.
+const USER_TIMER_BASE: usize = 0x1_0000;
+
+/// Timers set with `Window::set_timer`, keyed by their `WM_TIMER` event id.
+#[derive(Debug, Default)]
+pub(crate) struct UserTimers {
+  timers: HashMap<usize, UserTimer>,
+  next_event: usize,
+}
+
+#[derive(Debug)]
+struct UserTimer {
+  id: u32,
+  repeating: bool,
+}
+
+impl UserTimers {
+  /// Start or restart the timer with the given id.
+  pub fn set(&mut self, hwnd: HWND, id: u32, interval: Duration, repeating: bool) {
+    let event = match self.event_of(id) {
+      Some(event) => event,
+      None => {
+        let event = USER_TIMER_BASE + self.next_event;
+        self.next_event += 1;
+        event
+      }
+    };
+    // USER_TIMER_MINIMUM is 10ms, shorter intervals are raised to it anyway
+    let elapse = interval.as_millis().clamp(1, u32::MAX as u128) as u32;
+    if unsafe { SetTimer(hwnd, event, elapse, None) } == 0 {
+      tracing::error!("{}", windows::core::Error::from_win32());
+      return;
+    }
+    self.timers.insert(event, UserTimer { id, repeating });
+  }
+
+  pub fn cancel(&mut self, hwnd: HWND, id: u32) {
+    if let Some(event) = self.event_of(id) {
+      self.timers.remove(&event);
+      let _ = unsafe { KillTimer(hwnd, event) };
+    }
+  }
+
+  /// Id of the user timer behind a `WM_TIMER` event, stopping it if it only
+  /// fires once. `None` for other timers.
+  pub fn fire(&mut self, hwnd: HWND, event: usize) -> Option<u32> {
+    let timer = self.timers.get(&event)?;
+    let id = timer.id;
+    if !timer.repeating {
+      self.timers.remove(&event);
+      let _ = unsafe { KillTimer(hwnd, event) };
+    }
+    Some(id)
+  }
+
+  fn event_of(&self, id: u32) -> Option<usize> {
+    self
+      .timers
+      .iter()
+      .find(|(_, timer)| timer.id == id)
+      .map(|(event, _)| *event)
+  }
+}
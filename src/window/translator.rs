@@ -0,0 +1,183 @@
+use std::{any::Any, sync::Arc};
+
+use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
+
+use super::message::Message;
+
+/// Turns raw window messages into [`Message`]s before the built-in
+/// translation runs.
+///
+/// Returning `Some` sends that message to the window's iterator instead of
+/// whatever the crate would have produced, while Windows still performs its
+/// default handling of the raw message. Returning `None` falls through to the
+/// built-in translation, so a translator only needs to handle the messages it
+/// cares about. Wrap your own event types in [`CustomMessage`] to receive them
+/// as [`Message::Custom`].
+///
+/// Translators are called on the window thread, and must not block on the
+/// window's iterator.
+///
+/// Closures with the same signature as [`MessageTranslator::translate`]
+/// implement this trait.
+pub trait MessageTranslator: Send + Sync {
+  fn translate(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> Option<Message>;
+}
+
+impl<F> MessageTranslator for F
+where
+  F: Fn(HWND, u32, WPARAM, LPARAM) -> Option<Message> + Send + Sync,
+{
+  fn translate(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> Option<Message> {
+    self(hwnd, msg, wparam, lparam)
+  }
+}
+
+impl std::fmt::Debug for dyn MessageTranslator {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("MessageTranslator")
+  }
+}
+
+/// What a [`MessageFilter`] does with a raw window message.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum FilterAction {
+  /// Let the crate handle the message as usual.
+  Pass,
+  /// Handle this message instead, as if Windows had sent it.
+  Replace {
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  },
+  /// Answer Windows with the result right away, skipping both the crate's
+  /// handling and the default handling of the message.
+  Consume(LRESULT),
+}
+
+/// Sees every raw window message before the crate does, including before any
+/// [`MessageTranslator`], and decides whether the crate handles it, a
+/// replacement, or nothing at all. Meant for messages the crate doesn't model,
+/// or handles differently than the app needs.
+///
+/// Consuming messages the crate relies on, such as `WM_SIZE` or
+/// `WM_DESTROY`, leaves its state out of date. The crate's own internal
+/// messages are never filtered.
+///
+/// Filters are called on the window thread, and must not block on the
+/// window's iterator.
+///
+/// Closures with the same signature as [`MessageFilter::filter`] implement
+/// this trait.
+pub trait MessageFilter: Send + Sync {
+  fn filter(&self, hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> FilterAction;
+}
+
+impl<F> MessageFilter for F
+where
+  F: Fn(HWND, u32, WPARAM, LPARAM) -> FilterAction + Send + Sync,
+{
+  fn filter(&self, hwnd: HWND, msg: u32, wparam: WPARAM, lparam: LPARAM) -> FilterAction {
+    self(hwnd, msg, wparam, lparam)
+  }
+}
+
+impl std::fmt::Debug for dyn MessageFilter {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("MessageFilter")
+  }
+}
+
+/// Answers raw window messages the crate doesn't handle itself, which would
+/// otherwise go straight to `DefWindowProc`, such as `WM_NCCALCSIZE` for
+/// drawing a custom frame.
+///
+/// Returning `None` leaves the message to `DefWindowProc`. Messages the crate
+/// handles never reach the replier; use a [`MessageFilter`] to answer those
+/// instead.
+///
+/// Repliers are called on the window thread, and must not block on the
+/// window's iterator.
+///
+/// Closures with the same signature as [`MessageReplier::reply`] implement
+/// this trait.
+pub trait MessageReplier: Send + Sync {
+  fn reply(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> Option<LRESULT>;
+}
+
+impl<F> MessageReplier for F
+where
+  F: Fn(HWND, u32, WPARAM, LPARAM) -> Option<LRESULT> + Send + Sync,
+{
+  fn reply(
+    &self,
+    hwnd: HWND,
+    msg: u32,
+    wparam: WPARAM,
+    lparam: LPARAM,
+  ) -> Option<LRESULT> {
+    self(hwnd, msg, wparam, lparam)
+  }
+}
+
+impl std::fmt::Debug for dyn MessageReplier {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.write_str("MessageReplier")
+  }
+}
+
+/// User-defined payload produced by a [`MessageTranslator`].
+#[derive(Clone)]
+pub struct CustomMessage {
+  value: Arc<dyn Any + Send + Sync>,
+  type_name: &'static str,
+}
+
+impl CustomMessage {
+  pub fn new<T: Any + Send + Sync>(value: T) -> Self {
+    Self {
+      value: Arc::new(value),
+      type_name: std::any::type_name::<T>(),
+    }
+  }
+
+  pub fn is<T: Any>(&self) -> bool {
+    self.value.is::<T>()
+  }
+
+  pub fn downcast_ref<T: Any>(&self) -> Option<&T> {
+    self.value.downcast_ref::<T>()
+  }
+}
+
+impl std::fmt::Debug for CustomMessage {
+  fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+    f.debug_tuple("CustomMessage")
+      .field(&self.type_name)
+      .finish()
+  }
+}
+
+/// Custom messages are only equal to clones of themselves.
+impl PartialEq for CustomMessage {
+  fn eq(&self, other: &Self) -> bool {
+    Arc::ptr_eq(&self.value, &other.value)
+  }
+}
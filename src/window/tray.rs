@@ -0,0 +1,95 @@
+use windows::Win32::{
+  Foundation::{HWND, LPARAM},
+  UI::{
+    Shell::{self, Shell_NotifyIconW, NOTIFYICONDATAW},
+    WindowsAndMessaging::{self, GetSystemMetrics, LoadIconW, HICON},
+  },
+};
+
+use super::{icon::Icon, input::mouse::MouseButton};
+use crate::utilities::lo_word;
+
+/// Whether the window hides itself to the notification area (tray) instead of
+/// minimizing or closing. While in the tray, the window is hidden and clicking
+/// its tray icon sends [`Message::TrayIcon`](crate::Message::TrayIcon).
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TrayBehavior {
+  /// Minimizing and closing behave normally.
+  #[default]
+  Never,
+  /// Minimizing hides the window to the tray.
+  OnMinimize,
+  /// Pressing the X button hides the window to the tray instead of sending
+  /// [`Message::CloseRequested`](crate::Message::CloseRequested).
+  OnClose,
+  /// Both minimizing and closing hide the window to the tray.
+  OnMinimizeOrClose,
+}
+
+impl TrayBehavior {
+  pub fn on_minimize(&self) -> bool {
+    matches!(self, Self::OnMinimize | Self::OnMinimizeOrClose)
+  }
+
+  pub fn on_close(&self) -> bool {
+    matches!(self, Self::OnClose | Self::OnMinimizeOrClose)
+  }
+}
+
+/// Callback message sent by the shell for tray icon interactions.
+pub(crate) const TRAY_MESSAGE_ID: u32 = WindowsAndMessaging::WM_USER + 70;
+const TRAY_ICON_ID: u32 = 1;
+
+fn notify_icon_data(hwnd: HWND) -> NOTIFYICONDATAW {
+  NOTIFYICONDATAW {
+    cbSize: std::mem::size_of::<NOTIFYICONDATAW>() as u32,
+    hWnd: hwnd,
+    uID: TRAY_ICON_ID,
+    ..Default::default()
+  }
+}
+
+/// Add the window's icon to the notification area, using the default
+/// application icon if the window has none.
+pub(crate) fn add_tray_icon(hwnd: HWND, icon: Option<&Icon>, tooltip: &str) -> bool {
+  let hicon = match icon {
+    Some(icon) => {
+      let size = unsafe { GetSystemMetrics(WindowsAndMessaging::SM_CXSMICON) };
+      icon.hicon(size.max(1) as u32)
+    }
+    None => unsafe { LoadIconW(None, WindowsAndMessaging::IDI_APPLICATION) }
+      .unwrap_or(HICON::default()),
+  };
+
+  let mut data = notify_icon_data(hwnd);
+  data.uFlags = Shell::NIF_MESSAGE | Shell::NIF_ICON | Shell::NIF_TIP;
+  data.uCallbackMessage = TRAY_MESSAGE_ID;
+  data.hIcon = hicon;
+  // leave room for the null terminator
+  for (dst, src) in data.szTip[..127].iter_mut().zip(tooltip.encode_utf16()) {
+    *dst = src;
+  }
+
+  unsafe { Shell_NotifyIconW(Shell::NIM_ADD, &data) }.as_bool()
+}
+
+/// Decode the button and whether it was a double click from the `lparam` of a
+/// [`TRAY_MESSAGE_ID`] callback. Returns `None` for anything but button releases
+/// and double clicks.
+pub(crate) fn tray_click(lparam: LPARAM) -> Option<(MouseButton, bool)> {
+  match lo_word(lparam.0 as u32) as u32 {
+    WindowsAndMessaging::WM_LBUTTONUP => Some((MouseButton::Left, false)),
+    WindowsAndMessaging::WM_LBUTTONDBLCLK => Some((MouseButton::Left, true)),
+    WindowsAndMessaging::WM_RBUTTONUP => Some((MouseButton::Right, false)),
+    WindowsAndMessaging::WM_RBUTTONDBLCLK => Some((MouseButton::Right, true)),
+    WindowsAndMessaging::WM_MBUTTONUP => Some((MouseButton::Middle, false)),
+    WindowsAndMessaging::WM_MBUTTONDBLCLK => Some((MouseButton::Middle, true)),
+    _ => None,
+  }
+}
+
+pub(crate) fn remove_tray_icon(hwnd: HWND) -> bool {
+  let data = notify_icon_data(hwnd);
+  unsafe { Shell_NotifyIconW(Shell::NIM_DELETE, &data) }.as_bool()
+}
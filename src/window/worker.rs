@@ -0,0 +1,335 @@
+use std::{
+  sync::{mpsc::sync_channel, Arc, Mutex},
+  thread::JoinHandle,
+};
+
+use windows::{
+  core::HSTRING,
+  Win32::{
+    Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM},
+    System::{
+      DataExchange::{AddClipboardFormatListener, RemoveClipboardFormatListener},
+      LibraryLoader::GetModuleHandleW,
+    },
+    UI::{
+      Input::KeyboardAndMouse::{self, RegisterHotKey, UnregisterHotKey, VIRTUAL_KEY},
+      WindowsAndMessaging::{
+        self,
+        CreateWindowExW,
+        DefWindowProcW,
+        DestroyWindow,
+        DispatchMessageW,
+        GetMessageW,
+        GetWindowLongPtrW,
+        PostQuitMessage,
+        SendMessageW,
+        SetWindowLongPtrW,
+        TranslateMessage,
+        MSG,
+      },
+    },
+  },
+};
+
+use super::{
+  broadcast::{Broadcaster, Receiver},
+  icon::Icon,
+  input::{key::Key, state::Modifiers},
+  message::Message,
+  registry::WindowClass,
+  tray::{self, add_tray_icon, remove_tray_icon},
+  Window,
+};
+use crate::error::WindowError;
+
+/*
+  The worker owns a message-only window on a thread of its own. Hotkeys, clipboard
+  listeners, and tray icons are all tied to a window, so hosting them here lets them
+  outlive any visible window. Registration has to happen on the thread that owns the
+  window, so requests are sent to it as closures. This is safe to do synchronously
+  because, unlike window threads, the worker thread never waits on the main thread.
+*/
+
+const CALL_ID: u32 = WindowsAndMessaging::WM_USER + 72;
+const CLASS_NAME: &str = "witer background worker";
+
+type Call = Box<dyn FnOnce(HWND, &WorkerState) + Send>;
+
+#[derive(Default)]
+struct WorkerState {
+  broadcaster: Broadcaster,
+  tray_icon: Mutex<Option<Option<Icon>>>,
+  clipboard_listener: Mutex<bool>,
+}
+
+/// Hidden, message-only window for global integrations which should keep
+/// working while no visible window exists, such as an app which closes to the
+/// tray and recreates its UI later.
+///
+/// Events are delivered through [`Receiver`]s created with
+/// [`BackgroundWorker::subscribe`]:
+/// - [`Message::Hotkey`] for hotkeys added with
+///   [`BackgroundWorker::register_hotkey`]
+/// - [`Message::ClipboardUpdated`] while
+///   [`BackgroundWorker::set_clipboard_listener`] is enabled
+/// - [`Message::TrayIcon`] for clicks on the tray icon shown with
+///   [`BackgroundWorker::show_tray_icon`]
+///
+/// Everything registered through the worker is removed when it is dropped.
+pub struct BackgroundWorker {
+  hwnd: HWND,
+  state: Arc<WorkerState>,
+  thread: Option<JoinHandle<()>>,
+}
+
+impl BackgroundWorker {
+  pub fn new() -> Result<Self, WindowError> {
+    let (sender, receiver) = sync_channel(0);
+
+    let thread = std::thread::Builder::new()
+      .name("background worker".to_owned())
+      .spawn(move || {
+        let state = Arc::new(WorkerState::default());
+        let created = Self::create_hwnd(state.clone());
+        let class = match created {
+          Ok((hwnd, class)) => {
+            sender
+              .send(Ok((hwnd.0, state)))
+              .expect("failed to send worker window");
+            class
+          }
+          Err(e) => {
+            let _ = sender.send(Err(e));
+            return;
+          }
+        };
+
+        let mut msg = MSG::default();
+        while unsafe { GetMessageW(&mut msg, None, 0, 0) }.as_bool() {
+          unsafe {
+            TranslateMessage(&msg);
+            DispatchMessageW(&msg);
+          }
+        }
+
+        // the window is gone by now, so the class can be unregistered
+        drop(class);
+      })?;
+
+    let (hwnd, state) = receiver
+      .recv()
+      .map_err(|e| WindowError::Error(e.to_string()))??;
+
+    Ok(Self {
+      hwnd: HWND(hwnd),
+      state,
+      thread: Some(thread),
+    })
+  }
+
+  fn create_hwnd(state: Arc<WorkerState>) -> Result<(HWND, WindowClass), WindowError> {
+    let hinstance: HINSTANCE = unsafe { GetModuleHandleW(None)? }.into();
+    let class = WindowClass::acquire(CLASS_NAME, hinstance, Some(worker_proc))?;
+
+    let hwnd = unsafe {
+      CreateWindowExW(
+        Default::default(),
+        &HSTRING::from(CLASS_NAME),
+        &HSTRING::from(CLASS_NAME),
+        Default::default(),
+        0,
+        0,
+        0,
+        0,
+        WindowsAndMessaging::HWND_MESSAGE,
+        None,
+        hinstance,
+        None,
+      )
+    };
+    if hwnd.0 == 0 {
+      return Err(WindowError::Win32Error(windows::core::Error::from_win32()));
+    }
+
+    let state_ptr = Arc::into_raw(state);
+    unsafe {
+      SetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_USERDATA, state_ptr as isize)
+    };
+
+    Ok((hwnd, class))
+  }
+
+  /// Run `f` on the worker thread and wait for its result.
+  fn call<R: Send + 'static>(
+    &self,
+    f: impl FnOnce(HWND, &WorkerState) -> R + Send + 'static,
+  ) -> R {
+    let (sender, receiver) = sync_channel(1);
+    let call: Call = Box::new(move |hwnd, state| {
+      let _ = sender.send(f(hwnd, state));
+    });
+    let call = Box::into_raw(Box::new(call));
+    unsafe { SendMessageW(self.hwnd, CALL_ID, WPARAM(call as usize), LPARAM(0)) };
+    receiver.recv().expect("worker thread exited")
+  }
+
+  /// Receive the worker's messages. See [`Window::subscribe`].
+  pub fn subscribe(&self) -> Receiver {
+    self.subscribe_with_capacity(Window::SUBSCRIBER_CAPACITY)
+  }
+
+  pub fn subscribe_with_capacity(&self, capacity: usize) -> Receiver {
+    self.state.broadcaster.subscribe(capacity)
+  }
+
+  /// Register a system-wide hotkey. Pressing it sends [`Message::Hotkey`]
+  /// with `id`, which must be between `0` and `0xBFFF`. Fails if another
+  /// application has already registered the same combination.
+  pub fn register_hotkey(
+    &self,
+    id: i32,
+    modifiers: Modifiers,
+    key: Key,
+  ) -> Result<(), WindowError> {
+    let mut flags = KeyboardAndMouse::MOD_NOREPEAT;
+    if modifiers.shift.is_pressed() {
+      flags |= KeyboardAndMouse::MOD_SHIFT;
+    }
+    if modifiers.ctrl.is_pressed() {
+      flags |= KeyboardAndMouse::MOD_CONTROL;
+    }
+    if modifiers.alt.is_pressed() {
+      flags |= KeyboardAndMouse::MOD_ALT;
+    }
+    if modifiers.win.is_pressed() {
+      flags |= KeyboardAndMouse::MOD_WIN;
+    }
+    let vk = VIRTUAL_KEY::from(key).0 as u32;
+
+    self
+      .call(move |hwnd, _| unsafe { RegisterHotKey(hwnd, id, flags, vk) })
+      .map_err(Into::into)
+  }
+
+  pub fn unregister_hotkey(&self, id: i32) -> Result<(), WindowError> {
+    self
+      .call(move |hwnd, _| unsafe { UnregisterHotKey(hwnd, id) })
+      .map_err(Into::into)
+  }
+
+  /// Start or stop sending [`Message::ClipboardUpdated`] whenever the
+  /// contents of the clipboard change.
+  pub fn set_clipboard_listener(&self, enabled: bool) -> Result<(), WindowError> {
+    self.call(move |hwnd, state| {
+      let mut listening = state.clipboard_listener.lock().unwrap();
+      if *listening == enabled {
+        return Ok(());
+      }
+      if enabled {
+        unsafe { AddClipboardFormatListener(hwnd) }?;
+      } else {
+        unsafe { RemoveClipboardFormatListener(hwnd) }?;
+      }
+      *listening = enabled;
+      Ok(())
+    })
+  }
+
+  /// Show an icon in the notification area, replacing any icon previously
+  /// shown by the worker. Uses the default application icon if `icon` is
+  /// `None`.
+  pub fn show_tray_icon(&self, icon: Option<Icon>, tooltip: impl Into<String>) {
+    let tooltip = tooltip.into();
+    self.call(move |hwnd, state| {
+      let mut tray_icon = state.tray_icon.lock().unwrap();
+      if tray_icon.is_some() {
+        remove_tray_icon(hwnd);
+      }
+      if add_tray_icon(hwnd, icon.as_ref(), &tooltip) {
+        *tray_icon = Some(icon);
+      } else {
+        tracing::error!("failed to add tray icon");
+        *tray_icon = None;
+      }
+    })
+  }
+
+  pub fn hide_tray_icon(&self) {
+    self.call(move |hwnd, state| {
+      if state.tray_icon.lock().unwrap().take().is_some() {
+        remove_tray_icon(hwnd);
+      }
+    })
+  }
+}
+
+impl Drop for BackgroundWorker {
+  fn drop(&mut self) {
+    self.call(|hwnd, _| {
+      if let Err(e) = unsafe { DestroyWindow(hwnd) } {
+        tracing::error!("{e}");
+      }
+    });
+    if let Some(thread) = self.thread.take() {
+      let _ = thread.join();
+    }
+    self.state.broadcaster.disconnect();
+  }
+}
+
+extern "system" fn worker_proc(
+  hwnd: HWND,
+  msg: u32,
+  wparam: WPARAM,
+  lparam: LPARAM,
+) -> LRESULT {
+  let state_ptr = unsafe { GetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_USERDATA) }
+    as *const WorkerState;
+  let Some(state) = (unsafe { state_ptr.as_ref() }) else {
+    return unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) };
+  };
+
+  match msg {
+    CALL_ID => {
+      let call = unsafe { Box::from_raw(wparam.0 as *mut Call) };
+      call(hwnd, state);
+      LRESULT(0)
+    }
+    WindowsAndMessaging::WM_HOTKEY => {
+      state.broadcaster.send(&Message::Hotkey {
+        id: wparam.0 as i32,
+      });
+      LRESULT(0)
+    }
+    WindowsAndMessaging::WM_CLIPBOARDUPDATE => {
+      state.broadcaster.send(&Message::ClipboardUpdated);
+      LRESULT(0)
+    }
+    tray::TRAY_MESSAGE_ID => {
+      let Some((button, is_double_click)) = tray::tray_click(lparam) else {
+        return LRESULT(0);
+      };
+      state.broadcaster.send(&Message::TrayIcon {
+        button,
+        is_double_click,
+      });
+      LRESULT(0)
+    }
+    WindowsAndMessaging::WM_DESTROY => {
+      if state.tray_icon.lock().unwrap().take().is_some() {
+        remove_tray_icon(hwnd);
+      }
+      if *state.clipboard_listener.lock().unwrap() {
+        let _ = unsafe { RemoveClipboardFormatListener(hwnd) };
+      }
+      unsafe { PostQuitMessage(0) };
+      LRESULT(0)
+    }
+    WindowsAndMessaging::WM_NCDESTROY => {
+      unsafe { SetWindowLongPtrW(hwnd, WindowsAndMessaging::GWLP_USERDATA, 0) };
+      drop(unsafe { Arc::from_raw(state_ptr) });
+      unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) }
+    }
+    _ => unsafe { DefWindowProcW(hwnd, msg, wparam, lparam) },
+  }
+}